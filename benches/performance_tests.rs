@@ -100,7 +100,7 @@ fn benchmark_server_operations(c: &mut Criterion) {
     let manager = PhpServerManager::new();
 
     c.bench_function("find_available_port", |b| {
-        b.iter(|| black_box(manager.find_available_port(8000)))
+        b.iter(|| black_box(manager.find_available_port(8000..=65535, &[])))
     });
 
     c.bench_function("list_running_servers", |b| {
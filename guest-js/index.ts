@@ -1,4 +1,5 @@
 import { invoke } from '@tauri-apps/api/core'
+import { listen, type UnlistenFn } from '@tauri-apps/api/event'
 
 export interface PhpBinaryInfo {
   version: string
@@ -8,6 +9,14 @@ export interface PhpBinaryInfo {
   size?: number
 }
 
+export interface ComposerInfo {
+  version: string
+  path: string
+  is_installed: boolean
+  download_url?: string
+  size?: number
+}
+
 export interface ServerStatus {
   is_running: boolean
   pid?: number
@@ -15,15 +24,220 @@ export interface ServerStatus {
   host?: string
   document_root?: string
   started_at?: string
+  https: boolean
+  project_path?: string
+  backend?: ServerBackend
 }
 
 export interface ProjectInfo {
   name: string
   path: string
   framework?: Framework
+  framework_version?: FrameworkVersion
   git_url?: string
   php_version?: string
   entry_point?: string
+  id?: string
+  settings?: ProjectSettings
+  setup?: SetupReport
+}
+
+export interface DatabaseConfig {
+  connection: string
+  host?: string
+  port?: number
+  database?: string
+  username?: string
+  password?: string
+}
+
+export interface LaravelSetupOptions {
+  migrate?: boolean
+  seed?: boolean
+  database?: DatabaseConfig
+  storage_link?: boolean
+  clear_caches?: boolean
+}
+
+export interface SetupProjectRequest {
+  project_path: string
+  laravel?: LaravelSetupOptions
+  dry_run?: boolean
+}
+
+export enum SetupStepStatus {
+  Succeeded = 'Succeeded',
+  Skipped = 'Skipped',
+  Failed = 'Failed',
+  Planned = 'Planned'
+}
+
+export interface SetupStep {
+  name: string
+  status: SetupStepStatus
+  message?: string
+  output: string
+}
+
+export interface SetupReport {
+  framework: Framework
+  framework_version?: FrameworkVersion
+  steps: SetupStep[]
+  dry_run: boolean
+}
+
+export interface ProjectSettings {
+  display_name?: string
+  php_version?: string
+  port?: number
+  document_root?: string
+  env?: Record<string, string>
+  composer?: ComposerSettings
+}
+
+export interface ComposerSettings {
+  dev?: boolean
+  extra_args?: string[]
+}
+
+export enum ComposerAction {
+  Install = 'Install',
+  Update = 'Update',
+  Require = 'Require',
+  Remove = 'Remove',
+  DumpAutoload = 'DumpAutoload'
+}
+
+export interface ComposerRequest {
+  project_path: string
+  action: ComposerAction
+  packages?: string[]
+  dev?: boolean
+  extra_args?: string[]
+  run_id?: string
+}
+
+export interface ComposerOutput {
+  run_id: string
+  stream: 'Stdout' | 'Stderr'
+  line: string
+}
+
+export interface ComposerProblem {
+  number?: number
+  messages: string[]
+  missing_extensions: string[]
+}
+
+export interface ComposerResult {
+  run_id: string
+  action: ComposerAction
+  exit_code?: number
+  success: boolean
+  duration_ms: number
+  output: string
+  problems: ComposerProblem[]
+}
+
+export interface FrameworkCommandRequest {
+  project_path: string
+  args: string[]
+  env?: Record<string, string>
+  run_id?: string
+}
+
+export interface FrameworkCommandOutput {
+  run_id: string
+  stream: 'Stdout' | 'Stderr'
+  line: string
+}
+
+export interface FrameworkCommandResult {
+  run_id: string
+  framework: Framework
+  script: string
+  args: string[]
+  exit_code?: number
+  success: boolean
+  duration_ms: number
+  output: string
+}
+
+export interface ConsoleArgument {
+  name: string
+  description: string
+  required: boolean
+  is_array: boolean
+}
+
+export interface ConsoleOption {
+  name: string
+  shortcut?: string
+  description: string
+  accepts_value: boolean
+}
+
+export interface ConsoleCommand {
+  name: string
+  description: string
+  arguments: ConsoleArgument[]
+  options: ConsoleOption[]
+}
+
+export interface ConsoleCommandList {
+  framework: Framework
+  script: string
+  commands: ConsoleCommand[]
+  lock_hash?: string
+  cached: boolean
+}
+
+export enum DependencyStatus {
+  UpToDate = 'UpToDate',
+  Outdated = 'Outdated',
+  NotLocked = 'NotLocked',
+  Unknown = 'Unknown'
+}
+
+export interface DependencyInfo {
+  name: string
+  constraint: string
+  dev: boolean
+  locked_version?: string
+  status: DependencyStatus
+}
+
+export interface PlatformRequirement {
+  name: string
+  constraint: string
+  dev: boolean
+  required_by: string[]
+}
+
+export interface ProjectDependencies {
+  project_path: string
+  has_lock_file: boolean
+  lock_outdated: boolean
+  packages: DependencyInfo[]
+  extensions: PlatformRequirement[]
+  platform: PlatformRequirement[]
+}
+
+export interface RemoveProjectOptions {
+  force?: boolean
+  trash?: boolean
+}
+
+export interface TrashedProject {
+  trash_id: string
+  name: string
+  original_path: string
+  trashed_at: number
+}
+
+export interface RemoveProjectResult {
+  stopped_servers: string[]
+  trashed?: TrashedProject
 }
 
 export enum Framework {
@@ -41,12 +255,76 @@ export enum Framework {
   Unknown = 'Unknown'
 }
 
+export enum EvidenceKind {
+  LockedPackage = 'LockedPackage',
+  RequiredPackage = 'RequiredPackage',
+  File = 'File',
+  FileContent = 'FileContent'
+}
+
+export interface DetectionEvidence {
+  kind: EvidenceKind
+  source: string
+  weight: number
+}
+
+export enum VersionSource {
+  LockFile = 'LockFile',
+  VersionConstant = 'VersionConstant',
+  Layout = 'Layout'
+}
+
+export interface FrameworkVersion {
+  major: number
+  /** Unknown when only the directory layout told the version */
+  minor?: number
+  source: VersionSource
+}
+
+export interface FrameworkCandidate {
+  framework: Framework
+  score: number
+  confidence: number
+  evidence: DetectionEvidence[]
+}
+
 export interface StartServerRequest {
   project_path: string
   port?: number
   host?: string
   php_version?: string
   document_root?: string
+  https?: boolean
+  backend?: ServerBackend
+  fastcgi?: FastCgiOptions
+  project_id?: string
+  env?: Record<string, string>
+  require_extensions?: boolean
+}
+
+export interface RequiredExtension {
+  name: string
+  required_by: string[]
+  dev: boolean
+}
+
+export interface ExtensionReport {
+  php_executable: string
+  loaded: string[]
+  required: RequiredExtension[]
+  missing: RequiredExtension[]
+}
+
+export enum ServerBackend {
+  BuiltIn = 'BuiltIn',
+  PhpCgi = 'PhpCgi',
+  PhpFpm = 'PhpFpm'
+}
+
+export interface FastCgiOptions {
+  workers?: number
+  max_requests_per_worker?: number
+  request_timeout_secs?: number
 }
 
 export interface CloneProjectRequest {
@@ -54,6 +332,104 @@ export interface CloneProjectRequest {
   destination?: string
   branch?: string
   auto_setup: boolean
+  clone_id?: string
+  credentials?: GitCredentials
+  tag?: string
+  commit?: string
+  depth?: number
+  single_branch?: boolean
+  recurse_submodules?: boolean
+}
+
+export interface CreateProjectRequest {
+  name: string
+  framework: Framework
+  destination?: string
+  version?: string
+  template_url?: string
+  auto_setup?: boolean
+}
+
+export interface ProjectTemplate {
+  framework: Framework
+  composer_package?: string
+  git_url?: string
+  bundled: boolean
+}
+
+/**
+ * Clone/fetch authentication. Passphrases and tokens are referenced by the id
+ * they were saved under with `setSecret`, never sent inline.
+ */
+export type GitCredentials =
+  | { type: 'SshAgent'; username?: string }
+  | {
+      type: 'SshKey'
+      username?: string
+      private_key_path: string
+      public_key_path?: string
+      passphrase_secret_id?: string
+    }
+  | { type: 'Token'; username?: string; token_secret_id: string }
+  | { type: 'CredentialHelper' }
+
+export enum CloneStage {
+  Receiving = 'Receiving',
+  Resolving = 'Resolving',
+  CheckingOut = 'CheckingOut',
+  Submodules = 'Submodules',
+  Done = 'Done'
+}
+
+export interface CloneProgress {
+  clone_id: string
+  stage: CloneStage
+  received_objects: number
+  indexed_objects: number
+  total_objects: number
+  local_objects: number
+  received_bytes: number
+  indexed_deltas: number
+  total_deltas: number
+  checkout_completed: number
+  checkout_total: number
+  submodules_completed: number
+  submodules_total: number
+}
+
+export interface GitStatus {
+  branch?: string
+  head_commit?: string
+  detached: boolean
+  upstream?: string
+  remote_url?: string
+  ahead: number
+  behind: number
+  is_dirty: boolean
+  files: GitFileStatus[]
+}
+
+export interface GitFileStatus {
+  path: string
+  index?: GitChange
+  worktree?: GitChange
+}
+
+export enum GitChange {
+  Added = 'Added',
+  Modified = 'Modified',
+  Deleted = 'Deleted',
+  Renamed = 'Renamed',
+  TypeChange = 'TypeChange',
+  Untracked = 'Untracked',
+  Conflicted = 'Conflicted'
+}
+
+export interface GitPullResult {
+  updated: boolean
+  previous_commit?: string
+  current_commit?: string
+  status: GitStatus
 }
 
 export interface Config {
@@ -76,7 +452,9 @@ export interface FrameworkInfo {
   default_entry_point: string
   requires_composer: boolean
   setup_commands: string[]
+  required_extensions: string[]
   default_port: number
+  console_script?: string
 }
 
 /**
@@ -114,6 +492,72 @@ export async function cloneProject(request: CloneProjectRequest): Promise<Projec
   return await invoke('plugin:php|clone_project', { request })
 }
 
+/**
+ * Listens for clone progress, optionally only for one clone id
+ */
+export async function onCloneProgress(
+  handler: (progress: CloneProgress) => void,
+  cloneId?: string
+): Promise<UnlistenFn> {
+  return await listen<CloneProgress>('php://clone-progress', (event) => {
+    if (!cloneId || event.payload.clone_id === cloneId) {
+      handler(event.payload)
+    }
+  })
+}
+
+/**
+ * Creates a project from the framework's starter template: Composer
+ * `create-project` when Composer is available, otherwise the template's git
+ * repository or the skeleton bundled with the plugin
+ */
+export async function createProject(request: CreateProjectRequest): Promise<ProjectInfo> {
+  return await invoke('plugin:php|create_project', { request })
+}
+
+/**
+ * Lists the starter templates available to `createProject`
+ */
+export async function listProjectTemplates(): Promise<ProjectTemplate[]> {
+  return await invoke('plugin:php|list_project_templates')
+}
+
+/**
+ * Runs the framework's setup in an existing project, returning what each step
+ * did, skipped or failed
+ */
+export async function setupProject(request: SetupProjectRequest): Promise<SetupReport> {
+  return await invoke('plugin:php|setup_project', { request })
+}
+
+/**
+ * Cancels a running clone and removes its partially written directory
+ */
+export async function cancelClone(cloneId: string): Promise<boolean> {
+  return await invoke('plugin:php|cancel_clone', { cloneId })
+}
+
+/**
+ * Saves a passphrase or token in the plugin's secret store
+ */
+export async function setSecret(secretId: string, secret: string): Promise<void> {
+  return await invoke('plugin:php|set_secret', { secretId, secret })
+}
+
+/**
+ * Deletes a stored secret, resolving to false when it did not exist
+ */
+export async function deleteSecret(secretId: string): Promise<boolean> {
+  return await invoke('plugin:php|delete_secret', { secretId })
+}
+
+/**
+ * Checks whether a secret is stored; secrets themselves cannot be read back
+ */
+export async function hasSecret(secretId: string): Promise<boolean> {
+  return await invoke('plugin:php|has_secret', { secretId })
+}
+
 /**
  * Detects the framework used in a project
  */
@@ -121,6 +565,14 @@ export async function detectFramework(projectPath: string): Promise<Framework> {
   return await invoke('plugin:php|detect_framework', { projectPath })
 }
 
+/**
+ * Scores every framework against a project, best match first, with the
+ * packages and files that count for each
+ */
+export async function rankFrameworks(projectPath: string): Promise<FrameworkCandidate[]> {
+  return await invoke('plugin:php|rank_frameworks', { projectPath })
+}
+
 /**
  * Gets the PHP version information
  */
@@ -128,6 +580,21 @@ export async function getPhpVersion(version: string): Promise<string> {
   return await invoke('plugin:php|get_php_version', { version })
 }
 
+/**
+ * Lists the extensions loaded by an installed PHP version (`php -m`)
+ */
+export async function getPhpExtensions(version: string): Promise<string[]> {
+  return await invoke('plugin:php|get_php_extensions', { version })
+}
+
+/**
+ * Compares the extensions a project needs (composer.json, locked packages and
+ * its framework) with those of the PHP binary its server would use
+ */
+export async function checkPhpExtensions(projectPath: string, phpVersion?: string): Promise<ExtensionReport> {
+  return await invoke('plugin:php|check_php_extensions', { projectPath, phpVersion })
+}
+
 /**
  * Lists all available PHP versions
  */
@@ -136,14 +603,14 @@ export async function listAvailablePhpVersions(): Promise<PhpBinaryInfo[]> {
 }
 
 /**
- * Sets the plugin configuration
+ * Saves the plugin configuration and applies it to servers started afterwards
  */
 export async function setPhpConfig(config: Config): Promise<void> {
   return await invoke('plugin:php|set_php_config', { config })
 }
 
 /**
- * Gets the plugin configuration
+ * Gets the configuration in effect
  */
 export async function getPhpConfig(): Promise<Config> {
   return await invoke('plugin:php|get_php_config')
@@ -164,10 +631,88 @@ export async function listProjects(): Promise<ProjectInfo[]> {
 }
 
 /**
- * Removes a project
+ * Registers an existing directory as a project without moving it
+ */
+export async function importProject(path: string, name?: string): Promise<ProjectInfo> {
+  return await invoke('plugin:php|import_project', { path, name })
+}
+
+/**
+ * Gets a registered project by its id
+ */
+export async function getProject(projectId: string): Promise<ProjectInfo> {
+  return await invoke('plugin:php|get_project', { projectId })
+}
+
+/**
+ * Gets the saved settings of a project
+ */
+export async function getProjectSettings(projectId: string): Promise<ProjectSettings> {
+  return await invoke('plugin:php|get_project_settings', { projectId })
+}
+
+/**
+ * Saves the settings of a project, used as defaults when its server starts
  */
-export async function removeProject(projectName: string): Promise<void> {
-  return await invoke('plugin:php|remove_project', { projectName })
+export async function updateProjectSettings(projectId: string, settings: ProjectSettings): Promise<ProjectInfo> {
+  return await invoke('plugin:php|update_project_settings', { projectId, settings })
+}
+
+/**
+ * Removes a project from the projects directory. Fails while a managed server
+ * uses it unless `force` is set; `trash` keeps the folder restorable.
+ */
+export async function removeProject(projectName: string, options?: RemoveProjectOptions): Promise<RemoveProjectResult> {
+  return await invoke('plugin:php|remove_project', { projectName, options })
+}
+
+/**
+ * Lists the projects in the plugin's trash
+ */
+export async function listTrash(): Promise<TrashedProject[]> {
+  return await invoke('plugin:php|list_trash')
+}
+
+/**
+ * Moves a trashed project back into the projects directory
+ */
+export async function restoreProject(trashId: string, name?: string): Promise<ProjectInfo> {
+  return await invoke('plugin:php|restore_project', { trashId, name })
+}
+
+/**
+ * Deletes one trashed project, or all of them, for good
+ */
+export async function purgeTrash(trashId?: string): Promise<number> {
+  return await invoke('plugin:php|purge_trash', { trashId })
+}
+
+/**
+ * Gets the branch, upstream divergence and changed files of a project's repository
+ */
+export async function getGitStatus(projectPath: string): Promise<GitStatus> {
+  return await invoke('plugin:php|get_git_status', { projectPath })
+}
+
+/**
+ * Fetches the project's upstream remote and returns the refreshed status
+ */
+export async function fetchProject(projectPath: string, credentials?: GitCredentials): Promise<GitStatus> {
+  return await invoke('plugin:php|fetch_project', { projectPath, credentials })
+}
+
+/**
+ * Fetches and fast-forwards the current branch; fails when it has diverged
+ */
+export async function pullProject(projectPath: string, credentials?: GitCredentials): Promise<GitPullResult> {
+  return await invoke('plugin:php|pull_project', { projectPath, credentials })
+}
+
+/**
+ * Checks out a branch, creating a tracking branch when it only exists on the remote
+ */
+export async function switchBranch(projectPath: string, branch: string): Promise<GitStatus> {
+  return await invoke('plugin:php|switch_branch', { projectPath, branch })
 }
 
 /**
@@ -177,6 +722,100 @@ export async function removePhpBinary(version: string): Promise<void> {
   return await invoke('plugin:php|remove_php_binary', { version })
 }
 
+/**
+ * Downloads composer.phar into the plugin's data directory, the latest stable
+ * release when no version is given
+ */
+export async function downloadComposer(version?: string): Promise<ComposerInfo> {
+  return await invoke('plugin:php|download_composer', { version })
+}
+
+/**
+ * Installs a composer.phar shipped with the application under the given version
+ */
+export async function installComposerFromFile(version: string, path: string): Promise<ComposerInfo> {
+  return await invoke('plugin:php|install_composer_from_file', { version, path })
+}
+
+/**
+ * Lists the installed Composer versions, newest first
+ */
+export async function listComposerVersions(): Promise<ComposerInfo[]> {
+  return await invoke('plugin:php|list_composer_versions')
+}
+
+/**
+ * Removes an installed Composer version
+ */
+export async function removeComposer(version: string): Promise<void> {
+  return await invoke('plugin:php|remove_composer', { version })
+}
+
+/**
+ * Runs a Composer action in a project with the project's PHP. A failing run
+ * resolves with `success: false` and the problems Composer reported
+ */
+export async function runComposer(request: ComposerRequest): Promise<ComposerResult> {
+  return await invoke('plugin:php|run_composer', { request })
+}
+
+/**
+ * Listens for Composer output lines, optionally only for one run id
+ */
+export async function onComposerOutput(
+  handler: (output: ComposerOutput) => void,
+  runId?: string
+): Promise<UnlistenFn> {
+  return await listen<ComposerOutput>('php://composer-output', (event) => {
+    if (!runId || event.payload.run_id === runId) {
+      handler(event.payload)
+    }
+  })
+}
+
+/**
+ * Runs a command of the project's framework console (artisan, bin/console,
+ * think, spark, bin/cake.php or yii) with the project's PHP. A failing command
+ * resolves with `success: false`; a cancelled one rejects
+ */
+export async function runFrameworkCommand(
+  request: FrameworkCommandRequest
+): Promise<FrameworkCommandResult> {
+  return await invoke('plugin:php|run_framework_command', { request })
+}
+
+/**
+ * Lists the commands the project's framework console offers. Lists are cached
+ * until composer.lock changes; `refresh` queries the console again
+ */
+export async function listFrameworkCommands(
+  projectPath: string,
+  refresh?: boolean
+): Promise<ConsoleCommandList> {
+  return await invoke('plugin:php|list_framework_commands', { projectPath, refresh })
+}
+
+/**
+ * Kills a running framework command
+ */
+export async function cancelFrameworkCommand(runId: string): Promise<boolean> {
+  return await invoke('plugin:php|cancel_framework_command', { runId })
+}
+
+/**
+ * Listens for framework command output lines, optionally only for one run id
+ */
+export async function onFrameworkCommandOutput(
+  handler: (output: FrameworkCommandOutput) => void,
+  runId?: string
+): Promise<UnlistenFn> {
+  return await listen<FrameworkCommandOutput>('php://framework-command-output', (event) => {
+    if (!runId || event.payload.run_id === runId) {
+      handler(event.payload)
+    }
+  })
+}
+
 /**
  * Gets server logs
  */
@@ -184,11 +823,63 @@ export async function getServerLogs(serverId: string): Promise<string> {
   return await invoke('plugin:php|get_server_logs', { serverId })
 }
 
+export interface FindPortOptions {
+  endPort?: number
+  exclude?: number[]
+}
+
+/**
+ * Finds and reserves an available port in the range startPort..=endPort
+ */
+export async function findAvailablePort(startPort: number, options?: FindPortOptions): Promise<number> {
+  return await invoke('plugin:php|find_available_port', {
+    startPort,
+    endPort: options?.endPort,
+    exclude: options?.exclude
+  })
+}
+
+/**
+ * Gets the PEM of the local CA that signs certificates for HTTPS servers
+ */
+export async function getHttpsCaCertificate(): Promise<string> {
+  return await invoke('plugin:php|get_https_ca_certificate')
+}
+
+export interface GatewayRoute {
+  project: string
+  server_id: string
+  host_url: string
+  path_url: string
+  upstream_port?: number
+}
+
+export interface GatewayStatus {
+  is_running: boolean
+  host?: string
+  port?: number
+  routes: GatewayRoute[]
+}
+
+/**
+ * Starts the gateway that serves every running project under one port
+ */
+export async function startGateway(port?: number, host?: string): Promise<GatewayStatus> {
+  return await invoke('plugin:php|start_gateway', { port, host })
+}
+
 /**
- * Finds an available port starting from the specified port
+ * Stops the gateway
  */
-export async function findAvailablePort(startPort: number): Promise<number> {
-  return await invoke('plugin:php|find_available_port', { startPort })
+export async function stopGateway(): Promise<void> {
+  return await invoke('plugin:php|stop_gateway')
+}
+
+/**
+ * Gets the gateway status and the URLs of all running projects
+ */
+export async function getGatewayStatus(): Promise<GatewayStatus> {
+  return await invoke('plugin:php|get_gateway_status')
 }
 
 /**
@@ -199,10 +890,14 @@ export async function stopAllServers(): Promise<void> {
 }
 
 /**
- * Gets framework information
+ * Gets framework information; with a version, the entry point, console
+ * script and setup steps are those of that release
  */
-export async function getFrameworkInfo(framework: Framework): Promise<FrameworkInfo> {
-  return await invoke('plugin:php|get_framework_info', { framework })
+export async function getFrameworkInfo(
+  framework: Framework,
+  version?: FrameworkVersion
+): Promise<FrameworkInfo> {
+  return await invoke('plugin:php|get_framework_info', { framework, version })
 }
 
 /**
@@ -212,6 +907,14 @@ export async function validateProjectPath(path: string): Promise<boolean> {
   return await invoke('plugin:php|validate_project_path', { path })
 }
 
+/**
+ * Reads composer.json and composer.lock: the direct requirements with their
+ * locked versions, and the required PHP extensions and platform packages
+ */
+export async function getProjectDependencies(projectPath: string): Promise<ProjectDependencies> {
+  return await invoke('plugin:php|get_project_dependencies', { projectPath })
+}
+
 /**
  * Gets project information from a path
  */
@@ -248,7 +951,12 @@ export function createStartServerRequest(projectPath: string, options?: Partial<
     port: options?.port,
     host: options?.host || '127.0.0.1',
     php_version: options?.php_version,
-    document_root: options?.document_root
+    document_root: options?.document_root,
+    https: options?.https,
+    backend: options?.backend,
+    fastcgi: options?.fastcgi,
+    project_id: options?.project_id,
+    env: options?.env
   }
 }
 
@@ -260,7 +968,14 @@ export function createCloneProjectRequest(gitUrl: string, options?: Partial<Clon
     git_url: gitUrl,
     destination: options?.destination,
     branch: options?.branch,
-    auto_setup: options?.auto_setup ?? true
+    auto_setup: options?.auto_setup ?? true,
+    clone_id: options?.clone_id,
+    credentials: options?.credentials,
+    tag: options?.tag,
+    commit: options?.commit,
+    depth: options?.depth,
+    single_branch: options?.single_branch,
+    recurse_submodules: options?.recurse_submodules
   }
 }
 
@@ -211,7 +211,8 @@ describe('Type definitions', () => {
       port: 8000,
       host: '127.0.0.1',
       document_root: '/path/to/root',
-      started_at: '2024-01-01T00:00:00Z'
+      started_at: '2024-01-01T00:00:00Z',
+      https: false
     }
     
     expect(status.is_running).toBe(true)
@@ -1,5 +1,7 @@
+use crate::project_trash::TrashedProject;
+use crate::secret_store::SecretStore;
 use crate::{models::*, Error, PhpManager, Result};
-use tauri::{command, State};
+use tauri::{command, AppHandle, Emitter, Runtime, State};
 
 #[command]
 pub async fn download_php_binary(
@@ -13,16 +15,63 @@ pub async fn download_php_binary(
 #[command]
 pub async fn start_php_server(
     state: State<'_, PhpManager>,
-    request: StartServerRequest,
+    mut request: StartServerRequest,
 ) -> Result<String> {
-    let binary_manager = state.binary_manager.lock().await;
+    // Saved project settings fill in whatever the request leaves out
+    state
+        .project_manager
+        .lock()
+        .await
+        .apply_project_settings(&mut request)?;
+
+    let php_executable = server_php_executable(&state, &request).await?;
+
+    // Compare the project's extension requirements with the binary first
+    let required = state
+        .project_manager
+        .lock()
+        .await
+        .required_extensions(std::path::Path::new(&request.project_path));
+    match crate::php_binary::check_extensions(&php_executable, required).await {
+        Ok(report) => {
+            let missing: Vec<String> = report
+                .missing
+                .into_iter()
+                .filter(|extension| !extension.dev)
+                .map(|extension| extension.name)
+                .collect();
+            if !missing.is_empty() {
+                if request.require_extensions {
+                    return Err(Error::MissingExtensions(missing));
+                }
+                log::warn!(
+                    "PHP at {} lacks extensions required by {}: {}",
+                    php_executable.display(),
+                    request.project_path,
+                    missing.join(", ")
+                );
+            }
+        }
+        Err(e) => log::warn!("Skipping PHP extension check: {}", e),
+    }
+
+    // Start the server
     let mut server_manager = state.server_manager.lock().await;
+    server_manager.start_server(request, php_executable).await
+}
+
+/// The PHP binary a server request runs with: its PHP version, or the default.
+async fn server_php_executable(
+    state: &State<'_, PhpManager>,
+    request: &StartServerRequest,
+) -> Result<std::path::PathBuf> {
+    let binary_manager = state.binary_manager.lock().await;
 
     // Get PHP version to use
     let php_version = request
         .php_version
         .clone()
-        .unwrap_or_else(|| "8.3.0".to_string());
+        .unwrap_or_else(|| binary_manager.default_version().to_string());
 
     // Get PHP executable path
     let php_executable = binary_manager.get_php_executable_path(&php_version);
@@ -30,9 +79,39 @@ pub async fn start_php_server(
     if !php_executable.exists() {
         return Err(Error::PhpBinaryNotFound);
     }
+    Ok(php_executable)
+}
 
-    // Start the server
-    server_manager.start_server(request, php_executable).await
+/// Compares the project's PHP extension requirements with the extensions of
+/// the PHP binary its server would use.
+#[command]
+pub async fn check_php_extensions(
+    state: State<'_, PhpManager>,
+    project_path: String,
+    php_version: Option<String>,
+) -> Result<ExtensionReport> {
+    let mut request = StartServerRequest {
+        project_path,
+        php_version,
+        ..Default::default()
+    };
+    let required = {
+        let project_manager = state.project_manager.lock().await;
+        project_manager.apply_project_settings(&mut request)?;
+        project_manager.required_extensions(std::path::Path::new(&request.project_path))
+    };
+    let php_executable = server_php_executable(&state, &request).await?;
+    crate::php_binary::check_extensions(&php_executable, required).await
+}
+
+/// Lists the extensions loaded by an installed PHP version.
+#[command]
+pub async fn get_php_extensions(
+    state: State<'_, PhpManager>,
+    version: String,
+) -> Result<Vec<String>> {
+    let binary_manager = state.binary_manager.lock().await;
+    binary_manager.get_loaded_extensions(&version).await
 }
 
 #[command]
@@ -51,12 +130,81 @@ pub async fn get_server_status(
 }
 
 #[command]
-pub async fn clone_project(
+pub async fn clone_project<R: Runtime>(
+    app: AppHandle<R>,
     state: State<'_, PhpManager>,
-    request: CloneProjectRequest,
+    mut request: CloneProjectRequest,
 ) -> Result<ProjectInfo> {
-    let project_manager = state.project_manager.lock().await;
-    project_manager.clone_project(request).await
+    let clone_id = request
+        .clone_id
+        .get_or_insert_with(|| uuid::Uuid::new_v4().to_string())
+        .clone();
+    // Registered outside the project manager lock so cancel_clone never waits on it
+    let cancel = state.clone_registry.register(&clone_id)?;
+
+    let on_progress: crate::git::ProgressCallback = Box::new(move |progress| {
+        let _ = app.emit(crate::git::CLONE_PROGRESS_EVENT, progress.clone());
+    });
+
+    // A snapshot, so that the clone and its setup do not block other commands
+    let project_manager = state.project_manager.lock().await.clone();
+    let result = project_manager
+        .clone_project_with_progress(request, cancel, Some(on_progress))
+        .await;
+    state.clone_registry.unregister(&clone_id);
+    result
+}
+
+#[command]
+pub async fn create_project(
+    state: State<'_, PhpManager>,
+    request: CreateProjectRequest,
+) -> Result<ProjectInfo> {
+    // Composer create-project can take minutes; run it on a snapshot
+    let project_manager = state.project_manager.lock().await.clone();
+    project_manager.create_project(request).await
+}
+
+/// Runs the framework's setup in an existing project and reports each step.
+#[command]
+pub async fn setup_project(
+    state: State<'_, PhpManager>,
+    request: SetupProjectRequest,
+) -> Result<SetupReport> {
+    // Setup runs Composer and migrations; run it on a snapshot
+    let project_manager = state.project_manager.lock().await.clone();
+    project_manager.setup_project(request).await
+}
+
+#[command]
+pub async fn list_project_templates() -> Result<Vec<ProjectTemplate>> {
+    Ok(crate::project_templates::templates())
+}
+
+#[command]
+pub async fn cancel_clone(state: State<'_, PhpManager>, clone_id: String) -> Result<bool> {
+    Ok(state.clone_registry.cancel(&clone_id))
+}
+
+/// Saves a passphrase or token for `GitCredentials` to reference by id.
+#[command]
+pub async fn set_secret(
+    state: State<'_, PhpManager>,
+    secret_id: String,
+    secret: String,
+) -> Result<()> {
+    state.secret_store.set(&secret_id, &secret)
+}
+
+#[command]
+pub async fn delete_secret(state: State<'_, PhpManager>, secret_id: String) -> Result<bool> {
+    state.secret_store.delete(&secret_id)
+}
+
+/// Secrets are write-only from the webview; this only reports whether one exists.
+#[command]
+pub async fn has_secret(state: State<'_, PhpManager>, secret_id: String) -> Result<bool> {
+    Ok(state.secret_store.get(&secret_id)?.is_some())
 }
 
 #[command]
@@ -68,6 +216,17 @@ pub async fn detect_framework(
     project_manager.detect_framework(&std::path::PathBuf::from(project_path))
 }
 
+/// Scores every framework against a project, best match first, with the
+/// evidence found for each.
+#[command]
+pub async fn rank_frameworks(
+    state: State<'_, PhpManager>,
+    project_path: String,
+) -> Result<Vec<FrameworkCandidate>> {
+    let project_manager = state.project_manager.lock().await;
+    project_manager.rank_frameworks(&std::path::PathBuf::from(project_path))
+}
+
 #[command]
 pub async fn get_php_version(state: State<'_, PhpManager>, version: String) -> Result<String> {
     let binary_manager = state.binary_manager.lock().await;
@@ -83,36 +242,14 @@ pub async fn list_available_php_versions(
 }
 
 #[command]
-pub async fn set_php_config(_state: State<'_, PhpManager>, config: Config) -> Result<()> {
-    // Save configuration to file
-    let config_dir = dirs::config_dir()
-        .unwrap_or_else(|| std::path::PathBuf::from("."))
-        .join("tauri-php-plugin");
-
-    std::fs::create_dir_all(&config_dir)?;
-
-    let config_file = config_dir.join("config.json");
-    let config_json = serde_json::to_string_pretty(&config)?;
-    std::fs::write(config_file, config_json)?;
-
-    Ok(())
+pub async fn set_php_config(state: State<'_, PhpManager>, config: Config) -> Result<()> {
+    crate::config::save_config(&config, &crate::config::config_file_path())?;
+    state.apply_config(config).await
 }
 
 #[command]
-pub async fn get_php_config(_state: State<'_, PhpManager>) -> Result<Config> {
-    let config_dir = dirs::config_dir()
-        .unwrap_or_else(|| std::path::PathBuf::from("."))
-        .join("tauri-php-plugin");
-
-    let config_file = config_dir.join("config.json");
-
-    if config_file.exists() {
-        let config_content = std::fs::read_to_string(config_file)?;
-        let config: Config = serde_json::from_str(&config_content)?;
-        Ok(config)
-    } else {
-        Ok(Config::default())
-    }
+pub async fn get_php_config(state: State<'_, PhpManager>) -> Result<Config> {
+    Ok(state.config.lock().await.clone())
 }
 
 #[command]
@@ -130,9 +267,120 @@ pub async fn list_projects(state: State<'_, PhpManager>) -> Result<Vec<ProjectIn
 }
 
 #[command]
-pub async fn remove_project(state: State<'_, PhpManager>, project_name: String) -> Result<()> {
+pub async fn import_project(
+    state: State<'_, PhpManager>,
+    path: String,
+    name: Option<String>,
+) -> Result<ProjectInfo> {
+    let project_manager = state.project_manager.lock().await;
+    project_manager.import_project(&std::path::PathBuf::from(path), name.as_deref())
+}
+
+#[command]
+pub async fn get_project(state: State<'_, PhpManager>, project_id: String) -> Result<ProjectInfo> {
+    let project_manager = state.project_manager.lock().await;
+    project_manager.get_project(&project_id)
+}
+
+#[command]
+pub async fn get_project_settings(
+    state: State<'_, PhpManager>,
+    project_id: String,
+) -> Result<ProjectSettings> {
+    let project_manager = state.project_manager.lock().await;
+    project_manager.get_project_settings(&project_id)
+}
+
+#[command]
+pub async fn update_project_settings(
+    state: State<'_, PhpManager>,
+    project_id: String,
+    settings: ProjectSettings,
+) -> Result<ProjectInfo> {
+    let project_manager = state.project_manager.lock().await;
+    project_manager.update_project_settings(&project_id, settings)
+}
+
+#[command]
+pub async fn remove_project(
+    state: State<'_, PhpManager>,
+    project_name: String,
+    options: Option<RemoveProjectOptions>,
+) -> Result<RemoveProjectResult> {
+    state
+        .remove_project(&project_name, options.unwrap_or_default())
+        .await
+}
+
+#[command]
+pub async fn list_trash(state: State<'_, PhpManager>) -> Result<Vec<TrashedProject>> {
+    let project_manager = state.project_manager.lock().await;
+    Ok(project_manager.list_trash())
+}
+
+#[command]
+pub async fn restore_project(
+    state: State<'_, PhpManager>,
+    trash_id: String,
+    name: Option<String>,
+) -> Result<ProjectInfo> {
+    let project_manager = state.project_manager.lock().await;
+    project_manager.restore_project(&trash_id, name.as_deref())
+}
+
+#[command]
+pub async fn purge_trash(state: State<'_, PhpManager>, trash_id: Option<String>) -> Result<usize> {
+    let project_manager = state.project_manager.lock().await;
+    project_manager.purge_trash(trash_id.as_deref())
+}
+
+#[command]
+pub async fn get_git_status(
+    state: State<'_, PhpManager>,
+    project_path: String,
+) -> Result<GitStatus> {
     let project_manager = state.project_manager.lock().await;
-    project_manager.remove_project(&project_name)
+    project_manager.git_status(&std::path::PathBuf::from(project_path))
+}
+
+#[command]
+pub async fn fetch_project(
+    state: State<'_, PhpManager>,
+    project_path: String,
+    credentials: Option<GitCredentials>,
+) -> Result<GitStatus> {
+    let project_manager = state.project_manager.lock().await;
+    project_manager
+        .fetch_project(
+            &std::path::PathBuf::from(project_path),
+            credentials.as_ref(),
+        )
+        .await
+}
+
+#[command]
+pub async fn pull_project(
+    state: State<'_, PhpManager>,
+    project_path: String,
+    credentials: Option<GitCredentials>,
+) -> Result<GitPullResult> {
+    let project_manager = state.project_manager.lock().await;
+    project_manager
+        .pull_project(
+            &std::path::PathBuf::from(project_path),
+            credentials.as_ref(),
+        )
+        .await
+}
+
+#[command]
+pub async fn switch_branch(
+    state: State<'_, PhpManager>,
+    project_path: String,
+    branch: String,
+) -> Result<GitStatus> {
+    let project_manager = state.project_manager.lock().await;
+    project_manager.switch_branch(&std::path::PathBuf::from(project_path), &branch)
 }
 
 #[command]
@@ -141,6 +389,124 @@ pub async fn remove_php_binary(state: State<'_, PhpManager>, version: String) ->
     binary_manager.remove_php_binary(&version)
 }
 
+/// Downloads `composer.phar` into the plugin's data directory; the latest
+/// stable release when `version` is omitted.
+#[command]
+pub async fn download_composer(
+    state: State<'_, PhpManager>,
+    version: Option<String>,
+) -> Result<ComposerInfo> {
+    let composer_manager = state.composer_manager.lock().await;
+    composer_manager.download_composer(version.as_deref()).await
+}
+
+#[command]
+pub async fn install_composer_from_file(
+    state: State<'_, PhpManager>,
+    version: String,
+    path: String,
+) -> Result<ComposerInfo> {
+    let composer_manager = state.composer_manager.lock().await;
+    composer_manager.install_composer_from_file(&version, std::path::Path::new(&path))
+}
+
+#[command]
+pub async fn list_composer_versions(state: State<'_, PhpManager>) -> Result<Vec<ComposerInfo>> {
+    let composer_manager = state.composer_manager.lock().await;
+    composer_manager.list_installed_versions()
+}
+
+#[command]
+pub async fn remove_composer(state: State<'_, PhpManager>, version: String) -> Result<()> {
+    let composer_manager = state.composer_manager.lock().await;
+    composer_manager.remove_composer(&version)
+}
+
+/// Runs a Composer action in a project, emitting each output line as a
+/// `php://composer-output` event.
+#[command]
+pub async fn run_composer<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, PhpManager>,
+    mut request: ComposerRequest,
+) -> Result<ComposerResult> {
+    request
+        .run_id
+        .get_or_insert_with(|| uuid::Uuid::new_v4().to_string());
+
+    let on_output: crate::composer::OutputCallback = Box::new(move |output| {
+        let _ = app.emit(crate::composer::COMPOSER_OUTPUT_EVENT, output.clone());
+    });
+
+    // `composer update` can run for minutes and must not hold the lock
+    let run = {
+        let project_manager = state.project_manager.lock().await;
+        project_manager.composer_run(&request)?
+    };
+    run.run(Some(on_output)).await
+}
+
+/// Runs a command of the project's framework console, emitting each output
+/// line as a `php://framework-command-output` event.
+#[command]
+pub async fn run_framework_command<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, PhpManager>,
+    request: FrameworkCommandRequest,
+) -> Result<FrameworkCommandResult> {
+    let run_id = request
+        .run_id
+        .clone()
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    // Long-running commands such as queue workers must not hold the lock
+    let command = {
+        let project_manager = state.project_manager.lock().await;
+        project_manager.framework_command(&request)?
+    };
+    let cancel = state.command_registry.register(&run_id)?;
+
+    let on_output: crate::framework_command::CommandOutputCallback = Box::new(move |output| {
+        let _ = app.emit(
+            crate::framework_command::FRAMEWORK_COMMAND_OUTPUT_EVENT,
+            output.clone(),
+        );
+    });
+
+    let result = command.run(&run_id, cancel, Some(on_output)).await;
+    state.command_registry.unregister(&run_id);
+    result
+}
+
+/// Lists the commands the project's framework console offers.
+#[command]
+pub async fn list_framework_commands(
+    state: State<'_, PhpManager>,
+    project_path: String,
+    refresh: Option<bool>,
+) -> Result<ConsoleCommandList> {
+    // Booting the console can take seconds; the snapshot shares the cache
+    let project_manager = state.project_manager.lock().await.clone();
+    project_manager
+        .list_framework_commands(
+            std::path::Path::new(&project_path),
+            refresh.unwrap_or(false),
+        )
+        .await
+}
+
+#[command]
+pub async fn cancel_framework_command(
+    state: State<'_, PhpManager>,
+    run_id: String,
+) -> Result<bool> {
+    Ok(state.command_registry.cancel(&run_id))
+}
+
+#[command]
+pub async fn get_project_dependencies(project_path: String) -> Result<ProjectDependencies> {
+    crate::composer_manifest::project_dependencies(std::path::Path::new(&project_path))
+}
+
 #[command]
 pub async fn get_server_logs(state: State<'_, PhpManager>, server_id: String) -> Result<String> {
     let server_manager = state.server_manager.lock().await;
@@ -148,9 +514,72 @@ pub async fn get_server_logs(state: State<'_, PhpManager>, server_id: String) ->
 }
 
 #[command]
-pub async fn find_available_port(state: State<'_, PhpManager>, start_port: u16) -> Result<u16> {
-    let server_manager = state.server_manager.lock().await;
-    Ok(server_manager.find_available_port(start_port))
+pub async fn find_available_port(
+    state: State<'_, PhpManager>,
+    start_port: u16,
+    end_port: Option<u16>,
+    exclude: Option<Vec<u16>>,
+) -> Result<u16> {
+    let mut server_manager = state.server_manager.lock().await;
+    let end_port = end_port.unwrap_or(u16::MAX);
+    // Reserve the port so a concurrent start_php_server call cannot be handed it too
+    server_manager.reserve_port(start_port..=end_port, &exclude.unwrap_or_default())
+}
+
+#[command]
+pub async fn get_https_ca_certificate(state: State<'_, PhpManager>) -> Result<String> {
+    let mut server_manager = state.server_manager.lock().await;
+    let authority = server_manager.certificate_authority()?;
+    Ok(authority.ca_cert_pem().to_string())
+}
+
+#[command]
+pub async fn start_gateway(
+    state: State<'_, PhpManager>,
+    port: Option<u16>,
+    host: Option<String>,
+) -> Result<GatewayStatus> {
+    let mut gateway = state.gateway.lock().await;
+    if let Some(running) = gateway.as_ref() {
+        return Err(Error::PhpServer(format!(
+            "Gateway is already running on {}",
+            running.local_addr()
+        )));
+    }
+
+    let host = host.unwrap_or_else(|| "127.0.0.1".to_string());
+    let port = port.unwrap_or(crate::gateway::DEFAULT_GATEWAY_PORT);
+    let listen_addr = tokio::net::lookup_host((host.as_str(), port))
+        .await?
+        .next()
+        .ok_or_else(|| Error::PhpServer(format!("Cannot resolve host: {}", host)))?;
+
+    let started = crate::gateway::Gateway::start(listen_addr, state.server_manager.clone()).await?;
+    let servers = state.server_manager.lock().await.list_running_servers();
+    let status = started.status(&servers);
+    *gateway = Some(started);
+    Ok(status)
+}
+
+#[command]
+pub async fn stop_gateway(state: State<'_, PhpManager>) -> Result<()> {
+    let mut gateway = state.gateway.lock().await;
+    if let Some(mut running) = gateway.take() {
+        running.shutdown();
+    }
+    Ok(())
+}
+
+#[command]
+pub async fn get_gateway_status(state: State<'_, PhpManager>) -> Result<GatewayStatus> {
+    let gateway = state.gateway.lock().await;
+    match gateway.as_ref() {
+        Some(running) => {
+            let servers = state.server_manager.lock().await.list_running_servers();
+            Ok(running.status(&servers))
+        }
+        None => Ok(GatewayStatus::default()),
+    }
 }
 
 #[command]
@@ -163,9 +592,10 @@ pub async fn stop_all_servers(state: State<'_, PhpManager>) -> Result<()> {
 pub async fn get_framework_info(
     state: State<'_, PhpManager>,
     framework: Framework,
+    version: Option<FrameworkVersion>,
 ) -> Result<crate::framework_detector::FrameworkInfo> {
     let project_manager = state.project_manager.lock().await;
-    Ok(project_manager.get_framework_info(&framework))
+    Ok(project_manager.framework_info(&framework, version.as_ref()))
 }
 
 #[command]
@@ -183,6 +613,9 @@ pub async fn get_project_info(
     let path = std::path::PathBuf::from(&project_path);
 
     let framework = project_manager.detect_framework(&path).ok();
+    let framework_version = framework
+        .as_ref()
+        .and_then(|framework| project_manager.detect_framework_version(&path, framework));
     let php_version = project_manager
         .detect_php_version_requirement_public(&path)
         .ok()
@@ -194,12 +627,20 @@ pub async fn get_project_info(
         .unwrap_or("Unknown")
         .to_string();
 
+    let registered = project_manager.find_registered_project(&path);
+
     Ok(ProjectInfo {
         name: project_name,
+        git_url: crate::git::remote_url(&path),
         path: project_path,
         framework,
-        git_url: None,
+        framework_version,
         php_version,
         entry_point: None,
+        id: registered.as_ref().map(|project| project.id.clone()),
+        settings: registered
+            .map(|project| project.settings)
+            .unwrap_or_default(),
+        setup: None,
     })
 }
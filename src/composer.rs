@@ -0,0 +1,509 @@
+use crate::models::{
+    ComposerAction, ComposerInfo, ComposerOutput, ComposerProblem, ComposerRequest, ComposerResult,
+    ComposerSettings, OutputStream,
+};
+use crate::{Error, Result};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Instant;
+use tokio::io::{AsyncBufReadExt, BufReader};
+
+/// Event carrying each line Composer prints during `run_composer`.
+pub const COMPOSER_OUTPUT_EVENT: &str = "php://composer-output";
+
+/// Receives Composer's output line by line as it is printed.
+pub type OutputCallback = Box<dyn FnMut(&ComposerOutput) + Send>;
+
+/// Serves `/versions` and `/download/<version>/composer.phar`.
+pub const DEFAULT_COMPOSER_BASE_URL: &str = "https://getcomposer.org";
+
+/// Keeps `composer.phar` versions in the plugin's data directory, one per
+/// `composer/<version>/composer.phar`, the way `PhpBinaryManager` keeps PHP.
+#[derive(Debug, Clone)]
+pub struct ComposerManager {
+    composer_dir: PathBuf,
+    base_url: String,
+}
+
+impl Default for ComposerManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ComposerManager {
+    pub fn new() -> Self {
+        let mut dir = dirs::data_dir().unwrap_or_else(|| PathBuf::from("."));
+        dir.push("tauri-php-plugin");
+        dir.push("composer");
+
+        Self {
+            composer_dir: dir,
+            base_url: DEFAULT_COMPOSER_BASE_URL.to_string(),
+        }
+    }
+
+    pub fn set_composer_directory(&mut self, path: PathBuf) {
+        self.composer_dir = path;
+    }
+
+    pub fn set_base_url(&mut self, base_url: &str) {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+    }
+
+    pub fn get_composer_path(&self, version: &str) -> PathBuf {
+        self.composer_dir.join(version).join("composer.phar")
+    }
+
+    /// Downloads `version`, or the latest stable release when None, and checks
+    /// it against the published SHA-256 checksum.
+    pub async fn download_composer(&self, version: Option<&str>) -> Result<ComposerInfo> {
+        let client = reqwest::Client::new();
+        let version = match version {
+            Some(version) => version.to_string(),
+            None => self.latest_stable_version(&client).await?,
+        };
+        validate_version(&version)?;
+
+        let download_url = format!("{}/download/{}/composer.phar", self.base_url, version);
+        let phar = client
+            .get(&download_url)
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?;
+        let checksum = client
+            .get(format!("{}.sha256sum", download_url))
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+
+        let expected = checksum
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        let actual = hex(&Sha256::digest(&phar));
+        if expected != actual {
+            return Err(Error::Http(format!(
+                "Checksum mismatch for Composer {}: expected {}, got {}",
+                version, expected, actual
+            )));
+        }
+
+        self.write_phar(&version, &phar)?;
+        log::info!("Installed Composer {}", version);
+        Ok(self.info(&version, Some(download_url)))
+    }
+
+    async fn latest_stable_version(&self, client: &reqwest::Client) -> Result<String> {
+        let versions = client
+            .get(format!("{}/versions", self.base_url))
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+        let versions: serde_json::Value = serde_json::from_str(&versions)?;
+        versions["stable"][0]["version"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| Error::Http("No stable Composer release listed".to_string()))
+    }
+
+    /// Installs a `composer.phar` shipped with the application, e.g. as a
+    /// bundled resource, under `version`.
+    pub fn install_composer_from_file(
+        &self,
+        version: &str,
+        phar_path: &Path,
+    ) -> Result<ComposerInfo> {
+        validate_version(version)?;
+        let phar = fs::read(phar_path)?;
+        self.write_phar(version, &phar)?;
+        Ok(self.info(version, None))
+    }
+
+    fn write_phar(&self, version: &str, phar: &[u8]) -> Result<()> {
+        let path = self.get_composer_path(version);
+        let version_dir = path.parent().unwrap_or(&self.composer_dir);
+        fs::create_dir_all(version_dir)?;
+
+        let temp_path = path.with_extension(format!("phar.{}.tmp", uuid::Uuid::new_v4()));
+        fs::write(&temp_path, phar)?;
+        fs::rename(&temp_path, &path).map_err(|e| {
+            let _ = fs::remove_file(&temp_path);
+            Error::Io(format!("Failed to install {}: {}", path.display(), e))
+        })
+    }
+
+    fn info(&self, version: &str, download_url: Option<String>) -> ComposerInfo {
+        let path = self.get_composer_path(version);
+        ComposerInfo {
+            version: version.to_string(),
+            is_installed: path.exists(),
+            size: fs::metadata(&path).ok().map(|metadata| metadata.len()),
+            path: path.to_string_lossy().to_string(),
+            download_url,
+        }
+    }
+
+    /// Installed versions, newest first.
+    pub fn list_installed_versions(&self) -> Result<Vec<ComposerInfo>> {
+        let mut versions = Vec::new();
+        if !self.composer_dir.exists() {
+            return Ok(versions);
+        }
+
+        for entry in fs::read_dir(&self.composer_dir)? {
+            let entry = entry?;
+            if let Some(version) = entry.file_name().to_str() {
+                if self.get_composer_path(version).is_file() {
+                    versions.push(self.info(version, None));
+                }
+            }
+        }
+        versions.sort_by(|a, b| compare_versions(&b.version, &a.version));
+        Ok(versions)
+    }
+
+    /// The newest installed `composer.phar`.
+    pub fn default_composer(&self) -> Option<PathBuf> {
+        self.list_installed_versions()
+            .ok()?
+            .into_iter()
+            .next()
+            .map(|info| PathBuf::from(info.path))
+    }
+
+    pub fn remove_composer(&self, version: &str) -> Result<()> {
+        validate_version(version)?;
+        let version_dir = self.composer_dir.join(version);
+        if version_dir.exists() {
+            fs::remove_dir_all(version_dir)?;
+        }
+        Ok(())
+    }
+}
+
+/// How to run Composer for a project: the managed `composer.phar` under the
+/// PHP binary the project resolves to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComposerLauncher {
+    pub php_executable: PathBuf,
+    pub composer_phar: PathBuf,
+}
+
+impl ComposerLauncher {
+    /// `php composer.phar` in `working_dir`, with the PHP binary's directory
+    /// first on `PATH` so scripts that call `php` get the same interpreter.
+    pub fn command(&self, working_dir: &Path) -> tokio::process::Command {
+        let mut command = tokio::process::Command::new(&self.php_executable);
+        command
+            .arg(&self.composer_phar)
+            .arg("--no-interaction")
+            .current_dir(working_dir);
+
+        if let Some(php_dir) = self.php_executable.parent() {
+            let mut paths = vec![php_dir.to_path_buf()];
+            if let Some(path) = std::env::var_os("PATH") {
+                paths.extend(std::env::split_paths(&path));
+            }
+            if let Ok(path) = std::env::join_paths(paths) {
+                command.env("PATH", path);
+            }
+        }
+        command
+    }
+
+    /// Runs Composer with `args`, handing each output line to `on_output`
+    /// as it arrives. A non-zero exit is reported in the result, not as an error.
+    pub async fn run(
+        &self,
+        working_dir: &Path,
+        action: ComposerAction,
+        args: &[String],
+        run_id: &str,
+        mut on_output: Option<OutputCallback>,
+    ) -> Result<ComposerResult> {
+        let started = Instant::now();
+        let mut child = self
+            .command(working_dir)
+            .arg("--no-ansi")
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| Error::Process(format!("Failed to start Composer: {}", e)))?;
+
+        // Both pipes feed one channel so lines keep their arrival order
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        if let Some(stdout) = child.stdout.take() {
+            forward_lines(stdout, OutputStream::Stdout, sender.clone());
+        }
+        if let Some(stderr) = child.stderr.take() {
+            forward_lines(stderr, OutputStream::Stderr, sender.clone());
+        }
+        drop(sender);
+
+        let mut output = String::new();
+        while let Some((stream, line)) = receiver.recv().await {
+            output.push_str(&line);
+            output.push('\n');
+            if let Some(callback) = on_output.as_mut() {
+                callback(&ComposerOutput {
+                    run_id: run_id.to_string(),
+                    stream,
+                    line,
+                });
+            }
+        }
+
+        let status = child.wait().await?;
+        Ok(ComposerResult {
+            run_id: run_id.to_string(),
+            action,
+            exit_code: status.code(),
+            success: status.success(),
+            duration_ms: started.elapsed().as_millis() as u64,
+            problems: if status.success() {
+                Vec::new()
+            } else {
+                parse_problems(&output)
+            },
+            output,
+        })
+    }
+}
+
+/// A Composer action resolved for a project, which runs without the
+/// project manager.
+#[derive(Debug, Clone)]
+pub struct ComposerRun {
+    pub launcher: ComposerLauncher,
+    pub project_path: PathBuf,
+    pub action: ComposerAction,
+    pub args: Vec<String>,
+    pub run_id: String,
+}
+
+impl ComposerRun {
+    pub async fn run(&self, on_output: Option<OutputCallback>) -> Result<ComposerResult> {
+        log::info!(
+            "Running composer {} in {}",
+            self.args.join(" "),
+            self.project_path.display()
+        );
+        self.launcher
+            .run(
+                &self.project_path,
+                self.action,
+                &self.args,
+                &self.run_id,
+                on_output,
+            )
+            .await
+    }
+}
+
+pub(crate) fn forward_lines<R>(
+    reader: R,
+    stream: OutputStream,
+    sender: tokio::sync::mpsc::UnboundedSender<(OutputStream, String)>,
+) where
+    R: tokio::io::AsyncRead + Unpin + Send + 'static,
+{
+    tokio::spawn(async move {
+        let mut lines = BufReader::new(reader).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            // Progress bars redraw with carriage returns; keep the final state
+            let line = line.rsplit('\r').next().unwrap_or_default().to_string();
+            if sender.send((stream, line)).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+/// Builds the Composer arguments for `request`, with the project's
+/// `settings` filling in what the request leaves out.
+pub fn composer_arguments(
+    request: &ComposerRequest,
+    settings: &ComposerSettings,
+) -> Result<Vec<String>> {
+    let mut args = vec![request.action.as_str().to_string()];
+
+    match request.action {
+        ComposerAction::Require | ComposerAction::Remove if request.packages.is_empty() => {
+            return Err(Error::Config(format!(
+                "{:?} needs at least one package",
+                request.action
+            )));
+        }
+        ComposerAction::Install | ComposerAction::DumpAutoload if !request.packages.is_empty() => {
+            return Err(Error::Config(format!(
+                "{:?} does not take packages",
+                request.action
+            )));
+        }
+        _ => {}
+    }
+    for package in &request.packages {
+        if package.trim().is_empty() || package.starts_with('-') {
+            return Err(Error::Config(format!("Invalid package: {:?}", package)));
+        }
+    }
+
+    // Only the request decides whether a package goes to require-dev
+    let package_action = matches!(
+        request.action,
+        ComposerAction::Require | ComposerAction::Remove
+    );
+    if package_action && request.dev == Some(true) {
+        args.push("--dev".to_string());
+    } else if !package_action && request.dev.or(settings.dev) == Some(false) {
+        args.push("--no-dev".to_string());
+    }
+
+    for flag in settings.extra_args.iter().chain(&request.extra_args) {
+        if !flag.starts_with('-') {
+            return Err(Error::Config(format!(
+                "Extra Composer arguments must be flags: {:?}",
+                flag
+            )));
+        }
+        args.push(flag.clone());
+    }
+    args.extend(request.packages.iter().cloned());
+    Ok(args)
+}
+
+/// Extracts the problems from Composer's output: the numbered `Problem N`
+/// list of a failed dependency resolution, or else the boxed error message
+/// Composer prints for other failures.
+pub fn parse_problems(output: &str) -> Vec<ComposerProblem> {
+    let mut problems: Vec<ComposerProblem> = Vec::new();
+    for line in output.lines() {
+        let trimmed = line.trim();
+        if let Some(number) = trimmed
+            .strip_prefix("Problem ")
+            .and_then(|n| n.parse::<usize>().ok())
+        {
+            problems.push(ComposerProblem {
+                number: Some(number),
+                ..Default::default()
+            });
+        } else if let Some(message) = trimmed.strip_prefix("- ") {
+            if let Some(problem) = problems.last_mut() {
+                problem.messages.push(message.to_string());
+            }
+        } else if !trimmed.is_empty() && !line.starts_with(' ') {
+            // Anything flush left ends the problem list
+            if problems.last().is_some_and(|p| !p.messages.is_empty()) {
+                break;
+            }
+        }
+    }
+
+    if problems.is_empty() {
+        problems = parse_error_blocks(output);
+    }
+    for problem in &mut problems {
+        problem.missing_extensions = missing_extensions(&problem.messages);
+    }
+    problems
+}
+
+/// Error messages are printed inside a box of space-padded lines.
+fn parse_error_blocks(output: &str) -> Vec<ComposerProblem> {
+    let mut problems = Vec::new();
+    let mut current: Option<Vec<String>> = None;
+    for line in output.lines() {
+        let padding = !line.is_empty() && line.trim().is_empty();
+        match (&mut current, padding) {
+            (None, true) => current = Some(Vec::new()),
+            (Some(messages), true) => {
+                if messages.is_empty() {
+                    continue;
+                }
+                problems.push(ComposerProblem {
+                    number: None,
+                    messages: std::mem::take(messages),
+                    missing_extensions: Vec::new(),
+                });
+                current = None;
+            }
+            (Some(messages), false) => {
+                let trimmed = line.trim();
+                let exception = trimmed.starts_with('[') && trimmed.ends_with(']');
+                if line.is_empty() {
+                    current = None;
+                } else if !exception {
+                    messages.push(trimmed.to_string());
+                }
+            }
+            (None, false) => {}
+        }
+    }
+    problems
+}
+
+fn missing_extensions(messages: &[String]) -> Vec<String> {
+    let mut extensions: Vec<String> = Vec::new();
+    for message in messages
+        .iter()
+        .filter(|m| m.contains("missing from your system"))
+    {
+        for word in message.split_whitespace() {
+            let Some(extension) = word.strip_prefix("ext-") else {
+                continue;
+            };
+            let extension = extension
+                .trim_end_matches(|c: char| !c.is_ascii_alphanumeric())
+                .to_lowercase();
+            if !extension.is_empty() && !extensions.contains(&extension) {
+                extensions.push(extension);
+            }
+        }
+    }
+    extensions
+}
+
+/// Compares dotted versions numerically, so `2.10.0` sorts after `2.8.1`.
+pub fn compare_versions(a: &str, b: &str) -> std::cmp::Ordering {
+    let parts = |version: &str| -> Vec<u64> {
+        version
+            .split(|c: char| !c.is_ascii_digit())
+            .filter(|part| !part.is_empty())
+            .map(|part| part.parse().unwrap_or(0))
+            .collect()
+    };
+    parts(a).cmp(&parts(b)).then_with(|| a.cmp(b))
+}
+
+fn validate_version(version: &str) -> Result<()> {
+    let valid = !version.is_empty()
+        && !version.starts_with('.')
+        && version
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'));
+    if valid {
+        Ok(())
+    } else {
+        Err(Error::Config(format!(
+            "Invalid Composer version: {:?}",
+            version
+        )))
+    }
+}
+
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
@@ -0,0 +1,447 @@
+use crate::models::{DependencyInfo, DependencyStatus, PlatformRequirement, ProjectDependencies};
+use crate::{Error, Result};
+use serde::{Deserialize, Deserializer, Serialize};
+use sha2::{Digest, Sha256};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// The parts of a project's `composer.json` the plugin reads.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ComposerJson {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default, rename = "type")]
+    pub package_type: Option<String>,
+    #[serde(default, deserialize_with = "string_map")]
+    pub require: BTreeMap<String, String>,
+    #[serde(default, rename = "require-dev", deserialize_with = "string_map")]
+    pub require_dev: BTreeMap<String, String>,
+}
+
+/// The parts of a project's `composer.lock` the plugin reads.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ComposerLock {
+    #[serde(default, rename = "content-hash")]
+    pub content_hash: Option<String>,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub packages: Vec<LockedPackage>,
+    #[serde(default, rename = "packages-dev", deserialize_with = "null_as_default")]
+    pub packages_dev: Vec<LockedPackage>,
+    /// The root platform requirements at the time of locking
+    #[serde(default, deserialize_with = "string_map")]
+    pub platform: BTreeMap<String, String>,
+    #[serde(default, rename = "platform-dev", deserialize_with = "string_map")]
+    pub platform_dev: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LockedPackage {
+    pub name: String,
+    pub version: String,
+    #[serde(default, rename = "type")]
+    pub package_type: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default, deserialize_with = "string_map")]
+    pub require: BTreeMap<String, String>,
+}
+
+impl ComposerJson {
+    /// Reads `composer.json` in `project_path`; None when there is none.
+    pub fn load(project_path: &Path) -> Result<Option<Self>> {
+        read_json(&project_path.join("composer.json"))
+    }
+
+    /// Whether the project requires `package`, directly or for development.
+    pub fn requires(&self, package: &str) -> bool {
+        self.require.contains_key(package) || self.require_dev.contains_key(package)
+    }
+}
+
+impl ComposerLock {
+    /// Reads `composer.lock` in `project_path`; None when there is none.
+    pub fn load(project_path: &Path) -> Result<Option<Self>> {
+        read_json(&project_path.join("composer.lock"))
+    }
+
+    /// Finds a locked package, production or dev.
+    pub fn package(&self, name: &str) -> Option<&LockedPackage> {
+        self.packages
+            .iter()
+            .chain(&self.packages_dev)
+            .find(|package| package.name.eq_ignore_ascii_case(name))
+    }
+
+    /// SHA-256 of `composer.lock` in `project_path`, which changes whenever
+    /// the installed packages do; None when there is none.
+    pub fn file_hash(project_path: &Path) -> Result<Option<String>> {
+        match fs::read(project_path.join("composer.lock")) {
+            Ok(content) => Ok(Some(crate::composer::hex(&Sha256::digest(content)))),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(path: &Path) -> Result<Option<T>> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    serde_json::from_str(&content)
+        .map(Some)
+        .map_err(|e| Error::Json(format!("{}: {}", path.display(), e)))
+}
+
+/// Composer writes empty maps as `[]`, so accept any JSON and keep the string entries.
+fn string_map<'de, D>(deserializer: D) -> std::result::Result<BTreeMap<String, String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::Object(map) => map
+            .into_iter()
+            .filter_map(|(key, value)| value.as_str().map(|value| (key, value.to_string())))
+            .collect(),
+        _ => BTreeMap::new(),
+    })
+}
+
+fn null_as_default<'de, D, T>(deserializer: D) -> std::result::Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Default + Deserialize<'de>,
+{
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+/// Whether `name` is a platform package (PHP, an extension, a library or a
+/// Composer API) rather than something installed from a repository.
+pub fn is_platform_package(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    matches!(
+        name.as_str(),
+        "php" | "php-64bit" | "php-ipv6" | "php-zts" | "php-debug" | "hhvm"
+    ) || name.starts_with("ext-")
+        || name.starts_with("lib-")
+        || name.starts_with("composer-")
+        || name == "composer"
+}
+
+/// Summarizes a project's Composer dependencies: the direct requirements with
+/// their locked versions, and the PHP, extension and other platform
+/// requirements of the project and everything it locks.
+pub fn project_dependencies(project_path: &Path) -> Result<ProjectDependencies> {
+    let manifest = ComposerJson::load(project_path)?.ok_or_else(|| {
+        Error::Config(format!(
+            "composer.json not found in {}",
+            project_path.display()
+        ))
+    })?;
+    let lock = ComposerLock::load(project_path)?;
+
+    let mut packages = Vec::new();
+    for (dev, requirements) in [(false, &manifest.require), (true, &manifest.require_dev)] {
+        for (name, constraint) in requirements {
+            if is_platform_package(name) {
+                continue;
+            }
+            let locked_version = lock
+                .as_ref()
+                .and_then(|lock| lock.package(name))
+                .map(|package| package.version.clone());
+            let status = match &locked_version {
+                None => DependencyStatus::NotLocked,
+                Some(version) => match constraint_matches(constraint, version) {
+                    Some(true) => DependencyStatus::UpToDate,
+                    Some(false) => DependencyStatus::Outdated,
+                    None => DependencyStatus::Unknown,
+                },
+            };
+            packages.push(DependencyInfo {
+                name: name.clone(),
+                constraint: constraint.clone(),
+                dev,
+                locked_version,
+                status,
+            });
+        }
+    }
+
+    // Root requirements first, then those of each locked package
+    let mut sources: Vec<(&str, bool, &BTreeMap<String, String>)> = vec![
+        ("composer.json", false, &manifest.require),
+        ("composer.json", true, &manifest.require_dev),
+    ];
+    if let Some(lock) = &lock {
+        sources.extend(
+            lock.packages
+                .iter()
+                .map(|p| (p.name.as_str(), false, &p.require)),
+        );
+        sources.extend(
+            lock.packages_dev
+                .iter()
+                .map(|p| (p.name.as_str(), true, &p.require)),
+        );
+    }
+
+    let mut platform: Vec<PlatformRequirement> = Vec::new();
+    for (source, dev, requirements) in sources {
+        for (name, constraint) in requirements {
+            if !is_platform_package(name) {
+                continue;
+            }
+            let name = name.to_ascii_lowercase();
+            match platform
+                .iter_mut()
+                .find(|requirement| requirement.name == name)
+            {
+                Some(requirement) => {
+                    requirement.dev &= dev;
+                    if !requirement.required_by.iter().any(|s| s == source) {
+                        requirement.required_by.push(source.to_string());
+                    }
+                }
+                None => platform.push(PlatformRequirement {
+                    name,
+                    constraint: constraint.clone(),
+                    dev,
+                    required_by: vec![source.to_string()],
+                }),
+            }
+        }
+    }
+    let (extensions, platform): (Vec<_>, Vec<_>) = platform
+        .into_iter()
+        .partition(|requirement| requirement.name.starts_with("ext-"));
+
+    Ok(ProjectDependencies {
+        project_path: project_path.to_string_lossy().to_string(),
+        has_lock_file: lock.is_some(),
+        lock_outdated: packages.iter().any(|package| {
+            matches!(
+                package.status,
+                DependencyStatus::Outdated | DependencyStatus::NotLocked
+            )
+        }),
+        packages,
+        extensions,
+        platform,
+    })
+}
+
+/// Checks a locked `version` against a Composer version `constraint`.
+/// Returns None when either cannot be evaluated, such as branch versions.
+pub fn constraint_matches(constraint: &str, version: &str) -> Option<bool> {
+    let version = Version::parse(version)?;
+    let mut any_group = false;
+    for group in constraint
+        .split('|')
+        .map(str::trim)
+        .filter(|g| !g.is_empty())
+    {
+        any_group = true;
+        if group_matches(group, &version)? {
+            return Some(true);
+        }
+    }
+    any_group.then_some(false)
+}
+
+fn group_matches(group: &str, version: &Version) -> Option<bool> {
+    let normalized = group.replace(',', " ");
+    let tokens: Vec<&str> = normalized.split_whitespace().collect();
+    let mut i = 0;
+    while i < tokens.len() {
+        // Hyphen ranges: `1.0 - 2.0`
+        if tokens.get(i + 1) == Some(&"-") {
+            let lower = Version::parse(tokens[i])?;
+            let upper_text = tokens.get(i + 2)?;
+            let upper = Version::parse(upper_text)?;
+            let upper_ok = if upper_text.split('.').count() < 3 {
+                version < &upper.next_at(upper_text.split('.').count() - 1).dev()
+            } else {
+                version <= &upper
+            };
+            if !(version >= &lower.dev() && upper_ok) {
+                return Some(false);
+            }
+            i += 3;
+            continue;
+        }
+        // Operators may be separated from their version: `>= 1.0`
+        let mut token = tokens[i].to_string();
+        if matches!(
+            tokens[i],
+            ">=" | "<=" | ">" | "<" | "!=" | "=" | "==" | "^" | "~"
+        ) {
+            token.push_str(tokens.get(i + 1)?);
+            i += 1;
+        }
+        if !atom_matches(&token, version)? {
+            return Some(false);
+        }
+        i += 1;
+    }
+    Some(true)
+}
+
+fn atom_matches(atom: &str, version: &Version) -> Option<bool> {
+    // Stability flags such as `@dev` don't narrow the range
+    let atom = atom
+        .split('@')
+        .next()
+        .unwrap_or_default()
+        .trim_start_matches('v');
+    if atom.is_empty() || atom == "*" {
+        return Some(true);
+    }
+
+    for (operator, rest) in [
+        (">=", atom.strip_prefix(">=")),
+        ("<=", atom.strip_prefix("<=")),
+        ("!=", atom.strip_prefix("!=")),
+        ("==", atom.strip_prefix("==")),
+        (">", atom.strip_prefix('>')),
+        ("<", atom.strip_prefix('<')),
+        ("=", atom.strip_prefix('=')),
+    ] {
+        let Some(rest) = rest else { continue };
+        let bound = Version::parse(rest)?;
+        return Some(match operator {
+            ">=" => version >= &bound.dev(),
+            "<=" => version <= &bound,
+            ">" => version > &bound,
+            "<" => version < &bound.dev(),
+            "!=" => version != &bound,
+            _ => version == &bound,
+        });
+    }
+
+    if let Some(rest) = atom.strip_prefix('^') {
+        let lower = Version::parse(rest)?;
+        // The first non-zero component may not change: ^0.3 means <0.4
+        let significant = lower.numbers[..rest.split('.').count().clamp(1, 3)]
+            .iter()
+            .position(|n| *n != 0)
+            .unwrap_or(rest.split('.').count().clamp(1, 3) - 1);
+        return Some(version >= &lower.dev() && version < &lower.next_at(significant).dev());
+    }
+    if let Some(rest) = atom.strip_prefix('~') {
+        let lower = Version::parse(rest)?;
+        let parts = rest.split('.').count();
+        let upper = lower.next_at(parts.saturating_sub(2));
+        return Some(version >= &lower.dev() && version < &upper.dev());
+    }
+
+    let parts: Vec<&str> = atom.split('.').collect();
+    if let Some(wildcard) = parts.iter().position(|part| *part == "*" || *part == "x") {
+        if wildcard == 0 {
+            return Some(true);
+        }
+        let lower = Version::parse(&parts[..wildcard].join("."))?;
+        let upper = lower.next_at(wildcard - 1);
+        return Some(version >= &lower.dev() && version < &upper.dev());
+    }
+
+    Some(version == &Version::parse(atom)?)
+}
+
+/// A released version: numeric components followed by a stability.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Version {
+    numbers: [u64; 4],
+    /// dev, alpha, beta, RC, stable (0..=4) and the number after it
+    stability: (u8, u64),
+}
+
+impl Version {
+    fn parse(text: &str) -> Option<Self> {
+        let text = text.trim().trim_start_matches(['v', 'V']);
+        let text = text.split('+').next()?;
+        if text.is_empty() || text.starts_with("dev-") || text.ends_with("-dev") {
+            return None;
+        }
+
+        let (release, suffix) = match text.find(|c: char| !c.is_ascii_digit() && c != '.') {
+            Some(index) => (&text[..index], &text[index..]),
+            None => (text, ""),
+        };
+        let mut numbers = [0u64; 4];
+        let components: Vec<&str> = release.trim_end_matches('.').split('.').collect();
+        if components.len() > 4 {
+            return None;
+        }
+        for (slot, component) in numbers.iter_mut().zip(&components) {
+            *slot = component.parse().ok()?;
+        }
+
+        let suffix = suffix.trim_start_matches(['-', '.']).to_ascii_lowercase();
+        let (rank, rest) = if suffix.is_empty() {
+            (4, "")
+        } else {
+            [
+                ("alpha", 1),
+                ("beta", 2),
+                ("rc", 3),
+                ("patch", 4),
+                ("pl", 4),
+                ("a", 1),
+                ("b", 2),
+                ("p", 4),
+            ]
+            .iter()
+            .find_map(|(prefix, rank)| suffix.strip_prefix(prefix).map(|rest| (*rank, rest)))?
+        };
+        let rest = rest.trim_start_matches(['.', '-']);
+        let number = if rest.is_empty() {
+            0
+        } else {
+            rest.parse().ok()?
+        };
+
+        Some(Self {
+            numbers,
+            stability: (rank, number),
+        })
+    }
+
+    /// The lowest version with the same numbers, including pre-releases.
+    fn dev(mut self) -> Self {
+        self.stability = (0, 0);
+        self
+    }
+
+    /// Increments component `index` and zeroes the ones after it.
+    fn next_at(&self, index: usize) -> Self {
+        let mut numbers = self.numbers;
+        numbers[index] += 1;
+        for number in numbers.iter_mut().skip(index + 1) {
+            *number = 0;
+        }
+        Self {
+            numbers,
+            stability: (4, 0),
+        }
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.numbers
+            .cmp(&other.numbers)
+            .then(self.stability.cmp(&other.stability))
+    }
+}
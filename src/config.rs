@@ -0,0 +1,94 @@
+use crate::models::Config;
+use crate::{Error, Result};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+/// Where `set_php_config` saves the configuration chosen at runtime.
+pub fn config_file_path() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("tauri-php-plugin")
+        .join("config.json")
+}
+
+/// Layers the saved configuration at `path` over `base`, the configuration
+/// from `tauri.conf.json`. Keys missing from the saved file keep the value
+/// from `base`; a missing or unreadable file leaves `base` unchanged.
+pub fn load_config(base: Config, path: &Path) -> Config {
+    let saved = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(_) => return base,
+    };
+
+    let merged = serde_json::from_str::<Value>(&saved)
+        .map_err(Error::from)
+        .and_then(|saved| {
+            let mut merged = serde_json::to_value(&base)?;
+            merge_json(&mut merged, saved);
+            Ok(serde_json::from_value(merged)?)
+        });
+    match merged {
+        Ok(config) => config,
+        Err(e) => {
+            log::warn!("Ignoring invalid config {}: {}", path.display(), e);
+            base
+        }
+    }
+}
+
+/// Saves `config` to `path`, replacing the previous file atomically.
+pub fn save_config(config: &Config, path: &Path) -> Result<()> {
+    validate_config(config)?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let temp_path = path.with_extension(format!("json.{}.tmp", uuid::Uuid::new_v4()));
+    std::fs::write(&temp_path, serde_json::to_string_pretty(config)?)?;
+    std::fs::rename(&temp_path, path).map_err(|e| {
+        let _ = std::fs::remove_file(&temp_path);
+        Error::Io(format!("Failed to save config {}: {}", path.display(), e))
+    })
+}
+
+pub fn validate_config(config: &Config) -> Result<()> {
+    if config.server_config.default_port == 0 {
+        return Err(Error::Config("Default port must not be 0".to_string()));
+    }
+    if config.server_config.default_host.trim().is_empty() {
+        return Err(Error::Config("Default host must not be empty".to_string()));
+    }
+    if config.download_base_url.trim().is_empty() {
+        return Err(Error::Config(
+            "Download base URL must not be empty".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// The directory projects are cloned into, with `~/` expanded; None means the
+/// default `~/tauri-php-projects`.
+pub fn projects_directory(config: &Config) -> Option<PathBuf> {
+    config
+        .projects_dir
+        .as_deref()
+        .map(str::trim)
+        .filter(|dir| !dir.is_empty())
+        .map(crate::git::expand_home)
+}
+
+fn merge_json(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Object(base), Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_json(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
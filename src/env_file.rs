@@ -0,0 +1,95 @@
+use crate::Result;
+use std::fs;
+use std::path::Path;
+
+/// Reads `key` from the contents of a dotenv file, unquoting the value.
+/// Commented-out assignments are ignored.
+pub fn get_value(content: &str, key: &str) -> Option<String> {
+    content.lines().rev().find_map(|line| {
+        let (name, value) = parse_line(line)?;
+        (name == key).then(|| unquote(value))
+    })
+}
+
+/// Sets `values` in the dotenv file at `path`, replacing existing assignments
+/// in place and appending new keys. Other lines are kept as they are.
+pub fn set_values(path: &Path, values: &[(&str, String)]) -> Result<()> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+    for (key, value) in values {
+        let assignment = format!("{}={}", key, quote(value));
+        match lines
+            .iter()
+            .position(|line| parse_line(line).is_some_and(|(name, _)| name == *key))
+        {
+            Some(index) => lines[index] = assignment,
+            None => lines.push(assignment),
+        }
+    }
+
+    let mut content = lines.join("\n");
+    content.push('\n');
+    fs::write(path, content)?;
+    Ok(())
+}
+
+fn parse_line(line: &str) -> Option<(&str, &str)> {
+    let line = line.trim_start();
+    if line.starts_with('#') {
+        return None;
+    }
+    let line = line.strip_prefix("export ").unwrap_or(line);
+    let (name, value) = line.split_once('=')?;
+    Some((name.trim(), value.trim()))
+}
+
+fn unquote(value: &str) -> String {
+    if let Some(rest) = value.strip_prefix('"') {
+        let mut unquoted = String::new();
+        let mut chars = rest.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => break,
+                '\\' => match chars.next() {
+                    Some('n') => unquoted.push('\n'),
+                    Some(escaped) => unquoted.push(escaped),
+                    None => {}
+                },
+                c => unquoted.push(c),
+            }
+        }
+        return unquoted;
+    }
+    if let Some(rest) = value.strip_prefix('\'') {
+        return rest.split('\'').next().unwrap_or_default().to_string();
+    }
+    // Unquoted values end at an inline comment
+    value
+        .split(" #")
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_string()
+}
+
+fn quote(value: &str) -> String {
+    let plain = value
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || "_-.:/@+,".contains(c));
+    if plain {
+        value.to_string()
+    } else {
+        format!(
+            "\"{}\"",
+            value
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n")
+        )
+    }
+}
@@ -19,6 +19,12 @@ pub enum Error {
     #[error("PHP server error: {0}")]
     PhpServer(String),
 
+    #[error("No available port in range {0}-{1}")]
+    NoAvailablePort(u16, u16),
+
+    #[error("TLS error: {0}")]
+    Tls(String),
+
     #[error("Git error: {0}")]
     Git(String),
 
@@ -37,6 +43,12 @@ pub enum Error {
     #[error("Invalid URL: {0}")]
     InvalidUrl(String),
 
+    #[error("Operation cancelled: {0}")]
+    Cancelled(String),
+
+    #[error("Missing PHP extensions: {}", .0.join(", "))]
+    MissingExtensions(Vec<String>),
+
     #[error("Permission denied: {0}")]
     PermissionDenied(String),
 
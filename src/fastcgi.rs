@@ -0,0 +1,175 @@
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+const FCGI_VERSION_1: u8 = 1;
+
+const FCGI_BEGIN_REQUEST: u8 = 1;
+const FCGI_END_REQUEST: u8 = 3;
+const FCGI_PARAMS: u8 = 4;
+const FCGI_STDIN: u8 = 5;
+const FCGI_STDOUT: u8 = 6;
+const FCGI_STDERR: u8 = 7;
+
+const FCGI_RESPONDER: u16 = 1;
+
+/// Requests are never multiplexed, every connection carries exactly one.
+const REQUEST_ID: u16 = 1;
+
+const MAX_RECORD_CONTENT: usize = 65535;
+
+/// Everything a FastCGI application wrote for one request.
+#[derive(Debug, Default)]
+pub struct FastCgiOutput {
+    pub stdout: Vec<u8>,
+    pub stderr: Vec<u8>,
+    pub app_status: u32,
+}
+
+/// An HTTP response decoded from CGI output.
+#[derive(Debug)]
+pub struct CgiResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+/// Sends one responder request over `stream` and reads the application's
+/// output until it ends the request.
+pub async fn execute<S>(
+    stream: &mut S,
+    params: &[(String, String)],
+    stdin: &[u8],
+) -> std::io::Result<FastCgiOutput>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let mut request = Vec::with_capacity(1024 + stdin.len());
+
+    let mut begin_body = [0u8; 8];
+    begin_body[..2].copy_from_slice(&FCGI_RESPONDER.to_be_bytes());
+    write_record(&mut request, FCGI_BEGIN_REQUEST, &begin_body);
+
+    let encoded_params = encode_params(params);
+    for chunk in encoded_params.chunks(MAX_RECORD_CONTENT) {
+        write_record(&mut request, FCGI_PARAMS, chunk);
+    }
+    write_record(&mut request, FCGI_PARAMS, &[]);
+
+    for chunk in stdin.chunks(MAX_RECORD_CONTENT) {
+        write_record(&mut request, FCGI_STDIN, chunk);
+    }
+    write_record(&mut request, FCGI_STDIN, &[]);
+
+    stream.write_all(&request).await?;
+    stream.flush().await?;
+
+    let mut output = FastCgiOutput::default();
+    loop {
+        let mut header = [0u8; 8];
+        stream.read_exact(&mut header).await?;
+
+        let record_type = header[1];
+        let content_length = u16::from_be_bytes([header[4], header[5]]) as usize;
+        let padding_length = header[6] as usize;
+
+        let mut content = vec![0u8; content_length + padding_length];
+        stream.read_exact(&mut content).await?;
+        content.truncate(content_length);
+
+        match record_type {
+            FCGI_STDOUT => output.stdout.extend_from_slice(&content),
+            FCGI_STDERR => output.stderr.extend_from_slice(&content),
+            FCGI_END_REQUEST => {
+                if content.len() >= 4 {
+                    output.app_status =
+                        u32::from_be_bytes([content[0], content[1], content[2], content[3]]);
+                }
+                return Ok(output);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn write_record(buffer: &mut Vec<u8>, record_type: u8, content: &[u8]) {
+    let padding_length = (8 - content.len() % 8) % 8;
+    buffer.push(FCGI_VERSION_1);
+    buffer.push(record_type);
+    buffer.extend_from_slice(&REQUEST_ID.to_be_bytes());
+    buffer.extend_from_slice(&(content.len() as u16).to_be_bytes());
+    buffer.push(padding_length as u8);
+    buffer.push(0);
+    buffer.extend_from_slice(content);
+    buffer.resize(buffer.len() + padding_length, 0);
+}
+
+/// Encodes name-value pairs using the FastCGI length prefixes: one byte for
+/// lengths below 128, otherwise four bytes with the high bit set.
+pub fn encode_params(params: &[(String, String)]) -> Vec<u8> {
+    let mut encoded = Vec::new();
+    for (name, value) in params {
+        encode_length(&mut encoded, name.len());
+        encode_length(&mut encoded, value.len());
+        encoded.extend_from_slice(name.as_bytes());
+        encoded.extend_from_slice(value.as_bytes());
+    }
+    encoded
+}
+
+fn encode_length(buffer: &mut Vec<u8>, length: usize) {
+    if length < 128 {
+        buffer.push(length as u8);
+    } else {
+        buffer.extend_from_slice(&((length as u32) | 0x8000_0000).to_be_bytes());
+    }
+}
+
+/// Splits CGI output into status, headers and body. A `Status` header sets the
+/// response code, `Location` without one implies a redirect, as in RFC 3875.
+pub fn parse_cgi_response(stdout: &[u8]) -> CgiResponse {
+    let (head, body) = match find_header_end(stdout) {
+        Some((head_end, body_start)) => (&stdout[..head_end], &stdout[body_start..]),
+        None => (&[][..], stdout),
+    };
+
+    let mut status = None;
+    let mut has_location = false;
+    let mut headers = Vec::new();
+    for line in String::from_utf8_lossy(head).lines() {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let name = name.trim();
+        let value = value.trim();
+
+        if name.eq_ignore_ascii_case("status") {
+            status = value
+                .split_whitespace()
+                .next()
+                .and_then(|code| code.parse::<u16>().ok());
+            continue;
+        }
+        if name.eq_ignore_ascii_case("location") {
+            has_location = true;
+        }
+        headers.push((name.to_string(), value.to_string()));
+    }
+
+    CgiResponse {
+        status: status.unwrap_or(if has_location { 302 } else { 200 }),
+        headers,
+        body: body.to_vec(),
+    }
+}
+
+/// Returns where the header block ends and where the body starts, accepting
+/// both CRLF and bare LF line endings.
+fn find_header_end(data: &[u8]) -> Option<(usize, usize)> {
+    let crlf = data.windows(4).position(|w| w == b"\r\n\r\n");
+    let lf = data.windows(2).position(|w| w == b"\n\n");
+    match (crlf, lf) {
+        (Some(c), Some(l)) if l < c => Some((l, l + 2)),
+        (Some(c), _) => Some((c, c + 4)),
+        (None, Some(l)) => Some((l, l + 2)),
+        (None, None) => None,
+    }
+}
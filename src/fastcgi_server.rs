@@ -0,0 +1,973 @@
+use crate::fastcgi::{self, FastCgiOutput};
+use crate::models::{FastCgiOptions, ServerBackend};
+use crate::proxy::{error_response, ProxyBody};
+use crate::{Error, Result};
+use bytes::Bytes;
+use http_body_util::{BodyExt, Full};
+use hyper::body::Incoming;
+use hyper::header::{HeaderName, HeaderValue, CONTENT_LENGTH, CONTENT_TYPE, HOST};
+use hyper::service::service_fn;
+use hyper::{Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use percent_encoding::percent_decode_str;
+use std::collections::{BTreeMap, VecDeque};
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::path::{Component, Path, PathBuf};
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::process::Command;
+use tokio::sync::{watch, Notify};
+use tokio::task::JoinHandle;
+
+/// How long to wait for the first worker to accept connections.
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Workers that fail sooner than this after being spawned are considered to be
+/// crashing and are restarted with a longer delay. php-cgi exits successfully
+/// when it reaches `PHP_FCGI_MAX_REQUESTS`, which is restarted immediately.
+const MIN_WORKER_LIFETIME: Duration = Duration::from_secs(1);
+
+/// A worker that crashed is unreachable until its supervisor notices and
+/// restarts it, connections are retried for up to
+/// `CONNECT_ATTEMPTS * CONNECT_RETRY_DELAY`.
+const CONNECT_ATTEMPTS: u32 = 40;
+const CONNECT_RETRY_DELAY: Duration = Duration::from_millis(50);
+
+pub struct FastCgiServerConfig {
+    pub backend: ServerBackend,
+    pub php_executable: PathBuf,
+    pub listen_addr: SocketAddr,
+    pub project_path: PathBuf,
+    pub document_root: PathBuf,
+    pub router_script: Option<String>,
+    pub options: FastCgiOptions,
+    /// Extra environment variables for the worker processes
+    pub env: BTreeMap<String, String>,
+}
+
+/// An HTTP front end that executes PHP through a pool of FastCGI workers.
+///
+/// With `ServerBackend::PhpCgi` every worker is a separate `php-cgi` process
+/// serving one request at a time and restarted after
+/// `max_requests_per_worker` requests. With `ServerBackend::PhpFpm` a single
+/// `php-fpm` master manages `workers` static children itself.
+pub struct FastCgiServer {
+    backend: ServerBackend,
+    local_addr: SocketAddr,
+    shutdown_tx: watch::Sender<bool>,
+    front_end: JoinHandle<()>,
+    supervisors: Vec<JoinHandle<()>>,
+    pids: Arc<Mutex<Vec<Option<u32>>>>,
+    fpm_config: Option<PathBuf>,
+}
+
+impl FastCgiServer {
+    pub async fn start(config: FastCgiServerConfig) -> Result<Self> {
+        let workers = config.options.workers.max(1);
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        let mut supervisors = Vec::new();
+        let mut fpm_config = None;
+
+        let (pool, pids) = match config.backend {
+            ServerBackend::PhpCgi => {
+                let php_cgi = locate_sibling_binary(&config.php_executable, "php-cgi")?;
+                let worker_addrs = (0..workers)
+                    .map(|_| free_loopback_addr())
+                    .collect::<Result<Vec<_>>>()?;
+                let pool = Arc::new(WorkerPool::new(
+                    worker_addrs.clone(),
+                    false,
+                    Some(config.options.max_requests_per_worker),
+                ));
+                let pids = Arc::new(Mutex::new(vec![None; workers]));
+
+                for (slot, addr) in worker_addrs.iter().enumerate() {
+                    let mut cmd = Command::new(&php_cgi);
+                    cmd.arg("-b")
+                        .arg(addr.to_string())
+                        // The pool counts requests and recycles workers itself, a
+                        // php-cgi exiting on its own could drop a queued connection
+                        .env("PHP_FCGI_CHILDREN", "0")
+                        .env("PHP_FCGI_MAX_REQUESTS", "0")
+                        .envs(&config.env)
+                        .current_dir(&config.project_path);
+
+                    supervisors.push(supervise(
+                        cmd,
+                        slot,
+                        pids.clone(),
+                        Some(pool.clone()),
+                        shutdown_rx.clone(),
+                    ));
+                }
+
+                (pool, pids)
+            }
+            ServerBackend::PhpFpm => {
+                if cfg!(windows) {
+                    return Err(Error::PhpServer(
+                        "php-fpm is not available on Windows, use the php-cgi backend".to_string(),
+                    ));
+                }
+                let php_fpm = locate_sibling_binary(&config.php_executable, "php-fpm")?;
+                let addr = free_loopback_addr()?;
+                let config_path = write_fpm_config(addr, workers, &config.options)?;
+                let pids = Arc::new(Mutex::new(vec![None]));
+
+                let mut cmd = Command::new(&php_fpm);
+                cmd.arg("--nodaemonize")
+                    .arg("--allow-to-run-as-root")
+                    .arg("--fpm-config")
+                    .arg(&config_path)
+                    // Passed on to the children since the pool config sets clear_env = no
+                    .envs(&config.env)
+                    .current_dir(&config.project_path);
+
+                // php-fpm spawns and recycles its children itself, the pool only
+                // bounds concurrency to the number of children
+                let pool = Arc::new(WorkerPool::new(vec![addr; workers], true, None));
+                supervisors.push(supervise(cmd, 0, pids.clone(), None, shutdown_rx.clone()));
+                fpm_config = Some(config_path);
+
+                (pool, pids)
+            }
+            ServerBackend::BuiltIn => {
+                return Err(Error::PhpServer(
+                    "The built-in server does not use FastCGI".to_string(),
+                ))
+            }
+        };
+
+        let mut server = Self {
+            backend: config.backend,
+            local_addr: config.listen_addr,
+            shutdown_tx,
+            front_end: tokio::spawn(async {}),
+            supervisors,
+            pids,
+            fpm_config,
+        };
+
+        if !server.wait_for_worker(&pool).await {
+            server.shutdown().await;
+            return Err(Error::PhpServer(
+                "FastCGI workers did not start accepting connections".to_string(),
+            ));
+        }
+
+        let listener = match TcpListener::bind(config.listen_addr).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                server.shutdown().await;
+                return Err(Error::PhpServer(format!(
+                    "Failed to bind FastCGI front end on {}: {}",
+                    config.listen_addr, e
+                )));
+            }
+        };
+        server.local_addr = listener.local_addr()?;
+
+        let front_end = Arc::new(FrontEnd {
+            pool,
+            document_root: config.document_root,
+            router_script: config.router_script,
+            local_addr: server.local_addr,
+            request_timeout: Duration::from_secs(config.options.request_timeout_secs.max(1)),
+        });
+        server.front_end = tokio::spawn(serve(listener, front_end, shutdown_rx));
+
+        Ok(server)
+    }
+
+    /// Waits until at least one worker accepts connections, giving up early
+    /// when every worker process failed to spawn.
+    async fn wait_for_worker(&self, pool: &WorkerPool) -> bool {
+        let started = Instant::now();
+        while started.elapsed() < STARTUP_TIMEOUT {
+            if let Some(addr) = pool.first_idle_addr() {
+                if TcpStream::connect(addr).await.is_ok() {
+                    return true;
+                }
+            }
+            if self.supervisors.iter().all(|s| s.is_finished()) {
+                return false;
+            }
+            tokio::time::sleep(CONNECT_RETRY_DELAY).await;
+        }
+        false
+    }
+
+    pub fn backend(&self) -> ServerBackend {
+        self.backend
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// PID of the php-fpm master, or of the first php-cgi worker.
+    pub fn pid(&self) -> Option<u32> {
+        self.pids
+            .lock()
+            .ok()
+            .and_then(|pids| pids.iter().flatten().next().copied())
+    }
+
+    pub fn is_running(&self) -> bool {
+        !self.front_end.is_finished() && self.supervisors.iter().any(|s| !s.is_finished())
+    }
+
+    /// Stops the front end and all workers and waits for them to exit.
+    pub async fn shutdown(&mut self) {
+        let _ = self.shutdown_tx.send(true);
+        self.front_end.abort();
+        for supervisor in self.supervisors.drain(..) {
+            let _ = supervisor.await;
+        }
+        if let Some(fpm_config) = self.fpm_config.take() {
+            remove_fpm_files(&fpm_config);
+        }
+    }
+
+    /// Non-blocking variant of `shutdown` for use from synchronous code. Worker
+    /// processes are spawned with `kill_on_drop`, so they are terminated when
+    /// their supervisors are cancelled.
+    pub fn start_shutdown(&mut self) {
+        let _ = self.shutdown_tx.send(true);
+        self.front_end.abort();
+        for supervisor in &self.supervisors {
+            supervisor.abort();
+        }
+        if let Some(fpm_config) = self.fpm_config.take() {
+            remove_fpm_files(&fpm_config);
+        }
+    }
+}
+
+impl Drop for FastCgiServer {
+    fn drop(&mut self) {
+        self.start_shutdown();
+    }
+}
+
+/// Keeps one worker process alive, respawning it whenever it exits until
+/// shutdown is requested. With a pool, the worker is only handed out once it
+/// accepts connections and is restarted when the pool asks for it.
+fn supervise(
+    mut cmd: Command,
+    slot: usize,
+    pids: Arc<Mutex<Vec<Option<u32>>>>,
+    pool: Option<Arc<WorkerPool>>,
+    mut shutdown_rx: watch::Receiver<bool>,
+) -> JoinHandle<()> {
+    cmd.stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .kill_on_drop(true);
+
+    tokio::spawn(async move {
+        loop {
+            let spawned_at = Instant::now();
+            let mut child = match cmd.spawn() {
+                Ok(child) => child,
+                Err(e) => {
+                    log::error!("Failed to spawn FastCGI worker: {}", e);
+                    if let Some(pool) = &pool {
+                        pool.mark_stopped(slot);
+                    }
+                    break;
+                }
+            };
+            set_pid(&pids, slot, child.id());
+
+            if let Some(pool) = &pool {
+                tokio::select! {
+                    reachable = wait_until_reachable(pool.addrs[slot], STARTUP_TIMEOUT) => {
+                        if reachable {
+                            pool.mark_ready(slot);
+                        }
+                    }
+                    _ = shutdown_rx.changed() => {
+                        let _ = child.kill().await;
+                        break;
+                    }
+                }
+            }
+
+            let exited_cleanly = tokio::select! {
+                status = child.wait() => {
+                    log::debug!("FastCGI worker {} exited with {:?}, restarting", slot, status);
+                    if let Some(pool) = &pool {
+                        pool.mark_down(slot);
+                    }
+                    status.is_ok_and(|status| status.success())
+                }
+                _ = recycle_requested(pool.as_deref(), slot) => {
+                    let _ = child.kill().await;
+                    true
+                }
+                _ = shutdown_rx.changed() => {
+                    let _ = child.kill().await;
+                    break;
+                }
+            };
+            set_pid(&pids, slot, None);
+
+            if *shutdown_rx.borrow() {
+                break;
+            }
+            if !exited_cleanly && spawned_at.elapsed() < MIN_WORKER_LIFETIME {
+                tokio::time::sleep(Duration::from_secs(1)).await;
+            }
+        }
+        set_pid(&pids, slot, None);
+    })
+}
+
+async fn recycle_requested(pool: Option<&WorkerPool>, slot: usize) {
+    match pool {
+        Some(pool) => pool.recycle[slot].notified().await,
+        None => std::future::pending().await,
+    }
+}
+
+fn set_pid(pids: &Mutex<Vec<Option<u32>>>, slot: usize, pid: Option<u32>) {
+    if let Ok(mut pids) = pids.lock() {
+        if let Some(entry) = pids.get_mut(slot) {
+            *entry = pid;
+        }
+    }
+}
+
+/// Finds `php-cgi` or `php-fpm` installed alongside the PHP CLI binary. Some
+/// distributions put php-fpm in `sbin` next to `bin`.
+pub fn locate_sibling_binary(php_executable: &Path, name: &str) -> Result<PathBuf> {
+    let file_name = if cfg!(windows) {
+        format!("{}.exe", name)
+    } else {
+        name.to_string()
+    };
+
+    let bin_dir = php_executable.parent().unwrap_or_else(|| Path::new("."));
+    let mut candidates = vec![bin_dir.join(&file_name)];
+    if let Some(prefix) = bin_dir.parent() {
+        candidates.push(prefix.join("sbin").join(&file_name));
+    }
+
+    candidates
+        .into_iter()
+        .find(|candidate| candidate.is_file())
+        .ok_or_else(|| {
+            Error::PhpServer(format!(
+                "{} was not found next to {}",
+                name,
+                php_executable.display()
+            ))
+        })
+}
+
+fn free_loopback_addr() -> Result<SocketAddr> {
+    let listener = std::net::TcpListener::bind(("127.0.0.1", 0))?;
+    Ok(listener.local_addr()?)
+}
+
+fn write_fpm_config(
+    listen: SocketAddr,
+    workers: usize,
+    options: &FastCgiOptions,
+) -> Result<PathBuf> {
+    let config_dir = std::env::temp_dir().join("tauri-php-plugin");
+    std::fs::create_dir_all(&config_dir)?;
+    let config_path = config_dir.join(format!("php-fpm-{}.conf", uuid::Uuid::new_v4()));
+    // A file rather than /proc/self/fd/2, which macOS does not have
+    let error_log = config_path.with_extension("log");
+
+    let config = format!(
+        "[global]\n\
+         daemonize = no\n\
+         error_log = {error_log}\n\
+         \n\
+         [www]\n\
+         listen = {listen}\n\
+         pm = static\n\
+         pm.max_children = {workers}\n\
+         pm.max_requests = {max_requests}\n\
+         clear_env = no\n\
+         catch_workers_output = yes\n",
+        error_log = error_log.display(),
+        listen = listen,
+        workers = workers,
+        max_requests = options.max_requests_per_worker,
+    );
+    std::fs::write(&config_path, config)?;
+    Ok(config_path)
+}
+
+/// Removes a php-fpm config and the error log written next to it.
+fn remove_fpm_files(config_path: &Path) {
+    let _ = std::fs::remove_file(config_path.with_extension("log"));
+    let _ = std::fs::remove_file(config_path);
+}
+
+async fn wait_until_reachable(addr: SocketAddr, timeout: Duration) -> bool {
+    let started = Instant::now();
+    while started.elapsed() < timeout {
+        if TcpStream::connect(addr).await.is_ok() {
+            return true;
+        }
+        tokio::time::sleep(CONNECT_RETRY_DELAY).await;
+    }
+    false
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WorkerState {
+    /// Not accepting connections yet, or being restarted
+    Down,
+    Idle,
+    Busy,
+    /// Its supervisor gave up on it
+    Stopped,
+}
+
+struct PoolState {
+    idle: VecDeque<usize>,
+    workers: Vec<WorkerState>,
+    served: Vec<u32>,
+}
+
+/// Hands out workers one request at a time. Workers are used round-robin,
+/// and with `max_requests` set a worker is taken out of rotation and restarted
+/// by its supervisor after serving that many requests.
+struct WorkerPool {
+    addrs: Vec<SocketAddr>,
+    state: Mutex<PoolState>,
+    available: Notify,
+    recycle: Vec<Notify>,
+    max_requests: Option<u32>,
+}
+
+impl WorkerPool {
+    fn new(addrs: Vec<SocketAddr>, ready: bool, max_requests: Option<u32>) -> Self {
+        let count = addrs.len();
+        let (idle, initial) = if ready {
+            ((0..count).collect(), WorkerState::Idle)
+        } else {
+            (VecDeque::new(), WorkerState::Down)
+        };
+
+        Self {
+            addrs,
+            state: Mutex::new(PoolState {
+                idle,
+                workers: vec![initial; count],
+                served: vec![0; count],
+            }),
+            available: Notify::new(),
+            recycle: (0..count).map(|_| Notify::new()).collect(),
+            max_requests: max_requests.filter(|max| *max > 0),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, PoolState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn first_idle_addr(&self) -> Option<SocketAddr> {
+        self.lock().idle.front().map(|slot| self.addrs[*slot])
+    }
+
+    /// Waits for an idle worker; None once every worker has stopped.
+    async fn checkout(&self) -> Option<Checkout<'_>> {
+        loop {
+            let available = self.available.notified();
+            {
+                let mut state = self.lock();
+                if let Some(slot) = state.idle.pop_front() {
+                    state.workers[slot] = WorkerState::Busy;
+                    return Some(Checkout { pool: self, slot });
+                }
+                if state
+                    .workers
+                    .iter()
+                    .all(|worker| *worker == WorkerState::Stopped)
+                {
+                    return None;
+                }
+            }
+            available.await;
+        }
+    }
+
+    fn checkin(&self, slot: usize) {
+        let mut state = self.lock();
+        // A worker that died while busy is already marked down
+        if state.workers[slot] != WorkerState::Busy {
+            return;
+        }
+
+        state.served[slot] += 1;
+        if self
+            .max_requests
+            .is_some_and(|max| state.served[slot] >= max)
+        {
+            state.workers[slot] = WorkerState::Down;
+            self.recycle[slot].notify_one();
+        } else {
+            state.workers[slot] = WorkerState::Idle;
+            state.idle.push_back(slot);
+            self.available.notify_one();
+        }
+    }
+
+    fn mark_ready(&self, slot: usize) {
+        let mut state = self.lock();
+        state.served[slot] = 0;
+        if state.workers[slot] != WorkerState::Idle {
+            state.workers[slot] = WorkerState::Idle;
+            state.idle.push_back(slot);
+            self.available.notify_one();
+        }
+    }
+
+    fn mark_down(&self, slot: usize) {
+        let mut state = self.lock();
+        if state.workers[slot] == WorkerState::Idle {
+            state.idle.retain(|idle| *idle != slot);
+        }
+        state.workers[slot] = WorkerState::Down;
+    }
+
+    /// Takes a worker out of the pool for good, waking the requests waiting
+    /// for one so they can give up when it was the last.
+    fn mark_stopped(&self, slot: usize) {
+        {
+            let mut state = self.lock();
+            state.idle.retain(|idle| *idle != slot);
+            state.workers[slot] = WorkerState::Stopped;
+        }
+        self.available.notify_waiters();
+    }
+
+    /// Runs a request on the next idle worker; None when no worker is left.
+    async fn execute(
+        &self,
+        params: &[(String, String)],
+        stdin: &[u8],
+    ) -> Option<std::io::Result<FastCgiOutput>> {
+        let checkout = self.checkout().await?;
+        Some(Self::execute_on(self.addrs[checkout.slot], params, stdin).await)
+    }
+
+    async fn execute_on(
+        addr: SocketAddr,
+        params: &[(String, String)],
+        stdin: &[u8],
+    ) -> std::io::Result<FastCgiOutput> {
+        let mut attempts = 0;
+        let mut stream = loop {
+            match TcpStream::connect(addr).await {
+                Ok(stream) => break stream,
+                Err(e) if attempts < CONNECT_ATTEMPTS => {
+                    attempts += 1;
+                    log::trace!("FastCGI worker {} not reachable yet: {}", addr, e);
+                    tokio::time::sleep(CONNECT_RETRY_DELAY).await;
+                }
+                Err(e) => return Err(e),
+            }
+        };
+        fastcgi::execute(&mut stream, params, stdin).await
+    }
+}
+
+/// Returns the worker to the pool when the request finishes or is cancelled.
+struct Checkout<'a> {
+    pool: &'a WorkerPool,
+    slot: usize,
+}
+
+impl Drop for Checkout<'_> {
+    fn drop(&mut self) {
+        self.pool.checkin(self.slot);
+    }
+}
+
+struct FrontEnd {
+    pool: Arc<WorkerPool>,
+    document_root: PathBuf,
+    router_script: Option<String>,
+    local_addr: SocketAddr,
+    /// Bounds waiting for a worker plus the FastCGI exchange
+    request_timeout: Duration,
+}
+
+async fn serve(
+    listener: TcpListener,
+    front_end: Arc<FrontEnd>,
+    mut shutdown_rx: watch::Receiver<bool>,
+) {
+    loop {
+        let (stream, client_addr) = tokio::select! {
+            _ = shutdown_rx.changed() => break,
+            accepted = listener.accept() => match accepted {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    log::warn!("FastCGI front end failed to accept connection: {}", e);
+                    continue;
+                }
+            },
+        };
+
+        let front_end = front_end.clone();
+        tokio::spawn(async move {
+            let service = service_fn(move |req| {
+                let front_end = front_end.clone();
+                async move { Ok::<_, Infallible>(front_end.handle(client_addr, req).await) }
+            });
+
+            if let Err(e) = hyper::server::conn::http1::Builder::new()
+                .serve_connection(TokioIo::new(stream), service)
+                .await
+            {
+                log::debug!(
+                    "FastCGI front end connection from {} ended: {}",
+                    client_addr,
+                    e
+                );
+            }
+        });
+    }
+}
+
+/// What a request path maps to inside the document root.
+#[derive(Debug, PartialEq, Eq)]
+pub enum RequestTarget {
+    Static(PathBuf),
+    Script {
+        script_filename: PathBuf,
+        script_name: String,
+        path_info: Option<String>,
+    },
+    NotFound,
+}
+
+/// Maps a decoded request path to a file the way `php -S` does: existing files
+/// are served (or executed when they are PHP scripts), otherwise the router
+/// script, a script earlier in the path (`/index.php/foo`), or the front
+/// controller `index.php` handles the request.
+pub fn resolve_request_path(
+    document_root: &Path,
+    router_script: Option<&str>,
+    path: &str,
+) -> RequestTarget {
+    let relative = path.trim_start_matches('/');
+    if !is_inside_root(relative) {
+        return RequestTarget::NotFound;
+    }
+
+    let candidate = document_root.join(relative);
+    if candidate.is_file() {
+        return if is_php_script(&candidate) {
+            script_target(document_root, relative, None)
+        } else {
+            RequestTarget::Static(candidate)
+        };
+    }
+
+    if candidate.is_dir() && router_script.is_none() {
+        let dir = relative.trim_end_matches('/');
+        let index_php = if dir.is_empty() {
+            "index.php".to_string()
+        } else {
+            format!("{}/index.php", dir)
+        };
+        if document_root.join(&index_php).is_file() {
+            return script_target(document_root, &index_php, None);
+        }
+        let index_html = candidate.join("index.html");
+        if index_html.is_file() {
+            return RequestTarget::Static(index_html);
+        }
+    }
+
+    if let Some(router) = router_script {
+        return script_target(document_root, router, None);
+    }
+
+    let segments: Vec<&str> = relative.split('/').collect();
+    for split in (1..segments.len()).rev() {
+        let script = segments[..split].join("/");
+        let script_path = document_root.join(&script);
+        if script_path.is_file() && is_php_script(&script_path) {
+            let path_info = format!("/{}", segments[split..].join("/"));
+            return script_target(document_root, &script, Some(path_info));
+        }
+    }
+
+    if document_root.join("index.php").is_file() {
+        return script_target(document_root, "index.php", Some(path.to_string()));
+    }
+
+    RequestTarget::NotFound
+}
+
+/// Whether a request path stays in the document root once joined onto it.
+/// Backslashes and drive prefixes are rejected on every platform, since
+/// Windows treats them as separators and roots. Symlinks inside the root,
+/// such as Laravel's `public/storage`, are followed as `php -S` does.
+fn is_inside_root(relative: &str) -> bool {
+    !relative.contains(['\\', ':', '\0'])
+        && Path::new(relative)
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+}
+
+fn script_target(document_root: &Path, relative: &str, path_info: Option<String>) -> RequestTarget {
+    RequestTarget::Script {
+        script_filename: document_root.join(relative),
+        script_name: format!("/{}", relative.trim_start_matches('/')),
+        path_info,
+    }
+}
+
+fn is_php_script(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "php")
+}
+
+impl FrontEnd {
+    async fn handle(&self, client_addr: SocketAddr, req: Request<Incoming>) -> Response<ProxyBody> {
+        let decoded_path = percent_decode_str(req.uri().path())
+            .decode_utf8_lossy()
+            .to_string();
+
+        match resolve_request_path(
+            &self.document_root,
+            self.router_script.as_deref(),
+            &decoded_path,
+        ) {
+            RequestTarget::Static(path) => serve_static(&path).await,
+            RequestTarget::Script {
+                script_filename,
+                script_name,
+                path_info,
+            } => {
+                let (parts, body) = req.into_parts();
+                let body = match body.collect().await {
+                    Ok(collected) => collected.to_bytes(),
+                    Err(e) => return error_response(StatusCode::BAD_REQUEST, &e.to_string()),
+                };
+
+                let params = self.build_params(
+                    &parts,
+                    client_addr,
+                    &script_filename,
+                    &script_name,
+                    path_info.as_deref(),
+                    body.len(),
+                );
+
+                let executed =
+                    tokio::time::timeout(self.request_timeout, self.pool.execute(&params, &body))
+                        .await;
+                match executed {
+                    Err(_) => {
+                        log::warn!("FastCGI request timed out after {:?}", self.request_timeout);
+                        error_response(StatusCode::GATEWAY_TIMEOUT, "PHP did not respond in time")
+                    }
+                    Ok(None) => error_response(
+                        StatusCode::SERVICE_UNAVAILABLE,
+                        "No FastCGI workers are running",
+                    ),
+                    Ok(Some(Ok(output))) => {
+                        if !output.stderr.is_empty() {
+                            log::warn!(
+                                "PHP: {}",
+                                String::from_utf8_lossy(&output.stderr).trim_end()
+                            );
+                        }
+                        cgi_to_http(&output.stdout)
+                    }
+                    Ok(Some(Err(e))) => {
+                        log::warn!("FastCGI request failed: {}", e);
+                        error_response(StatusCode::BAD_GATEWAY, "FastCGI worker is not reachable")
+                    }
+                }
+            }
+            RequestTarget::NotFound => error_response(StatusCode::NOT_FOUND, "Not Found"),
+        }
+    }
+
+    fn build_params(
+        &self,
+        parts: &hyper::http::request::Parts,
+        client_addr: SocketAddr,
+        script_filename: &Path,
+        script_name: &str,
+        path_info: Option<&str>,
+        content_length: usize,
+    ) -> Vec<(String, String)> {
+        let query = parts.uri.query().unwrap_or("").to_string();
+        let request_uri = parts
+            .uri
+            .path_and_query()
+            .map(|p| p.as_str().to_string())
+            .unwrap_or_else(|| "/".to_string());
+        let server_name = parts
+            .headers
+            .get(HOST)
+            .and_then(|h| h.to_str().ok())
+            .map(|h| {
+                h.rsplit_once(':')
+                    .map(|(name, _)| name)
+                    .unwrap_or(h)
+                    .to_string()
+            })
+            .unwrap_or_else(|| self.local_addr.ip().to_string());
+
+        let mut params = vec![
+            ("GATEWAY_INTERFACE".to_string(), "CGI/1.1".to_string()),
+            (
+                "SERVER_SOFTWARE".to_string(),
+                "tauri-plugin-php".to_string(),
+            ),
+            (
+                "SERVER_PROTOCOL".to_string(),
+                format!("{:?}", parts.version),
+            ),
+            ("SERVER_NAME".to_string(), server_name),
+            ("SERVER_ADDR".to_string(), self.local_addr.ip().to_string()),
+            (
+                "SERVER_PORT".to_string(),
+                self.local_addr.port().to_string(),
+            ),
+            ("REMOTE_ADDR".to_string(), client_addr.ip().to_string()),
+            ("REMOTE_PORT".to_string(), client_addr.port().to_string()),
+            ("REQUEST_METHOD".to_string(), parts.method.to_string()),
+            ("REQUEST_URI".to_string(), request_uri),
+            ("QUERY_STRING".to_string(), query),
+            (
+                "DOCUMENT_ROOT".to_string(),
+                self.document_root.to_string_lossy().to_string(),
+            ),
+            (
+                "SCRIPT_FILENAME".to_string(),
+                script_filename.to_string_lossy().to_string(),
+            ),
+            ("SCRIPT_NAME".to_string(), script_name.to_string()),
+            ("DOCUMENT_URI".to_string(), script_name.to_string()),
+            (
+                "PHP_SELF".to_string(),
+                format!("{}{}", script_name, path_info.unwrap_or("")),
+            ),
+            // php-cgi refuses to run scripts without this when cgi.force_redirect is on
+            ("REDIRECT_STATUS".to_string(), "200".to_string()),
+            ("CONTENT_LENGTH".to_string(), content_length.to_string()),
+        ];
+
+        if let Some(path_info) = path_info {
+            params.push(("PATH_INFO".to_string(), path_info.to_string()));
+        }
+        if let Some(content_type) = parts
+            .headers
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+        {
+            params.push(("CONTENT_TYPE".to_string(), content_type.to_string()));
+        }
+
+        // Only trust the forwarded scheme from the plugin's own HTTPS proxy on loopback
+        let forwarded_https = parts
+            .headers
+            .get("x-forwarded-proto")
+            .is_some_and(|v| v.as_bytes().eq_ignore_ascii_case(b"https"));
+        if forwarded_https && client_addr.ip().is_loopback() {
+            params.push(("HTTPS".to_string(), "on".to_string()));
+        }
+
+        for (name, value) in parts.headers.iter() {
+            // Proxy is skipped to avoid the "httpoxy" HTTP_PROXY injection
+            if name == CONTENT_TYPE || name == CONTENT_LENGTH || name.as_str() == "proxy" {
+                continue;
+            }
+            if let Ok(value) = value.to_str() {
+                let param = format!(
+                    "HTTP_{}",
+                    name.as_str().to_ascii_uppercase().replace('-', "_")
+                );
+                params.push((param, value.to_string()));
+            }
+        }
+
+        params
+    }
+}
+
+fn cgi_to_http(stdout: &[u8]) -> Response<ProxyBody> {
+    let cgi_response = fastcgi::parse_cgi_response(stdout);
+
+    let body = Full::new(Bytes::from(cgi_response.body))
+        .map_err(|never| match never {})
+        .boxed();
+    let mut response = Response::new(body);
+    *response.status_mut() =
+        StatusCode::from_u16(cgi_response.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+
+    for (name, value) in cgi_response.headers {
+        if let (Ok(name), Ok(value)) = (
+            HeaderName::from_bytes(name.as_bytes()),
+            HeaderValue::from_str(&value),
+        ) {
+            response.headers_mut().append(name, value);
+        }
+    }
+    response
+}
+
+async fn serve_static(path: &Path) -> Response<ProxyBody> {
+    match tokio::fs::read(path).await {
+        Ok(contents) => {
+            let body = Full::new(Bytes::from(contents))
+                .map_err(|never| match never {})
+                .boxed();
+            let mut response = Response::new(body);
+            response.headers_mut().insert(
+                CONTENT_TYPE,
+                HeaderValue::from_static(content_type_for(path)),
+            );
+            response
+        }
+        Err(_) => error_response(StatusCode::NOT_FOUND, "Not Found"),
+    }
+}
+
+fn content_type_for(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_ascii_lowercase())
+        .unwrap_or_default();
+
+    match extension.as_str() {
+        "html" | "htm" => "text/html; charset=utf-8",
+        "css" => "text/css; charset=utf-8",
+        "js" | "mjs" => "text/javascript; charset=utf-8",
+        "json" | "map" => "application/json",
+        "txt" => "text/plain; charset=utf-8",
+        "xml" => "application/xml",
+        "svg" => "image/svg+xml",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "ico" => "image/x-icon",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        "ttf" => "font/ttf",
+        "pdf" => "application/pdf",
+        "wasm" => "application/wasm",
+        _ => "application/octet-stream",
+    }
+}
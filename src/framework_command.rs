@@ -0,0 +1,402 @@
+use crate::composer::forward_lines;
+use crate::models::{
+    ConsoleArgument, ConsoleCommand, ConsoleOption, Framework, FrameworkCommandOutput,
+    FrameworkCommandResult, OutputStream,
+};
+use crate::{Error, Result};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Event carrying each line a command prints during `run_framework_command`.
+pub const FRAMEWORK_COMMAND_OUTPUT_EVENT: &str = "php://framework-command-output";
+
+/// Receives a command's output line by line as it is printed.
+pub type CommandOutputCallback = Box<dyn FnMut(&FrameworkCommandOutput) + Send>;
+
+/// How often a running command checks whether it was cancelled.
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A framework console command resolved for a project, ready to run without
+/// holding on to the project manager.
+#[derive(Debug, Clone)]
+pub struct FrameworkCommand {
+    pub php_executable: PathBuf,
+    pub project_path: PathBuf,
+    pub framework: Framework,
+    /// The console script relative to the project
+    pub script: String,
+    pub args: Vec<String>,
+    pub env: BTreeMap<String, String>,
+}
+
+impl FrameworkCommand {
+    /// Runs `php <script> <args>` in the project, streaming its output to
+    /// `on_output`. Setting `cancel` kills the process.
+    pub async fn run(
+        &self,
+        run_id: &str,
+        cancel: Arc<AtomicBool>,
+        mut on_output: Option<CommandOutputCallback>,
+    ) -> Result<FrameworkCommandResult> {
+        let started = Instant::now();
+        log::info!(
+            "Running php {} {} in {}",
+            self.script,
+            self.args.join(" "),
+            self.project_path.display()
+        );
+        let mut command = tokio::process::Command::new(&self.php_executable);
+        command
+            .arg(&self.script)
+            .args(&self.args)
+            .current_dir(&self.project_path)
+            .envs(&self.env)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        // Its own process group, so cancelling reaches the workers it spawns
+        #[cfg(unix)]
+        command.process_group(0);
+        let mut child = command
+            .spawn()
+            .map_err(|e| Error::Process(format!("Failed to start {}: {}", self.script, e)))?;
+
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        if let Some(stdout) = child.stdout.take() {
+            forward_lines(stdout, OutputStream::Stdout, sender.clone());
+        }
+        if let Some(stderr) = child.stderr.take() {
+            forward_lines(stderr, OutputStream::Stderr, sender.clone());
+        }
+        drop(sender);
+
+        let mut output = String::new();
+        let mut poll = tokio::time::interval(CANCEL_POLL_INTERVAL);
+        loop {
+            tokio::select! {
+                line = receiver.recv() => {
+                    let Some((stream, line)) = line else { break };
+                    output.push_str(&line);
+                    output.push('\n');
+                    if let Some(callback) = on_output.as_mut() {
+                        callback(&FrameworkCommandOutput {
+                            run_id: run_id.to_string(),
+                            stream,
+                            line,
+                        });
+                    }
+                }
+                _ = poll.tick() => {
+                    if cancel.load(Ordering::SeqCst) {
+                        // Stop reading rather than waiting for the pipes to
+                        // close, in case a child escaped the process tree
+                        kill_process_tree(&mut child).await;
+                        return Err(self.cancelled());
+                    }
+                }
+            }
+        }
+
+        // A command may close its pipes and keep running, e.g. once it daemonizes
+        let status = loop {
+            tokio::select! {
+                status = child.wait() => break status?,
+                _ = poll.tick() => {
+                    if cancel.load(Ordering::SeqCst) {
+                        kill_process_tree(&mut child).await;
+                        return Err(self.cancelled());
+                    }
+                }
+            }
+        };
+        Ok(FrameworkCommandResult {
+            run_id: run_id.to_string(),
+            framework: self.framework.clone(),
+            script: self.script.clone(),
+            args: self.args.clone(),
+            exit_code: status.code(),
+            success: status.success(),
+            duration_ms: started.elapsed().as_millis() as u64,
+            output,
+        })
+    }
+
+    fn cancelled(&self) -> Error {
+        Error::Cancelled(format!(
+            "{} {} was cancelled",
+            self.script,
+            self.args.join(" ")
+        ))
+    }
+}
+
+/// Kills a command along with the processes it started: its process group
+/// on Unix, its process tree on Windows.
+async fn kill_process_tree(child: &mut tokio::process::Child) {
+    if let Some(pid) = child.id() {
+        #[cfg(unix)]
+        let killed = tokio::process::Command::new("kill")
+            .args(["-KILL", "--"])
+            .arg(format!("-{}", pid))
+            .output()
+            .await;
+        #[cfg(windows)]
+        let killed = tokio::process::Command::new("taskkill")
+            .args(["/T", "/F", "/PID"])
+            .arg(pid.to_string())
+            .output()
+            .await;
+        #[cfg(any(unix, windows))]
+        if let Err(e) = killed {
+            log::warn!("Failed to kill the processes of {}: {}", pid, e);
+        }
+    }
+    let _ = child.kill().await;
+}
+
+/// Cancellation flags of the framework commands in flight, keyed by run id.
+#[derive(Default)]
+pub struct CommandRegistry {
+    runs: Mutex<HashMap<String, Arc<AtomicBool>>>,
+}
+
+impl CommandRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(&self, run_id: &str) -> Result<Arc<AtomicBool>> {
+        let mut runs = self.runs.lock().unwrap_or_else(|e| e.into_inner());
+        if runs.contains_key(run_id) {
+            return Err(Error::Process(format!(
+                "Command {} is already running",
+                run_id
+            )));
+        }
+        let cancel = Arc::new(AtomicBool::new(false));
+        runs.insert(run_id.to_string(), cancel.clone());
+        Ok(cancel)
+    }
+
+    pub fn unregister(&self, run_id: &str) {
+        let mut runs = self.runs.lock().unwrap_or_else(|e| e.into_inner());
+        runs.remove(run_id);
+    }
+
+    /// Requests cancellation, returning false when no such command is running.
+    pub fn cancel(&self, run_id: &str) -> bool {
+        let runs = self.runs.lock().unwrap_or_else(|e| e.into_inner());
+        match runs.get(run_id) {
+            Some(cancel) => {
+                cancel.store(true, Ordering::SeqCst);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Runs the console `command` was resolved for with the framework's list
+/// command in place of its arguments, and parses what it prints.
+pub async fn list_commands(mut command: FrameworkCommand) -> Result<Vec<ConsoleCommand>> {
+    // Symfony's console, which Laravel builds on, describes itself as JSON
+    let (args, json): (&[&str], bool) = match command.framework {
+        Framework::Laravel | Framework::Lumen | Framework::Symfony => {
+            (&["list", "--format=json"], true)
+        }
+        Framework::CakePHP => (&["--help"], false),
+        Framework::Yii => (&["help"], false),
+        _ => (&["list"], false),
+    };
+    command.args = args.iter().map(|arg| arg.to_string()).collect();
+
+    let run_id = uuid::Uuid::new_v4().to_string();
+    let result = command.run(&run_id, Arc::default(), None).await?;
+    if !result.success {
+        return Err(Error::Process(format!(
+            "{} {} exited with {:?}: {}",
+            command.script,
+            args.join(" "),
+            result.exit_code,
+            result.output.lines().last().unwrap_or_default()
+        )));
+    }
+
+    if json {
+        parse_json_commands(&result.output)
+    } else {
+        Ok(parse_text_commands(&result.output))
+    }
+}
+
+/// Parses `list --format=json` from a Symfony console. Hidden commands are
+/// left out; PHP notices printed before the document are ignored.
+pub fn parse_json_commands(output: &str) -> Result<Vec<ConsoleCommand>> {
+    let start = std::iter::once(0)
+        .chain(output.match_indices('\n').map(|(index, _)| index + 1))
+        .find(|&index| output[index..].starts_with('{'))
+        .ok_or_else(|| Error::Json("The console printed no JSON".to_string()))?;
+    let document: Value = serde_json::Deserializer::from_str(&output[start..])
+        .into_iter()
+        .next()
+        .unwrap_or(Ok(Value::Null))?;
+
+    let text = |value: &Value, key: &str| {
+        value
+            .get(key)
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string()
+    };
+    let flag = |value: &Value, key: &str| value.get(key).and_then(Value::as_bool).unwrap_or(false);
+
+    let commands = document
+        .get("commands")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    Ok(commands
+        .iter()
+        .filter(|command| !flag(command, "hidden"))
+        .map(|command| {
+            let definition = command.get("definition").unwrap_or(&Value::Null);
+            ConsoleCommand {
+                name: text(command, "name"),
+                description: text(command, "description"),
+                arguments: definition_entries(definition, "arguments")
+                    .map(|argument| ConsoleArgument {
+                        name: text(argument, "name"),
+                        description: text(argument, "description"),
+                        required: flag(argument, "is_required"),
+                        is_array: flag(argument, "is_array"),
+                    })
+                    .collect(),
+                options: definition_entries(definition, "options")
+                    .map(|option| ConsoleOption {
+                        name: text(option, "name"),
+                        shortcut: Some(text(option, "shortcut")).filter(|s| !s.is_empty()),
+                        description: text(option, "description"),
+                        accepts_value: flag(option, "accept_value"),
+                    })
+                    .collect(),
+            }
+        })
+        .filter(|command| !command.name.is_empty())
+        .collect())
+}
+
+/// PHP encodes an empty definition as `[]` rather than `{}`.
+fn definition_entries<'a>(
+    definition: &'a Value,
+    key: &str,
+) -> Box<dyn Iterator<Item = &'a Value> + 'a> {
+    match definition.get(key) {
+        Some(Value::Object(entries)) => Box::new(entries.values()),
+        Some(Value::Array(entries)) => Box::new(entries.iter()),
+        _ => Box::new(std::iter::empty()),
+    }
+}
+
+/// Parses the command list a console prints for people: ThinkPHP's and
+/// CodeIgniter's `list`, Yii's `help` and CakePHP's `--help`. Only names and
+/// descriptions are known.
+pub fn parse_text_commands(output: &str) -> Vec<ConsoleCommand> {
+    let output = strip_ansi(output);
+    let lines: Vec<&str> = output.lines().collect();
+    // Usage, options and paths come before the list where there is a heading
+    let start = lines
+        .iter()
+        .position(|line| {
+            let line = line.to_ascii_lowercase();
+            line.contains("commands") && line.contains("available")
+        })
+        .map_or(0, |index| index + 1);
+
+    let mut commands: Vec<ConsoleCommand> = Vec::new();
+    for command in lines[start..]
+        .iter()
+        .filter_map(|line| parse_command_line(line))
+    {
+        if !commands.iter().any(|known| known.name == command.name) {
+            commands.push(command);
+        }
+    }
+    commands
+}
+
+/// Reads `  name   description` or ` - name   description`; group headings
+/// are not indented and wrapped descriptions are indented further.
+fn parse_command_line(line: &str) -> Option<ConsoleCommand> {
+    let trimmed = line.trim_start();
+    let indent = line.len() - trimmed.len();
+    let (entry, bullet) = match trimmed.strip_prefix("- ") {
+        Some(entry) => (entry.trim_start(), true),
+        None if (2..=8).contains(&indent) => (trimmed, false),
+        None => return None,
+    };
+
+    let (name, description) = match entry.split_once("  ") {
+        Some((name, description)) => (name, description.trim()),
+        None => (entry, ""),
+    };
+    let name = name.trim().trim_end_matches("(default)").trim();
+    // CakePHP's commands are words separated by spaces, e.g. `cache clear`
+    let valid = name.starts_with(|c: char| c.is_ascii_alphanumeric())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_-:/.".contains(c) || (bullet && c == ' '));
+    valid.then(|| ConsoleCommand {
+        name: name.to_string(),
+        description: description.to_string(),
+        ..Default::default()
+    })
+}
+
+fn strip_ansi(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\u{1b}' {
+            stripped.push(c);
+        } else if chars.next_if_eq(&'[').is_some() {
+            // Skip the parameters up to the final letter
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        }
+    }
+    stripped
+}
+
+/// Console command lists by project, valid while `composer.lock` is unchanged.
+#[derive(Default)]
+pub struct CommandCache {
+    entries: Mutex<HashMap<PathBuf, (String, Vec<ConsoleCommand>)>>,
+}
+
+impl CommandCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, project_path: &Path, lock_hash: &str) -> Option<Vec<ConsoleCommand>> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries
+            .get(project_path)
+            .filter(|(hash, _)| hash == lock_hash)
+            .map(|(_, commands)| commands.clone())
+    }
+
+    pub fn insert(&self, project_path: &Path, lock_hash: String, commands: Vec<ConsoleCommand>) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.insert(project_path.to_path_buf(), (lock_hash, commands));
+    }
+}
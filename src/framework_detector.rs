@@ -1,8 +1,14 @@
-use crate::models::Framework;
-use crate::{Error, Result};
+use crate::composer_manifest::{ComposerJson, ComposerLock};
+use crate::models::{
+    DetectionEvidence, EvidenceKind, Framework, FrameworkCandidate, FrameworkVersion,
+    LaravelSetupOptions, VersionSource,
+};
+use crate::setup_pipeline::default_pipeline;
+use crate::Result;
 use std::fs;
 use std::path::Path;
 
+#[derive(Clone, Copy)]
 pub struct FrameworkDetector;
 
 impl Default for FrameworkDetector {
@@ -17,54 +23,12 @@ impl FrameworkDetector {
     }
 
     pub fn detect_framework(&self, project_path: &Path) -> Result<Framework> {
-        // Check for Laravel
-        if self.is_laravel_project(project_path) {
-            return Ok(Framework::Laravel);
-        }
-
-        // Check for Symfony
-        if self.is_symfony_project(project_path) {
-            return Ok(Framework::Symfony);
-        }
-
-        // Check for CodeIgniter
-        if self.is_codeigniter_project(project_path) {
-            return Ok(Framework::CodeIgniter);
-        }
-
-        // Check for CakePHP
-        if self.is_cakephp_project(project_path) {
-            return Ok(Framework::CakePHP);
-        }
-
-        // Check for Zend/Laminas
-        if self.is_zend_project(project_path) {
-            return Ok(Framework::Zend);
-        }
-
-        // Check for Yii
-        if self.is_yii_project(project_path) {
-            return Ok(Framework::Yii);
-        }
-
-        // Check for ThinkPHP
-        if self.is_thinkphp_project(project_path) {
-            return Ok(Framework::ThinkPHP);
-        }
-
-        // Check for Phalcon
-        if self.is_phalcon_project(project_path) {
-            return Ok(Framework::Phalcon);
-        }
-
-        // Check for Slim
-        if self.is_slim_project(project_path) {
-            return Ok(Framework::Slim);
-        }
-
-        // Check for Lumen
-        if self.is_lumen_project(project_path) {
-            return Ok(Framework::Lumen);
+        let candidates = self.rank_frameworks(project_path)?;
+        if let Some(best) = candidates
+            .into_iter()
+            .find(|candidate| candidate.score >= MIN_SCORE)
+        {
+            return Ok(best.framework);
         }
 
         // Check for plain PHP
@@ -75,267 +39,31 @@ impl FrameworkDetector {
         Ok(Framework::Unknown)
     }
 
-    fn is_laravel_project(&self, project_path: &Path) -> bool {
-        // Check for Laravel-specific files and directories
-        let laravel_indicators = [
-            "artisan",
-            "app/Http/Kernel.php",
-            "bootstrap/app.php",
-            "config/app.php",
-        ];
-
-        for indicator in &laravel_indicators {
-            if project_path.join(indicator).exists() {
-                return true;
-            }
-        }
-
-        // Check composer.json for Laravel dependency
-        if let Ok(composer_content) = self.read_composer_json(project_path) {
-            if composer_content.contains("laravel/framework") {
-                return true;
-            }
-        }
-
-        false
-    }
-
-    fn is_symfony_project(&self, project_path: &Path) -> bool {
-        // Check for Symfony-specific files
-        let symfony_indicators = [
-            "bin/console",
-            "config/bundles.php",
-            "symfony.lock",
-            "src/Kernel.php",
-        ];
-
-        for indicator in &symfony_indicators {
-            if project_path.join(indicator).exists() {
-                return true;
-            }
-        }
-
-        // Check composer.json for Symfony dependency
-        if let Ok(composer_content) = self.read_composer_json(project_path) {
-            if composer_content.contains("symfony/framework-bundle")
-                || composer_content.contains("symfony/symfony")
-            {
-                return true;
-            }
-        }
-
-        false
-    }
-
-    fn is_codeigniter_project(&self, project_path: &Path) -> bool {
-        // Check for CodeIgniter-specific files (must have system directory)
-        let ci_indicators = ["system/CodeIgniter.php", "application/config/config.php"];
-
-        for indicator in &ci_indicators {
-            if project_path.join(indicator).exists() {
-                return true;
-            }
-        }
-
-        // Check for CodeIgniter 4
-        if project_path.join("app").exists() && project_path.join("system").exists() {
-            if let Ok(content) = fs::read_to_string(project_path.join("index.php")) {
-                if content.contains("CodeIgniter") {
-                    return true;
-                }
-            }
-        }
-
-        false
-    }
-
-    fn is_cakephp_project(&self, project_path: &Path) -> bool {
-        // Check for CakePHP-specific files
-        let cake_indicators = [
-            "bin/cake",
-            "config/app.php",
-            "src/Application.php",
-            "webroot/index.php",
-        ];
-
-        for indicator in &cake_indicators {
-            if project_path.join(indicator).exists() {
-                return true;
-            }
-        }
-
-        // Check composer.json for CakePHP dependency
-        if let Ok(composer_content) = self.read_composer_json(project_path) {
-            if composer_content.contains("cakephp/cakephp") {
-                return true;
-            }
-        }
-
-        false
-    }
-
-    fn is_zend_project(&self, project_path: &Path) -> bool {
-        // Check for Zend/Laminas-specific files
-        let zend_indicators = [
-            "config/application.config.php",
-            "config/modules.config.php",
-            "module/Application",
-        ];
-
-        for indicator in &zend_indicators {
-            if project_path.join(indicator).exists() {
-                return true;
-            }
-        }
-
-        // Check composer.json for Zend/Laminas dependency
-        if let Ok(composer_content) = self.read_composer_json(project_path) {
-            if composer_content.contains("zendframework/") || composer_content.contains("laminas/")
-            {
-                return true;
-            }
-        }
-
-        false
-    }
-
-    fn is_yii_project(&self, project_path: &Path) -> bool {
-        // Check for Yii-specific files
-        let yii_indicators = [
-            "yii",
-            "config/web.php",
-            "web/index.php",
-            "commands/MigrateController.php",
-        ];
-
-        for indicator in &yii_indicators {
-            if project_path.join(indicator).exists() {
-                return true;
-            }
-        }
-
-        // Check composer.json for Yii dependency
-        if let Ok(composer_content) = self.read_composer_json(project_path) {
-            if composer_content.contains("yiisoft/yii2") {
-                return true;
-            }
-        }
-
-        false
-    }
-
-    fn is_thinkphp_project(&self, project_path: &Path) -> bool {
-        // Check for ThinkPHP-specific files
-        let thinkphp_indicators = ["think", "public/index.php", "app"];
-
-        for indicator in &thinkphp_indicators {
-            if project_path.join(indicator).exists() {
-                // Additional check for ThinkPHP specific content
-                if *indicator == "think" {
-                    if let Ok(content) = fs::read_to_string(project_path.join(indicator)) {
-                        if content.contains("think\\Console") || content.contains("think/Console") {
-                            return true;
-                        }
-                    }
-                }
-                if *indicator == "public/index.php" {
-                    if let Ok(content) = fs::read_to_string(project_path.join(indicator)) {
-                        if content.contains("think\\App") || content.contains("think/App") {
-                            return true;
-                        }
-                    }
-                }
-            }
-        }
-
-        // Check composer.json for ThinkPHP dependency
-        if let Ok(composer_content) = self.read_composer_json(project_path) {
-            if composer_content.contains("topthink/framework")
-                || composer_content.contains("topthink/think")
-            {
-                return true;
-            }
-        }
-
-        // Check for ThinkPHP 5.x structure
-        if project_path.join("application").exists() && project_path.join("public").exists() {
-            return true;
-        }
-
-        // Check for ThinkPHP 3.x structure
-        if project_path.join("ThinkPHP").exists() && project_path.join("Application").exists() {
-            return true;
-        }
-
-        false
-    }
-
-    fn is_phalcon_project(&self, project_path: &Path) -> bool {
-        // Check for Phalcon-specific files
-        let phalcon_indicators = ["app/config/config.php", "public/index.php"];
-
-        for indicator in &phalcon_indicators {
-            if project_path.join(indicator).exists() {
-                if let Ok(content) = fs::read_to_string(project_path.join(indicator)) {
-                    if content.contains("Phalcon") {
-                        return true;
-                    }
-                }
-            }
-        }
-
-        // Check composer.json for Phalcon dependency
-        if let Ok(composer_content) = self.read_composer_json(project_path) {
-            if composer_content.contains("phalcon/") {
-                return true;
-            }
-        }
-
-        false
-    }
-
-    fn is_slim_project(&self, project_path: &Path) -> bool {
-        // Check composer.json for Slim dependency
-        if let Ok(composer_content) = self.read_composer_json(project_path) {
-            if composer_content.contains("slim/slim") {
-                return true;
-            }
-        }
-
-        // Check for typical Slim structure
-        if project_path.join("public").exists() && project_path.join("src").exists() {
-            if let Ok(content) = fs::read_to_string(project_path.join("public/index.php")) {
-                if content.contains("Slim\\App") || content.contains("Slim/App") {
-                    return true;
+    /// Scores every framework against the project, best match first. Only
+    /// frameworks with some evidence are listed; ties keep the order in which
+    /// frameworks used to be checked, Laravel first.
+    pub fn rank_frameworks(&self, project_path: &Path) -> Result<Vec<FrameworkCandidate>> {
+        let packages = ProjectPackages::load(project_path);
+
+        let mut candidates: Vec<FrameworkCandidate> = FRAMEWORKS
+            .iter()
+            .map(|framework| {
+                let evidence: Vec<DetectionEvidence> = signals(framework)
+                    .iter()
+                    .flat_map(|signal| signal.evidence(project_path, &packages))
+                    .collect();
+                let score = evidence.iter().map(|found| found.weight).sum::<u32>();
+                FrameworkCandidate {
+                    framework: framework.clone(),
+                    score,
+                    confidence: score.min(CERTAIN_SCORE) as f32 / CERTAIN_SCORE as f32,
+                    evidence,
                 }
-            }
-        }
-
-        false
-    }
-
-    fn is_lumen_project(&self, project_path: &Path) -> bool {
-        // Check for Lumen-specific files
-        let lumen_indicators = ["bootstrap/app.php", "public/index.php"];
-
-        for indicator in &lumen_indicators {
-            if project_path.join(indicator).exists() {
-                if let Ok(content) = fs::read_to_string(project_path.join(indicator)) {
-                    if content.contains("Laravel\\Lumen") {
-                        return true;
-                    }
-                }
-            }
-        }
-
-        // Check composer.json for Lumen dependency
-        if let Ok(composer_content) = self.read_composer_json(project_path) {
-            if composer_content.contains("laravel/lumen") {
-                return true;
-            }
-        }
-
-        false
+            })
+            .filter(|candidate| candidate.score > 0)
+            .collect();
+        candidates.sort_by_key(|candidate| std::cmp::Reverse(candidate.score));
+        Ok(candidates)
     }
 
     fn is_php_project(&self, project_path: &Path) -> bool {
@@ -355,102 +83,212 @@ impl FrameworkDetector {
         has_php_file
     }
 
-    fn read_composer_json(&self, project_path: &Path) -> Result<String> {
-        let composer_path = project_path.join("composer.json");
-        fs::read_to_string(composer_path).map_err(|e| Error::Io(e.to_string()))
+    /// The installed version of `framework`, read from `composer.lock`, then
+    /// from the framework's version constant and last from directory layouts
+    /// only one major version has.
+    pub fn detect_version(
+        &self,
+        project_path: &Path,
+        framework: &Framework,
+    ) -> Option<FrameworkVersion> {
+        locked_version(project_path, framework)
+            .or_else(|| constant_version(project_path, framework))
+            .or_else(|| layout_version(project_path, framework))
     }
 
     pub fn get_framework_info(&self, framework: &Framework) -> FrameworkInfo {
-        match framework {
+        self.framework_info(framework, None)
+    }
+
+    /// Like `get_framework_info`, with the entry point, console script and
+    /// setup steps of a particular version when it is known.
+    pub fn framework_info(
+        &self,
+        framework: &Framework,
+        version: Option<&FrameworkVersion>,
+    ) -> FrameworkInfo {
+        let console_script = console_script(framework, version);
+        let setup_commands = default_pipeline(framework, version, &LaravelSetupOptions::default())
+            .describe(console_script);
+        let mut info = match framework {
             Framework::Laravel => FrameworkInfo {
                 name: "Laravel",
                 default_entry_point: "public/index.php",
                 requires_composer: true,
-                setup_commands: vec![
-                    "composer install",
-                    "cp .env.example .env",
-                    "php artisan key:generate",
+                setup_commands,
+                required_extensions: vec![
+                    "ctype",
+                    "curl",
+                    "dom",
+                    "fileinfo",
+                    "filter",
+                    "hash",
+                    "mbstring",
+                    "openssl",
+                    "pcre",
+                    "pdo",
+                    "session",
+                    "tokenizer",
+                    "xml",
                 ],
                 default_port: 8000,
+                console_script,
             },
             Framework::Symfony => FrameworkInfo {
                 name: "Symfony",
                 default_entry_point: "public/index.php",
                 requires_composer: true,
-                setup_commands: vec!["composer install"],
+                setup_commands,
+                required_extensions: vec![
+                    "ctype",
+                    "iconv",
+                    "pcre",
+                    "session",
+                    "simplexml",
+                    "tokenizer",
+                ],
                 default_port: 8000,
+                console_script,
             },
             Framework::CodeIgniter => FrameworkInfo {
                 name: "CodeIgniter",
                 default_entry_point: "index.php",
                 requires_composer: false,
-                setup_commands: vec![],
+                setup_commands,
+                required_extensions: vec!["intl", "mbstring", "json"],
                 default_port: 8080,
+                console_script,
             },
             Framework::CakePHP => FrameworkInfo {
                 name: "CakePHP",
                 default_entry_point: "webroot/index.php",
                 requires_composer: true,
-                setup_commands: vec!["composer install"],
+                setup_commands,
+                required_extensions: vec!["intl", "mbstring", "simplexml", "pdo"],
                 default_port: 8765,
+                console_script,
             },
             Framework::Zend => FrameworkInfo {
                 name: "Zend/Laminas",
                 default_entry_point: "public/index.php",
                 requires_composer: true,
-                setup_commands: vec!["composer install"],
+                setup_commands,
+                required_extensions: vec![],
                 default_port: 8080,
+                console_script,
             },
             Framework::Yii => FrameworkInfo {
                 name: "Yii",
                 default_entry_point: "web/index.php",
                 requires_composer: true,
-                setup_commands: vec!["composer install"],
+                setup_commands,
+                required_extensions: vec!["ctype", "mbstring"],
                 default_port: 8080,
+                console_script,
             },
             Framework::Phalcon => FrameworkInfo {
                 name: "Phalcon",
                 default_entry_point: "public/index.php",
                 requires_composer: false,
-                setup_commands: vec![],
+                setup_commands,
+                required_extensions: vec!["phalcon"],
                 default_port: 8080,
+                console_script,
             },
             Framework::Slim => FrameworkInfo {
                 name: "Slim",
                 default_entry_point: "public/index.php",
                 requires_composer: true,
-                setup_commands: vec!["composer install"],
+                setup_commands,
+                required_extensions: vec![],
                 default_port: 8080,
+                console_script,
             },
             Framework::Lumen => FrameworkInfo {
                 name: "Lumen",
                 default_entry_point: "public/index.php",
                 requires_composer: true,
-                setup_commands: vec!["composer install"],
+                setup_commands,
+                required_extensions: vec!["mbstring", "openssl", "pdo"],
                 default_port: 8000,
+                console_script,
             },
             Framework::ThinkPHP => FrameworkInfo {
                 name: "ThinkPHP",
                 default_entry_point: "public/index.php",
                 requires_composer: true,
-                setup_commands: vec!["composer install"],
+                setup_commands,
+                required_extensions: vec!["json", "mbstring"],
                 default_port: 8000,
+                console_script,
             },
             Framework::Plain => FrameworkInfo {
                 name: "Plain PHP",
                 default_entry_point: "index.php",
                 requires_composer: false,
-                setup_commands: vec![],
+                setup_commands,
+                required_extensions: vec![],
                 default_port: 8000,
+                console_script,
             },
             Framework::Unknown => FrameworkInfo {
                 name: "Unknown",
                 default_entry_point: "index.php",
                 requires_composer: false,
-                setup_commands: vec![],
+                setup_commands,
+                required_extensions: vec![],
                 default_port: 8000,
+                console_script,
             },
+        };
+
+        // Releases that predate the framework's current layout
+        let before = |major| version.is_some_and(|version| version.is_before(major, 0));
+        match framework {
+            Framework::CodeIgniter if version.is_some() && !before(4) => {
+                info.default_entry_point = "public/index.php";
+                info.requires_composer = true;
+            }
+            Framework::Symfony if before(4) => info.default_entry_point = "web/app.php",
+            Framework::CakePHP if before(3) => {
+                info.default_entry_point = "app/webroot/index.php";
+                info.requires_composer = false;
+            }
+            Framework::Yii if before(2) => {
+                info.default_entry_point = "index.php";
+                info.requires_composer = false;
+            }
+            Framework::ThinkPHP if before(5) => {
+                info.default_entry_point = "index.php";
+                info.requires_composer = false;
+            }
+            _ => {}
         }
+        info
+    }
+}
+
+/// The framework's command-line entry point, relative to the project root.
+/// Older releases of some frameworks kept it elsewhere or had none.
+pub fn console_script(
+    framework: &Framework,
+    version: Option<&FrameworkVersion>,
+) -> Option<&'static str> {
+    let before = |major| version.is_some_and(|version| version.is_before(major, 0));
+    match framework {
+        Framework::Laravel | Framework::Lumen => Some("artisan"),
+        Framework::Symfony if before(3) => Some("app/console"),
+        Framework::Symfony => Some("bin/console"),
+        Framework::CodeIgniter if before(4) => None,
+        Framework::CodeIgniter => Some("spark"),
+        Framework::CakePHP if before(3) => Some("app/Console/cake.php"),
+        Framework::CakePHP => Some("bin/cake.php"),
+        Framework::Yii if before(2) => Some("protected/yiic.php"),
+        Framework::Yii => Some("yii"),
+        Framework::ThinkPHP if before(5) => None,
+        Framework::ThinkPHP => Some("think"),
+        Framework::Zend | Framework::Phalcon | Framework::Slim => None,
+        Framework::Plain | Framework::Unknown => None,
     }
 }
 
@@ -459,6 +297,441 @@ pub struct FrameworkInfo {
     pub name: &'static str,
     pub default_entry_point: &'static str,
     pub requires_composer: bool,
-    pub setup_commands: Vec<&'static str>,
+    /// The default setup pipeline, summarized
+    pub setup_commands: Vec<String>,
+    /// Extensions the framework needs at runtime, without the `ext-` prefix
+    pub required_extensions: Vec<&'static str>,
     pub default_port: u16,
+    /// The framework's command-line entry point, relative to the project
+    pub console_script: Option<&'static str>,
+}
+
+/// Below this a framework is not detected, so that a shared layout such as a
+/// `config/app.php` alone does not decide.
+const MIN_SCORE: u32 = 10;
+
+/// A locked framework package alone makes the detection certain.
+const CERTAIN_SCORE: u32 = 100;
+
+const LOCKED_PACKAGE_WEIGHT: u32 = 100;
+const REQUIRED_PACKAGE_WEIGHT: u32 = 80;
+
+/// The frameworks that are scored, in the order ties are broken.
+const FRAMEWORKS: [Framework; 10] = [
+    Framework::Laravel,
+    Framework::Symfony,
+    Framework::CodeIgniter,
+    Framework::CakePHP,
+    Framework::Zend,
+    Framework::Yii,
+    Framework::ThinkPHP,
+    Framework::Phalcon,
+    Framework::Slim,
+    Framework::Lumen,
+];
+
+/// Something in a project that points to a framework.
+enum Signal {
+    /// A Composer package; a trailing `/` matches every package of the vendor
+    Package(&'static str),
+    /// A file or directory, with its weight; names match case-sensitively
+    File(&'static str, u32),
+    /// A file containing one of the needles, with its weight
+    Content(&'static str, &'static [&'static str], u32),
+}
+
+fn signals(framework: &Framework) -> &'static [Signal] {
+    use Signal::{Content, File, Package};
+
+    match framework {
+        Framework::Laravel => &[
+            Package("laravel/framework"),
+            File("artisan", 20),
+            File("app/Http/Kernel.php", 10),
+            Content(
+                "bootstrap/app.php",
+                &["Illuminate\\Foundation\\Application"],
+                30,
+            ),
+            File("config/app.php", 3),
+        ],
+        Framework::Lumen => &[
+            Package("laravel/lumen-framework"),
+            Content("bootstrap/app.php", &["Laravel\\Lumen"], 40),
+            Content("public/index.php", &["Laravel\\Lumen"], 20),
+        ],
+        Framework::Symfony => &[
+            Package("symfony/framework-bundle"),
+            Package("symfony/symfony"),
+            File("symfony.lock", 30),
+            File("config/bundles.php", 20),
+            File("bin/console", 20),
+            File("src/Kernel.php", 10),
+        ],
+        Framework::CodeIgniter => &[
+            Package("codeigniter4/framework"),
+            Package("codeigniter/framework"),
+            File("system/CodeIgniter.php", 40),
+            File("application/config/config.php", 20),
+            File("spark", 20),
+            File("app/Config/App.php", 10),
+            Content("index.php", &["CodeIgniter"], 20),
+        ],
+        Framework::CakePHP => &[
+            Package("cakephp/cakephp"),
+            File("bin/cake", 20),
+            File("bin/cake.php", 20),
+            File("webroot/index.php", 15),
+            Content("src/Application.php", &["Cake\\"], 20),
+            File("config/app.php", 3),
+        ],
+        Framework::Zend => &[
+            Package("laminas/laminas-mvc"),
+            Package("zendframework/zend-mvc"),
+            Package("mezzio/mezzio"),
+            Package("zendframework/zend-expressive"),
+            File("config/application.config.php", 30),
+            File("config/modules.config.php", 20),
+            File("module/Application", 20),
+        ],
+        Framework::Yii => &[
+            Package("yiisoft/yii2"),
+            File("yii", 20),
+            File("config/web.php", 15),
+            Content("web/index.php", &["yii\\web\\Application"], 30),
+        ],
+        Framework::ThinkPHP => &[
+            Package("topthink/framework"),
+            Package("topthink/think"),
+            Content("think", &["think\\Console", "think/Console"], 40),
+            Content("public/index.php", &["think\\App", "think/App"], 40),
+            // ThinkPHP 3.x ships the framework inside the project
+            File("ThinkPHP/ThinkPHP.php", 40),
+            // Common app folders only count alongside a signal above
+            File("Application", 4),
+            File("application", 4),
+        ],
+        Framework::Phalcon => &[
+            Package("phalcon/"),
+            Content("app/config/config.php", &["Phalcon"], 30),
+            Content("public/index.php", &["Phalcon"], 30),
+        ],
+        Framework::Slim => &[
+            Package("slim/slim"),
+            Content(
+                "public/index.php",
+                &["Slim\\App", "Slim/App", "Slim\\Factory\\AppFactory"],
+                40,
+            ),
+        ],
+        Framework::Plain | Framework::Unknown => &[],
+    }
+}
+
+impl Signal {
+    fn evidence(&self, project_path: &Path, packages: &ProjectPackages) -> Vec<DetectionEvidence> {
+        match self {
+            Signal::Package(package) => packages.matching(package),
+            Signal::File(path, weight) => {
+                if exists_exactly(project_path, path) {
+                    vec![DetectionEvidence::new(EvidenceKind::File, path, *weight)]
+                } else {
+                    Vec::new()
+                }
+            }
+            Signal::Content(path, needles, weight) => {
+                let found = fs::read_to_string(project_path.join(path))
+                    .is_ok_and(|content| needles.iter().any(|needle| content.contains(needle)));
+                if found {
+                    vec![DetectionEvidence::new(
+                        EvidenceKind::FileContent,
+                        path,
+                        *weight,
+                    )]
+                } else {
+                    Vec::new()
+                }
+            }
+        }
+    }
+}
+
+/// Whether `relative` exists under `root` with exactly this spelling, also on
+/// case-insensitive filesystems, where `Application` would match `application/`.
+fn exists_exactly(root: &Path, relative: &str) -> bool {
+    let mut current = root.to_path_buf();
+    for name in relative.split('/') {
+        let found = fs::read_dir(&current)
+            .map(|entries| entries.flatten().any(|entry| entry.file_name() == name))
+            .unwrap_or(false);
+        if !found {
+            return false;
+        }
+        current.push(name);
+    }
+    true
+}
+
+/// The package names a project requires and locks, lowercased.
+struct ProjectPackages {
+    required: Vec<String>,
+    locked: Vec<String>,
+}
+
+impl ProjectPackages {
+    /// Unreadable manifests count as having no packages.
+    fn load(project_path: &Path) -> Self {
+        let mut required = Vec::new();
+        if let Ok(Some(manifest)) = ComposerJson::load(project_path) {
+            required.extend(manifest.require.into_keys());
+            required.extend(manifest.require_dev.into_keys());
+        }
+        let mut locked = Vec::new();
+        if let Ok(Some(lock)) = ComposerLock::load(project_path) {
+            locked.extend(
+                lock.packages
+                    .into_iter()
+                    .chain(lock.packages_dev)
+                    .map(|package| package.name),
+            );
+        }
+        let lowercase = |names: Vec<String>| {
+            names
+                .into_iter()
+                .map(|name| name.to_ascii_lowercase())
+                .collect()
+        };
+        Self {
+            required: lowercase(required),
+            locked: lowercase(locked),
+        }
+    }
+
+    /// Evidence for `package` from `composer.lock` and `composer.json`; a
+    /// vendor prefix counts once per file.
+    fn matching(&self, package: &str) -> Vec<DetectionEvidence> {
+        let matches = |name: &&String| {
+            if package.ends_with('/') {
+                name.starts_with(package)
+            } else {
+                name.as_str() == package
+            }
+        };
+        let mut evidence = Vec::new();
+        if let Some(name) = self.locked.iter().find(matches) {
+            evidence.push(DetectionEvidence::new(
+                EvidenceKind::LockedPackage,
+                name,
+                LOCKED_PACKAGE_WEIGHT,
+            ));
+        }
+        if let Some(name) = self.required.iter().find(matches) {
+            evidence.push(DetectionEvidence::new(
+                EvidenceKind::RequiredPackage,
+                name,
+                REQUIRED_PACKAGE_WEIGHT,
+            ));
+        }
+        evidence
+    }
+}
+
+/// Packages whose locked version is the framework's, most specific first.
+fn version_packages(framework: &Framework) -> &'static [&'static str] {
+    match framework {
+        Framework::Laravel => &["laravel/framework"],
+        Framework::Lumen => &["laravel/lumen-framework"],
+        Framework::Symfony => &[
+            "symfony/symfony",
+            "symfony/framework-bundle",
+            "symfony/http-kernel",
+        ],
+        Framework::CodeIgniter => &["codeigniter4/framework", "codeigniter/framework"],
+        Framework::CakePHP => &["cakephp/cakephp"],
+        Framework::Zend => &[
+            "laminas/laminas-mvc",
+            "zendframework/zend-mvc",
+            "mezzio/mezzio",
+            "zendframework/zend-expressive",
+            "zendframework/zend-version",
+        ],
+        Framework::Yii => &["yiisoft/yii2", "yiisoft/yii"],
+        Framework::ThinkPHP => &["topthink/framework"],
+        Framework::Slim => &["slim/slim"],
+        Framework::Phalcon | Framework::Plain | Framework::Unknown => &[],
+    }
+}
+
+/// Files declaring the framework's version, each with the text the version
+/// follows.
+fn version_constants(framework: &Framework) -> &'static [(&'static str, &'static str)] {
+    match framework {
+        Framework::Laravel => &[(
+            "vendor/laravel/framework/src/Illuminate/Foundation/Application.php",
+            "const VERSION",
+        )],
+        Framework::Lumen => &[(
+            "vendor/laravel/lumen-framework/src/Application.php",
+            "Lumen (",
+        )],
+        Framework::Symfony => &[
+            ("vendor/symfony/http-kernel/Kernel.php", "const VERSION"),
+            (
+                "vendor/symfony/symfony/src/Symfony/Component/HttpKernel/Kernel.php",
+                "const VERSION",
+            ),
+        ],
+        Framework::CodeIgniter => &[
+            (
+                "vendor/codeigniter4/framework/system/CodeIgniter.php",
+                "CI_VERSION",
+            ),
+            ("system/CodeIgniter.php", "CI_VERSION"),
+            ("system/core/CodeIgniter.php", "CI_VERSION"),
+        ],
+        Framework::CakePHP => &[
+            ("vendor/cakephp/cakephp/VERSION.txt", ""),
+            ("lib/Cake/VERSION.txt", ""),
+        ],
+        Framework::Yii => &[
+            ("vendor/yiisoft/yii2/BaseYii.php", "function getVersion()"),
+            ("framework/YiiBase.php", "function getVersion()"),
+        ],
+        Framework::ThinkPHP => &[
+            (
+                "vendor/topthink/framework/src/think/App.php",
+                "const VERSION",
+            ),
+            ("thinkphp/library/think/App.php", "const VERSION"),
+            ("thinkphp/base.php", "THINK_VERSION"),
+            ("ThinkPHP/ThinkPHP.php", "THINK_VERSION"),
+        ],
+        Framework::Slim => &[("vendor/slim/slim/Slim/App.php", "const VERSION")],
+        Framework::Zend | Framework::Phalcon | Framework::Plain | Framework::Unknown => &[],
+    }
+}
+
+/// Layouts only one major version of a framework has.
+enum Fingerprint {
+    /// Every path exists, spelled exactly so
+    Files(u32, &'static [&'static str]),
+    /// A file contains the needle
+    Content(u32, &'static str, &'static str),
+}
+
+fn fingerprints(framework: &Framework) -> &'static [Fingerprint] {
+    use Fingerprint::{Content, Files};
+
+    match framework {
+        // Laravel 11 configures the application fluently in bootstrap/app.php
+        Framework::Laravel => &[Content(11, "bootstrap/app.php", "Application::configure(")],
+        Framework::Symfony => &[Files(2, &["app/console"]), Files(3, &["app/AppKernel.php"])],
+        Framework::CodeIgniter => &[
+            Files(3, &["system/core/CodeIgniter.php"]),
+            Files(3, &["application/config/config.php"]),
+            Files(4, &["spark"]),
+            Files(4, &["app/Config/App.php"]),
+        ],
+        Framework::CakePHP => &[Files(2, &["app/Config/core.php"]), Files(2, &["lib/Cake"])],
+        Framework::Yii => &[
+            Files(1, &["protected/config/main.php"]),
+            Files(2, &["yii", "config/web.php"]),
+        ],
+        Framework::ThinkPHP => &[
+            Files(3, &["ThinkPHP/ThinkPHP.php"]),
+            Files(5, &["thinkphp/base.php"]),
+            Files(5, &["application/config.php"]),
+            Files(5, &["application", "thinkphp"]),
+            Files(6, &["app", "think"]),
+        ],
+        _ => &[],
+    }
+}
+
+fn locked_version(project_path: &Path, framework: &Framework) -> Option<FrameworkVersion> {
+    let lock = ComposerLock::load(project_path).ok()??;
+    version_packages(framework).iter().find_map(|name| {
+        let package = lock
+            .packages
+            .iter()
+            .chain(&lock.packages_dev)
+            .find(|package| package.name.eq_ignore_ascii_case(name))?;
+        parse_version(&package.version, VersionSource::LockFile)
+    })
+}
+
+fn constant_version(project_path: &Path, framework: &Framework) -> Option<FrameworkVersion> {
+    let declared = version_constants(framework)
+        .iter()
+        .find_map(|(path, needle)| version_after(&project_path.join(path), needle));
+    // Some packages keep their version in a class such as `src/Version.php`
+    declared.or_else(|| {
+        version_packages(framework).iter().find_map(|package| {
+            let source = project_path.join("vendor").join(package).join("src");
+            fs::read_dir(source).ok()?.flatten().find_map(|entry| {
+                let file_name = entry.file_name();
+                let file_name = file_name.to_string_lossy();
+                if file_name.contains("Version") && file_name.ends_with(".php") {
+                    version_after(&entry.path(), "const VERSION")
+                } else {
+                    None
+                }
+            })
+        })
+    })
+}
+
+/// The first version in `path` after `needle`, ignoring `//` comment lines.
+fn version_after(path: &Path, needle: &str) -> Option<FrameworkVersion> {
+    let content = fs::read_to_string(path).ok()?;
+    let content: String = content
+        .lines()
+        .filter(|line| !line.trim_start().starts_with("//"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let (_, rest) = content.split_once(needle)?;
+    let start = rest.find(|c: char| c.is_ascii_digit())?;
+    // The version belongs to the statement the needle starts
+    if rest[..start].contains(';') {
+        return None;
+    }
+    parse_version(&rest[start..], VersionSource::VersionConstant)
+}
+
+fn layout_version(project_path: &Path, framework: &Framework) -> Option<FrameworkVersion> {
+    let major = fingerprints(framework)
+        .iter()
+        .find_map(|fingerprint| match fingerprint {
+            Fingerprint::Files(major, paths) => paths
+                .iter()
+                .all(|path| exists_exactly(project_path, path))
+                .then_some(*major),
+            Fingerprint::Content(major, path, needle) => {
+                fs::read_to_string(project_path.join(path))
+                    .is_ok_and(|content| content.contains(needle))
+                    .then_some(*major)
+            }
+        })?;
+    Some(FrameworkVersion {
+        major,
+        minor: None,
+        source: VersionSource::Layout,
+    })
+}
+
+/// Reads `major.minor` from a version such as `v11.2.0` or `5.4.x-dev`;
+/// branch versions such as `dev-master` have none.
+fn parse_version(version: &str, source: VersionSource) -> Option<FrameworkVersion> {
+    let version = version.trim_start_matches(['v', 'V']);
+    let mut parts = version.split('.');
+    let number = |part: Option<&str>| -> Option<u32> {
+        let digits: String = part?.chars().take_while(char::is_ascii_digit).collect();
+        digits.parse().ok()
+    };
+    let major = number(parts.next())?;
+    Some(FrameworkVersion {
+        major,
+        minor: number(parts.next()),
+        source,
+    })
 }
@@ -0,0 +1,327 @@
+use crate::models::{GatewayRoute, GatewayStatus, ServerStatus};
+use crate::php_server::PhpServerManager;
+use crate::proxy::{error_response, forward_request, upstream_client, ProxyBody, UpstreamClient};
+use crate::{Error, Result};
+use bytes::Bytes;
+use http_body_util::{BodyExt, Full};
+use hyper::body::Incoming;
+use hyper::header::{HeaderValue, CONTENT_TYPE, HOST, LOCATION};
+use hyper::service::service_fn;
+use hyper::{Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tokio::sync::{oneshot, Mutex};
+use tokio::task::JoinHandle;
+
+/// Port the gateway listens on when none is given.
+pub const DEFAULT_GATEWAY_PORT: u16 = 8080;
+
+/// Browsers resolve every `*.localhost` name to the loopback interface, so
+/// `project-a.localhost` reaches the gateway without any DNS setup.
+const PROJECT_HOST_SUFFIX: &str = ".localhost";
+
+/// The project a gateway request is routed to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GatewayTarget {
+    pub project: String,
+    /// Set when the project was taken from the first path segment
+    pub prefix: Option<String>,
+}
+
+/// Picks the project from `project-a.localhost` style hosts, falling back to
+/// the first path segment (`/project-a/...`) for any other host.
+pub fn resolve_gateway_target(host: Option<&str>, path: &str) -> Option<GatewayTarget> {
+    let hostname = host
+        .map(|host| host.split(':').next().unwrap_or(host))
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+
+    if let Some(subdomain) = hostname.strip_suffix(PROJECT_HOST_SUFFIX) {
+        // www.project-a.localhost routes like project-a.localhost
+        if let Some(project) = subdomain.rsplit('.').next().filter(|p| !p.is_empty()) {
+            return Some(GatewayTarget {
+                project: project.to_string(),
+                prefix: None,
+            });
+        }
+    }
+
+    let project = path.trim_start_matches('/').split('/').next()?;
+    if project.is_empty() {
+        return None;
+    }
+    Some(GatewayTarget {
+        project: project.to_string(),
+        prefix: Some(format!("/{}", project)),
+    })
+}
+
+/// The path forwarded for a request served under `prefix`, read from the
+/// path as `resolve_gateway_target` normalized it: `//project-a/x` gives `/x`.
+pub fn path_below_prefix(path: &str, prefix: &str) -> Option<String> {
+    let normalized = format!("/{}", path.trim_start_matches('/'));
+    let rest = normalized.strip_prefix(prefix)?;
+    (rest.is_empty() || rest.starts_with('/')).then(|| rest.to_string())
+}
+
+/// Builds the public URLs of every running server, keyed by project directory.
+pub fn gateway_routes(servers: &[(String, ServerStatus)], gateway_port: u16) -> Vec<GatewayRoute> {
+    let mut routes: Vec<GatewayRoute> = servers
+        .iter()
+        .filter_map(|(server_id, status)| {
+            let project = Path::new(status.project_path.as_deref()?)
+                .file_name()?
+                .to_str()?
+                .to_string();
+            Some(GatewayRoute {
+                host_url: format!(
+                    "http://{}{}:{}/",
+                    project.to_ascii_lowercase(),
+                    PROJECT_HOST_SUFFIX,
+                    gateway_port
+                ),
+                path_url: format!("http://localhost:{}/{}/", gateway_port, project),
+                project,
+                server_id: server_id.clone(),
+                upstream_port: status.port,
+            })
+        })
+        .collect();
+    routes.sort_by(|a, b| a.project.cmp(&b.project));
+    routes
+}
+
+/// A plain HTTP front door that serves every running project under one port.
+///
+/// Routes are not registered explicitly: each request is matched against the
+/// servers currently managed by `PhpServerManager`, so servers that start or
+/// stop are picked up immediately.
+pub struct Gateway {
+    local_addr: SocketAddr,
+    shutdown_tx: Option<oneshot::Sender<()>>,
+    task: JoinHandle<()>,
+}
+
+impl Gateway {
+    pub async fn start(
+        listen_addr: SocketAddr,
+        server_manager: Arc<Mutex<PhpServerManager>>,
+    ) -> Result<Self> {
+        let listener = TcpListener::bind(listen_addr).await.map_err(|e| {
+            Error::PhpServer(format!("Failed to bind gateway on {}: {}", listen_addr, e))
+        })?;
+        let local_addr = listener.local_addr()?;
+        let client = upstream_client();
+        let (shutdown_tx, mut shutdown_rx) = oneshot::channel::<()>();
+
+        let task = tokio::spawn(async move {
+            loop {
+                let (stream, client_addr) = tokio::select! {
+                    _ = &mut shutdown_rx => break,
+                    accepted = listener.accept() => match accepted {
+                        Ok(accepted) => accepted,
+                        Err(e) => {
+                            log::warn!("Gateway failed to accept connection: {}", e);
+                            continue;
+                        }
+                    },
+                };
+
+                let client = client.clone();
+                let server_manager = server_manager.clone();
+                tokio::spawn(async move {
+                    let service = service_fn(move |req| {
+                        let client = client.clone();
+                        let server_manager = server_manager.clone();
+                        async move {
+                            Ok::<_, Infallible>(
+                                handle_request(
+                                    &client,
+                                    &server_manager,
+                                    local_addr,
+                                    client_addr,
+                                    req,
+                                )
+                                .await,
+                            )
+                        }
+                    });
+
+                    if let Err(e) = hyper::server::conn::http1::Builder::new()
+                        .serve_connection(TokioIo::new(stream), service)
+                        .with_upgrades()
+                        .await
+                    {
+                        log::debug!("Gateway connection from {} ended: {}", client_addr, e);
+                    }
+                });
+            }
+        });
+
+        log::info!("Gateway listening on {}", local_addr);
+        Ok(Self {
+            local_addr,
+            shutdown_tx: Some(shutdown_tx),
+            task,
+        })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    pub fn status(&self, servers: &[(String, ServerStatus)]) -> GatewayStatus {
+        GatewayStatus {
+            is_running: !self.task.is_finished(),
+            host: Some(self.local_addr.ip().to_string()),
+            port: Some(self.local_addr.port()),
+            routes: gateway_routes(servers, self.local_addr.port()),
+        }
+    }
+
+    pub fn shutdown(&mut self) {
+        if let Some(shutdown_tx) = self.shutdown_tx.take() {
+            let _ = shutdown_tx.send(());
+        }
+    }
+}
+
+impl Drop for Gateway {
+    fn drop(&mut self) {
+        self.shutdown();
+        self.task.abort();
+    }
+}
+
+async fn handle_request(
+    client: &UpstreamClient,
+    server_manager: &Mutex<PhpServerManager>,
+    gateway_addr: SocketAddr,
+    client_addr: SocketAddr,
+    mut req: Request<Incoming>,
+) -> Response<ProxyBody> {
+    let host = req
+        .headers()
+        .get(HOST)
+        .and_then(|h| h.to_str().ok())
+        .map(|h| h.to_string());
+
+    let Some(target) = resolve_gateway_target(host.as_deref(), req.uri().path()) else {
+        let servers = server_manager.lock().await.list_running_servers();
+        return index_response(&gateway_routes(&servers, gateway_addr.port()));
+    };
+
+    let backend = server_manager
+        .lock()
+        .await
+        .find_project_backend(&target.project);
+    let Some(backend) = backend else {
+        return error_response(
+            StatusCode::SERVICE_UNAVAILABLE,
+            &format!("No running PHP server for project '{}'", target.project),
+        );
+    };
+
+    let Some(prefix) = target.prefix else {
+        return forward_request(client, backend, client_addr, "http", req).await;
+    };
+
+    let query = req
+        .uri()
+        .query()
+        .map(|q| format!("?{}", q))
+        .unwrap_or_default();
+    let Some(rest) = path_below_prefix(req.uri().path(), &prefix) else {
+        return error_response(StatusCode::BAD_REQUEST, "Malformed project path");
+    };
+
+    // Relative links only resolve below the prefix when it ends with a slash
+    if rest.is_empty() {
+        let mut response = error_response(StatusCode::PERMANENT_REDIRECT, "");
+        if let Ok(location) = HeaderValue::from_str(&format!("{}/{}", prefix, query)) {
+            response.headers_mut().insert(LOCATION, location);
+        }
+        return response;
+    }
+
+    match format!("{}{}", rest, query).parse() {
+        Ok(uri) => *req.uri_mut() = uri,
+        Err(e) => return error_response(StatusCode::BAD_REQUEST, &e.to_string()),
+    }
+    if let Ok(value) = HeaderValue::from_str(&prefix) {
+        req.headers_mut().insert("x-forwarded-prefix", value);
+    }
+
+    let mut response = forward_request(client, backend, client_addr, "http", req).await;
+    if let Some(location) = response.headers().get(LOCATION) {
+        let rewritten = rewrite_location(location, host.as_deref(), &prefix);
+        response.headers_mut().insert(LOCATION, rewritten);
+    }
+    response
+}
+
+/// Keeps redirects from a project served under a path prefix inside that prefix.
+fn rewrite_location(value: &HeaderValue, host: Option<&str>, prefix: &str) -> HeaderValue {
+    let Ok(location) = value.to_str() else {
+        return value.clone();
+    };
+
+    let rewritten = if location.starts_with('/') && !location.starts_with("//") {
+        Some(format!("{}{}", prefix, location))
+    } else {
+        host.and_then(|host| {
+            let origin = format!("http://{}", host);
+            location
+                .strip_prefix(&origin)
+                .filter(|rest| rest.is_empty() || rest.starts_with('/'))
+                .map(|rest| format!("{}{}{}", origin, prefix, rest))
+        })
+    };
+
+    rewritten
+        .and_then(|location| HeaderValue::from_str(&location).ok())
+        .unwrap_or_else(|| value.clone())
+}
+
+fn index_response(routes: &[GatewayRoute]) -> Response<ProxyBody> {
+    let mut html = String::from(
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>PHP projects</title></head><body>\n<h1>Running PHP projects</h1>\n<ul>\n",
+    );
+    if routes.is_empty() {
+        html.push_str("<li>No PHP servers are running</li>\n");
+    }
+    for route in routes {
+        let project = escape_html(&route.project);
+        html.push_str(&format!(
+            "<li>{} &mdash; <a href=\"{}\">{}</a> &middot; <a href=\"{}\">{}</a></li>\n",
+            project,
+            escape_html(&route.host_url),
+            escape_html(&route.host_url),
+            escape_html(&route.path_url),
+            escape_html(&route.path_url),
+        ));
+    }
+    html.push_str("</ul>\n</body></html>\n");
+
+    let body = Full::new(Bytes::from(html))
+        .map_err(|never| match never {})
+        .boxed();
+    let mut response = Response::new(body);
+    response.headers_mut().insert(
+        CONTENT_TYPE,
+        HeaderValue::from_static("text/html; charset=utf-8"),
+    );
+    response
+}
+
+fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,990 @@
+use crate::models::{
+    CloneProgress, CloneStage, GitChange, GitCredentials, GitFileStatus, GitPullResult, GitStatus,
+};
+use crate::secret_store::{require_secret, SecretStore};
+use crate::{Error, Result};
+use git2::build::{CheckoutBuilder, RepoBuilder};
+use git2::{Cred, CredentialType, FetchOptions, RemoteCallbacks, Repository};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use url::Url;
+
+/// Event emitted with a `CloneProgress` payload while a clone runs.
+pub const CLONE_PROGRESS_EVENT: &str = "php://clone-progress";
+
+/// Receives clone progress; called from the blocking clone thread.
+pub type ProgressCallback = Box<dyn FnMut(&CloneProgress) + Send>;
+
+/// Progress callbacks are rate limited to this interval, except on stage changes.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Default)]
+pub struct CloneOptions {
+    /// Identifies the clone in progress reports
+    pub clone_id: String,
+    pub branch: Option<String>,
+    /// Tag to check out detached; conflicts with `branch` and `commit`
+    pub tag: Option<String>,
+    /// Commit to check out detached, looked up after fetching `branch`
+    pub commit: Option<String>,
+    /// Shallow clone depth; local transports cannot do shallow fetches
+    pub depth: Option<u32>,
+    /// Fetch only the branch or tag being checked out
+    pub single_branch: bool,
+    pub recurse_submodules: bool,
+    /// Aborts the clone once set
+    pub cancel: Option<Arc<AtomicBool>>,
+    pub progress: Option<ProgressCallback>,
+    /// Explicit credentials; the SSH agent and credential helper are tried otherwise
+    pub credentials: Option<ResolvedCredentials>,
+}
+
+/// Clones `source` into `destination`, checking out `options.branch` when
+/// given and the remote's default branch otherwise, or a detached tag or
+/// commit.
+///
+/// `source` may be any URL libgit2 understands (`https://`, `ssh://`,
+/// `git@host:path`, `file://`) or a path to a local repository. Nothing is
+/// left behind at `destination` when the clone fails or is cancelled.
+pub fn clone_repository(
+    source: &str,
+    destination: &Path,
+    options: CloneOptions,
+) -> Result<Repository> {
+    if destination.exists() {
+        return Err(Error::Config(format!(
+            "Destination already exists: {}",
+            destination.display()
+        )));
+    }
+    if options.tag.is_some() && (options.branch.is_some() || options.commit.is_some()) {
+        return Err(Error::Config(
+            "A tag cannot be combined with a branch or commit".to_string(),
+        ));
+    }
+
+    let cancel = options.cancel.unwrap_or_default();
+    if cancel.load(Ordering::SeqCst) {
+        return Err(Error::Cancelled(format!(
+            "Clone of {} was cancelled",
+            source
+        )));
+    }
+
+    let remote = normalize_remote(source);
+    let refspecs = clone_refspecs(
+        &remote,
+        &options.branch,
+        &options.tag,
+        options.single_branch,
+        &options.credentials,
+    )?;
+
+    let reporter = RefCell::new(ProgressReporter::new(options.clone_id, options.progress));
+
+    let mut callbacks = remote_callbacks(options.credentials.clone());
+    callbacks.transfer_progress(|stats| {
+        reporter.borrow_mut().update(|progress| {
+            progress.stage = if stats.received_objects() < stats.total_objects() {
+                CloneStage::Receiving
+            } else {
+                CloneStage::Resolving
+            };
+            progress.received_objects = stats.received_objects();
+            progress.indexed_objects = stats.indexed_objects();
+            progress.total_objects = stats.total_objects();
+            progress.local_objects = stats.local_objects();
+            progress.received_bytes = stats.received_bytes();
+            progress.indexed_deltas = stats.indexed_deltas();
+            progress.total_deltas = stats.total_deltas();
+        });
+        // Returning false makes libgit2 abort the transfer
+        !cancel.load(Ordering::SeqCst)
+    });
+
+    let mut fetch_options = FetchOptions::new();
+    fetch_options.remote_callbacks(callbacks);
+    if let Some(depth) = options.depth.filter(|depth| *depth > 0) {
+        // Like `git clone --depth` on a plain path, fall back to a full clone
+        if is_local_remote(&remote) {
+            log::warn!("Ignoring depth {} for local repository {}", depth, remote);
+        } else {
+            fetch_options.depth(depth.min(i32::MAX as u32) as i32);
+        }
+    }
+
+    let mut checkout = CheckoutBuilder::new();
+    checkout.progress(|_path, completed, total| {
+        reporter.borrow_mut().update(|progress| {
+            progress.stage = CloneStage::CheckingOut;
+            progress.checkout_completed = completed;
+            progress.checkout_total = total;
+        });
+    });
+
+    log::info!("Cloning {} into {}", remote, destination.display());
+    let cloned = match refspecs {
+        // The remote HEAD is never fetched, which RepoBuilder refuses
+        Some(refspecs) if options.single_branch && options.tag.is_some() => {
+            init_and_fetch(&remote, destination, &refspecs, &mut fetch_options)
+        }
+        refspecs => {
+            let mut builder = RepoBuilder::new();
+            builder.fetch_options(fetch_options).with_checkout(checkout);
+            if let Some(branch) = options.branch.as_deref() {
+                builder.branch(branch);
+            }
+            if let Some(refspecs) = refspecs {
+                builder.remote_create(move |repo, name, url| {
+                    repo.remote_with_fetch(name, url, &refspecs[0])?;
+                    for refspec in &refspecs[1..] {
+                        repo.remote_add_fetch(name, refspec)?;
+                    }
+                    repo.find_remote(name)
+                });
+            }
+            builder.clone(&remote, destination)
+        }
+    };
+
+    let result = cloned.and_then(|repo| {
+        let revision = match (&options.tag, &options.commit) {
+            (Some(tag), _) => Some(format!("refs/tags/{}", tag)),
+            (None, Some(commit)) => Some(commit.clone()),
+            (None, None) => None,
+        };
+        if let Some(revision) = revision {
+            checkout_detached(&repo, &revision, &reporter)?;
+        }
+        if options.recurse_submodules {
+            update_submodules(&repo, &options.credentials, &cancel, &reporter)?;
+        }
+        Ok(repo)
+    });
+
+    // Checkout cannot be interrupted, so a late cancellation is honoured here
+    let cancelled = cancel.load(Ordering::SeqCst);
+    match result {
+        Ok(repo) if !cancelled => {
+            let mut reporter = reporter.borrow_mut();
+            reporter.progress.stage = CloneStage::Done;
+            reporter.emit();
+            Ok(repo)
+        }
+        Ok(_) => {
+            let _ = std::fs::remove_dir_all(destination);
+            Err(Error::Cancelled(format!(
+                "Clone of {} was cancelled",
+                source
+            )))
+        }
+        Err(e) => {
+            let _ = std::fs::remove_dir_all(destination);
+            if cancelled {
+                Err(Error::Cancelled(format!(
+                    "Clone of {} was cancelled",
+                    source
+                )))
+            } else if e.code() == git2::ErrorCode::Auth {
+                Err(Error::PermissionDenied(format!(
+                    "Authentication failed for {}: {}",
+                    source,
+                    e.message()
+                )))
+            } else {
+                Err(Error::Git(format!(
+                    "Failed to clone {}: {}",
+                    source,
+                    e.message()
+                )))
+            }
+        }
+    }
+}
+
+/// Fetch refspecs for the clone's `origin`, or None for git's defaults
+/// (every branch, plus tags pointing into them).
+fn clone_refspecs(
+    remote: &str,
+    branch: &Option<String>,
+    tag: &Option<String>,
+    single_branch: bool,
+    credentials: &Option<ResolvedCredentials>,
+) -> Result<Option<Vec<String>>> {
+    // Shallow histories may not contain the tag, so it is always fetched explicitly
+    let tag_refspec = tag
+        .as_deref()
+        .map(|tag| format!("+refs/tags/{0}:refs/tags/{0}", tag));
+
+    if !single_branch {
+        return Ok(tag_refspec.map(|tag_refspec| {
+            vec![
+                "+refs/heads/*:refs/remotes/origin/*".to_string(),
+                tag_refspec,
+            ]
+        }));
+    }
+
+    if let Some(tag_refspec) = tag_refspec {
+        return Ok(Some(vec![tag_refspec]));
+    }
+    let branch = match branch {
+        Some(branch) => branch.clone(),
+        None => remote_default_branch(remote, credentials.clone())?,
+    };
+    Ok(Some(vec![format!(
+        "+refs/heads/{0}:refs/remotes/origin/{0}",
+        branch
+    )]))
+}
+
+/// Creates `destination` with an `origin` limited to `refspecs` and fetches it,
+/// leaving the checkout to the caller.
+fn init_and_fetch(
+    remote: &str,
+    destination: &Path,
+    refspecs: &[String],
+    fetch_options: &mut FetchOptions<'_>,
+) -> std::result::Result<Repository, git2::Error> {
+    let repo = Repository::init(destination)?;
+    {
+        let mut origin = repo.remote_with_fetch("origin", remote, &refspecs[0])?;
+        for refspec in &refspecs[1..] {
+            repo.remote_add_fetch("origin", refspec)?;
+        }
+        origin.fetch::<&str>(&[], Some(fetch_options), None)?;
+    }
+    Ok(repo)
+}
+
+/// Asks the remote which branch its HEAD points at.
+fn remote_default_branch(remote: &str, credentials: Option<ResolvedCredentials>) -> Result<String> {
+    let mut detached = git2::Remote::create_detached(remote)?;
+    let connection = detached.connect_auth(
+        git2::Direction::Fetch,
+        Some(remote_callbacks(credentials)),
+        None,
+    )?;
+    let head = connection.default_branch()?;
+    let head = head
+        .as_str()
+        .ok_or_else(|| Error::Git(format!("Default branch of {} is not UTF-8", remote)))?;
+    Ok(head.trim_start_matches("refs/heads/").to_string())
+}
+
+fn checkout_detached(
+    repo: &Repository,
+    revision: &str,
+    reporter: &RefCell<ProgressReporter>,
+) -> std::result::Result<(), git2::Error> {
+    let commit = repo
+        .revparse_single(revision)
+        .and_then(|object| object.peel_to_commit())
+        .map_err(|_| {
+            git2::Error::from_str(&format!(
+                "{} was not found in the fetched history",
+                revision.trim_start_matches("refs/tags/")
+            ))
+        })?;
+
+    let mut checkout = CheckoutBuilder::new();
+    checkout.force().progress(|_path, completed, total| {
+        reporter.borrow_mut().update(|progress| {
+            progress.stage = CloneStage::CheckingOut;
+            progress.checkout_completed = completed;
+            progress.checkout_total = total;
+        });
+    });
+    repo.checkout_tree(commit.as_object(), Some(&mut checkout))?;
+    repo.set_head_detached(commit.id())
+}
+
+/// Clones and checks out every submodule at its recorded commit, depth first.
+fn update_submodules(
+    repo: &Repository,
+    credentials: &Option<ResolvedCredentials>,
+    cancel: &AtomicBool,
+    reporter: &RefCell<ProgressReporter>,
+) -> std::result::Result<(), git2::Error> {
+    let submodules = repo.submodules()?;
+    reporter.borrow_mut().update(|progress| {
+        progress.stage = CloneStage::Submodules;
+        progress.submodules_total += submodules.len();
+    });
+
+    for mut submodule in submodules {
+        if cancel.load(Ordering::SeqCst) {
+            return Err(git2::Error::from_str("cancelled"));
+        }
+
+        let mut callbacks = remote_callbacks(credentials.clone());
+        callbacks.transfer_progress(|_| !cancel.load(Ordering::SeqCst));
+        let mut fetch_options = FetchOptions::new();
+        fetch_options.remote_callbacks(callbacks);
+        let mut update_options = git2::SubmoduleUpdateOptions::new();
+        update_options.fetch(fetch_options);
+
+        log::info!("Updating submodule {}", submodule.path().display());
+        submodule.update(true, Some(&mut update_options))?;
+        update_submodules(&submodule.open()?, credentials, cancel, reporter)?;
+
+        reporter.borrow_mut().update(|progress| {
+            progress.submodules_completed += 1;
+        });
+    }
+    Ok(())
+}
+
+/// Fetches `remote` (its configured refspecs) into the repository at
+/// `repo_path`, authenticating the same way clones do.
+pub fn fetch_repository(
+    repo_path: &Path,
+    remote: &str,
+    credentials: Option<ResolvedCredentials>,
+) -> Result<()> {
+    let repo = Repository::open(repo_path)?;
+    let mut remote = repo.find_remote(remote)?;
+
+    let mut fetch_options = FetchOptions::new();
+    fetch_options.remote_callbacks(remote_callbacks(credentials));
+
+    remote
+        .fetch::<&str>(&[], Some(&mut fetch_options), None)
+        .map_err(|e| {
+            let target = remote.url().unwrap_or_default().to_string();
+            if e.code() == git2::ErrorCode::Auth {
+                Error::PermissionDenied(format!(
+                    "Authentication failed for {}: {}",
+                    target,
+                    e.message()
+                ))
+            } else {
+                Error::Git(format!("Failed to fetch {}: {}", target, e.message()))
+            }
+        })
+}
+
+/// URL of the `origin` remote, or of the only remote when it has another name.
+pub fn remote_url(repo_path: &Path) -> Option<String> {
+    let repo = Repository::open(repo_path).ok()?;
+    let remote = match repo.find_remote("origin") {
+        Ok(remote) => remote,
+        Err(_) => {
+            let names = repo.remotes().ok()?;
+            let name = names.iter().flatten().next()?;
+            repo.find_remote(name).ok()?
+        }
+    };
+    remote.url().map(str::to_string)
+}
+
+/// Reports branch, upstream divergence and changed files of the repository
+/// at `repo_path`.
+pub fn repository_status(repo_path: &Path) -> Result<GitStatus> {
+    let repo = Repository::open(repo_path)?;
+    let mut status = GitStatus {
+        detached: repo.head_detached().unwrap_or(false),
+        remote_url: remote_url(repo_path),
+        ..Default::default()
+    };
+
+    // An unborn HEAD (no commits yet) has neither branch nor upstream
+    if let Ok(head) = repo.head() {
+        status.head_commit = head.target().map(|oid| oid.to_string());
+        if head.is_branch() {
+            let local = head.target();
+            let branch = git2::Branch::wrap(head);
+            status.branch = branch.name()?.map(str::to_string);
+
+            if let Ok(upstream) = branch.upstream() {
+                status.upstream = upstream.name()?.map(str::to_string);
+                if let (Some(local), Some(upstream)) = (local, upstream.get().target()) {
+                    let (ahead, behind) = repo.graph_ahead_behind(local, upstream)?;
+                    status.ahead = ahead;
+                    status.behind = behind;
+                }
+                if let Some(name) = branch.get().name() {
+                    if let Ok(remote) = repo.branch_upstream_remote(name) {
+                        if let Some(url) = remote
+                            .as_str()
+                            .and_then(|remote| repo.find_remote(remote).ok())
+                            .and_then(|remote| remote.url().map(str::to_string))
+                        {
+                            status.remote_url = Some(url);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    let mut options = git2::StatusOptions::new();
+    options
+        .include_untracked(true)
+        .include_ignored(false)
+        .renames_head_to_index(true);
+    for entry in repo.statuses(Some(&mut options))?.iter() {
+        let Some(path) = entry.path() else {
+            continue;
+        };
+        let (index, worktree) = file_changes(entry.status());
+        if index.is_some() || worktree.is_some() {
+            status.files.push(GitFileStatus {
+                path: path.to_string(),
+                index,
+                worktree,
+            });
+        }
+    }
+    status.is_dirty = !status.files.is_empty();
+
+    Ok(status)
+}
+
+fn file_changes(status: git2::Status) -> (Option<GitChange>, Option<GitChange>) {
+    if status.is_conflicted() {
+        return (None, Some(GitChange::Conflicted));
+    }
+
+    let index = if status.is_index_new() {
+        Some(GitChange::Added)
+    } else if status.is_index_modified() {
+        Some(GitChange::Modified)
+    } else if status.is_index_deleted() {
+        Some(GitChange::Deleted)
+    } else if status.is_index_renamed() {
+        Some(GitChange::Renamed)
+    } else if status.is_index_typechange() {
+        Some(GitChange::TypeChange)
+    } else {
+        None
+    };
+
+    let worktree = if status.is_wt_new() {
+        Some(GitChange::Untracked)
+    } else if status.is_wt_modified() {
+        Some(GitChange::Modified)
+    } else if status.is_wt_deleted() {
+        Some(GitChange::Deleted)
+    } else if status.is_wt_renamed() {
+        Some(GitChange::Renamed)
+    } else if status.is_wt_typechange() {
+        Some(GitChange::TypeChange)
+    } else {
+        None
+    };
+
+    (index, worktree)
+}
+
+/// Fetches the current branch's upstream remote and fast-forwards onto it.
+///
+/// Diverged branches are reported as an error rather than merged or rebased,
+/// and local changes that the update would overwrite abort it untouched.
+pub fn pull_fast_forward(
+    repo_path: &Path,
+    credentials: Option<ResolvedCredentials>,
+) -> Result<GitPullResult> {
+    let repo = Repository::open(repo_path)?;
+    let head = repo.head()?;
+    if !head.is_branch() {
+        return Err(Error::Git(
+            "HEAD is detached; switch to a branch before pulling".to_string(),
+        ));
+    }
+    let refname = head
+        .name()
+        .ok_or_else(|| Error::Git("Branch name is not UTF-8".to_string()))?
+        .to_string();
+    let branch_name = head.shorthand().unwrap_or(&refname).to_string();
+
+    let remote = repo.branch_upstream_remote(&refname).map_err(|_| {
+        Error::Git(format!(
+            "Branch {} has no upstream to pull from",
+            branch_name
+        ))
+    })?;
+    let remote = remote
+        .as_str()
+        .ok_or_else(|| Error::Git("Remote name is not UTF-8".to_string()))?;
+    fetch_repository(repo_path, remote, credentials)?;
+
+    let upstream_name = repo.branch_upstream_name(&refname)?;
+    let upstream_name = upstream_name
+        .as_str()
+        .ok_or_else(|| Error::Git("Upstream name is not UTF-8".to_string()))?;
+    let upstream = repo.find_reference(upstream_name).map_err(|_| {
+        Error::Git(format!(
+            "Upstream {} of {} was not fetched",
+            upstream_name, branch_name
+        ))
+    })?;
+    let target = repo.reference_to_annotated_commit(&upstream)?;
+    let previous_commit = head.target().map(|oid| oid.to_string());
+
+    let (analysis, _) = repo.merge_analysis(&[&target])?;
+    let updated = if analysis.is_up_to_date() {
+        false
+    } else if analysis.is_fast_forward() {
+        let commit = repo.find_commit(target.id())?;
+        let mut checkout = CheckoutBuilder::new();
+        checkout.safe();
+        repo.checkout_tree(commit.as_object(), Some(&mut checkout))
+            .map_err(checkout_error)?;
+        repo.find_reference(&refname)?.set_target(
+            target.id(),
+            &format!("pull: fast-forward to {}", upstream_name),
+        )?;
+        true
+    } else {
+        return Err(Error::Git(format!(
+            "{} has diverged from {}; only fast-forward pulls are supported",
+            branch_name, upstream_name
+        )));
+    };
+
+    log::info!(
+        "Pulled {} in {}: {}",
+        branch_name,
+        repo_path.display(),
+        if updated {
+            "fast-forwarded"
+        } else {
+            "up to date"
+        }
+    );
+    Ok(GitPullResult {
+        updated,
+        previous_commit,
+        current_commit: Some(target.id().to_string()),
+        status: repository_status(repo_path)?,
+    })
+}
+
+/// Checks out `branch`, creating it from the matching remote-tracking branch
+/// (with that as its upstream) when it only exists on the remote.
+pub fn switch_branch(repo_path: &Path, branch: &str) -> Result<GitStatus> {
+    let repo = Repository::open(repo_path)?;
+
+    let local = match repo.find_branch(branch, git2::BranchType::Local) {
+        Ok(local) => local,
+        Err(_) => {
+            let remote_branch = find_remote_branch(&repo, branch)?;
+            let commit = remote_branch.get().peel_to_commit()?;
+            let mut local = repo.branch(branch, &commit, false)?;
+            if let Some(upstream) = remote_branch.name()? {
+                local.set_upstream(Some(upstream))?;
+            }
+            local
+        }
+    };
+
+    let refname = local
+        .get()
+        .name()
+        .ok_or_else(|| Error::Git("Branch name is not UTF-8".to_string()))?
+        .to_string();
+    let commit = local.get().peel_to_commit()?;
+
+    let mut checkout = CheckoutBuilder::new();
+    checkout.safe();
+    repo.checkout_tree(commit.as_object(), Some(&mut checkout))
+        .map_err(checkout_error)?;
+    repo.set_head(&refname)?;
+
+    log::info!("Switched {} to branch {}", repo_path.display(), branch);
+    repository_status(repo_path)
+}
+
+fn find_remote_branch<'r>(repo: &'r Repository, branch: &str) -> Result<git2::Branch<'r>> {
+    if let Ok(found) = repo.find_branch(&format!("origin/{}", branch), git2::BranchType::Remote) {
+        return Ok(found);
+    }
+    for remote_branch in repo.branches(Some(git2::BranchType::Remote))? {
+        let (remote_branch, _) = remote_branch?;
+        let matches = remote_branch
+            .name()?
+            .and_then(|name| name.split_once('/'))
+            .is_some_and(|(_, name)| name == branch);
+        if matches {
+            return Ok(remote_branch);
+        }
+    }
+    Err(Error::Git(format!("Branch {} does not exist", branch)))
+}
+
+fn checkout_error(e: git2::Error) -> Error {
+    if e.code() == git2::ErrorCode::Conflict {
+        Error::Git(format!(
+            "Local changes would be overwritten; commit or stash them first ({})",
+            e.message()
+        ))
+    } else {
+        e.into()
+    }
+}
+
+/// `GitCredentials` with the referenced secrets looked up, ready to hand to
+/// libgit2 on a blocking thread.
+#[derive(Clone)]
+pub enum ResolvedCredentials {
+    SshAgent {
+        username: Option<String>,
+    },
+    SshKey {
+        username: Option<String>,
+        private_key: PathBuf,
+        public_key: Option<PathBuf>,
+        passphrase: Option<String>,
+    },
+    Token {
+        username: Option<String>,
+        token: String,
+    },
+    CredentialHelper,
+}
+
+// Never print passphrases or tokens
+impl std::fmt::Debug for ResolvedCredentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::SshAgent { .. } => f.write_str("SshAgent"),
+            Self::SshKey { private_key, .. } => write!(f, "SshKey({})", private_key.display()),
+            Self::Token { .. } => f.write_str("Token"),
+            Self::CredentialHelper => f.write_str("CredentialHelper"),
+        }
+    }
+}
+
+impl ResolvedCredentials {
+    pub fn resolve(credentials: &GitCredentials, store: &dyn SecretStore) -> Result<Self> {
+        Ok(match credentials {
+            GitCredentials::SshAgent { username } => Self::SshAgent {
+                username: username.clone(),
+            },
+            GitCredentials::SshKey {
+                username,
+                private_key_path,
+                public_key_path,
+                passphrase_secret_id,
+            } => {
+                let private_key = expand_home(private_key_path);
+                if !private_key.is_file() {
+                    return Err(Error::Config(format!(
+                        "SSH private key not found: {}",
+                        private_key.display()
+                    )));
+                }
+                Self::SshKey {
+                    username: username.clone(),
+                    private_key,
+                    public_key: public_key_path.as_deref().map(expand_home),
+                    passphrase: passphrase_secret_id
+                        .as_deref()
+                        .map(|id| require_secret(store, id))
+                        .transpose()?,
+                }
+            }
+            GitCredentials::Token {
+                username,
+                token_secret_id,
+            } => Self::Token {
+                username: username.clone(),
+                token: require_secret(store, token_secret_id)?,
+            },
+            GitCredentials::CredentialHelper => Self::CredentialHelper,
+        })
+    }
+}
+
+/// Username sent with tokens when none is given; GitHub, GitLab and Gitea
+/// accept any non-empty name for personal access tokens.
+const DEFAULT_TOKEN_USERNAME: &str = "x-access-token";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CredentialMethod {
+    SshAgent,
+    SshKey,
+    Token,
+    CredentialHelper,
+}
+
+impl CredentialMethod {
+    fn allowed(self, allowed: CredentialType) -> bool {
+        match self {
+            Self::SshAgent | Self::SshKey => allowed.contains(CredentialType::SSH_KEY),
+            Self::Token | Self::CredentialHelper => {
+                allowed.contains(CredentialType::USER_PASS_PLAINTEXT)
+            }
+        }
+    }
+}
+
+/// Answers libgit2's credential requests, trying each applicable method once.
+///
+/// libgit2 asks again after every rejected credential, so without the
+/// bookkeeping a wrong token would loop forever.
+pub struct CredentialProvider {
+    credentials: Option<ResolvedCredentials>,
+    tried: Vec<CredentialMethod>,
+}
+
+impl CredentialProvider {
+    pub fn new(credentials: Option<ResolvedCredentials>) -> Self {
+        Self {
+            credentials,
+            tried: Vec::new(),
+        }
+    }
+
+    pub fn credentials(
+        &mut self,
+        url: &str,
+        username_from_url: Option<&str>,
+        allowed: CredentialType,
+    ) -> std::result::Result<Cred, git2::Error> {
+        // SSH without a user in the URL first asks for the name alone
+        if allowed.contains(CredentialType::USERNAME) {
+            return Cred::username(&self.ssh_username(username_from_url));
+        }
+
+        let candidates: &[CredentialMethod] = match &self.credentials {
+            Some(ResolvedCredentials::SshAgent { .. }) => &[CredentialMethod::SshAgent],
+            Some(ResolvedCredentials::SshKey { .. }) => &[CredentialMethod::SshKey],
+            Some(ResolvedCredentials::Token { .. }) => &[CredentialMethod::Token],
+            Some(ResolvedCredentials::CredentialHelper) => &[CredentialMethod::CredentialHelper],
+            None => &[
+                CredentialMethod::SshAgent,
+                CredentialMethod::CredentialHelper,
+            ],
+        };
+
+        for &method in candidates {
+            if !method.allowed(allowed) || self.tried.contains(&method) {
+                continue;
+            }
+            self.tried.push(method);
+            match self.build(method, url, username_from_url) {
+                Ok(cred) => return Ok(cred),
+                Err(e) => log::debug!("{:?} credentials unavailable for {}: {}", method, url, e),
+            }
+        }
+
+        Err(git2::Error::new(
+            git2::ErrorCode::Auth,
+            git2::ErrorClass::Net,
+            format!("no accepted credentials for {}", url),
+        ))
+    }
+
+    fn build(
+        &self,
+        method: CredentialMethod,
+        url: &str,
+        username_from_url: Option<&str>,
+    ) -> std::result::Result<Cred, git2::Error> {
+        match (method, &self.credentials) {
+            (CredentialMethod::SshAgent, _) => {
+                Cred::ssh_key_from_agent(&self.ssh_username(username_from_url))
+            }
+            (
+                CredentialMethod::SshKey,
+                Some(ResolvedCredentials::SshKey {
+                    private_key,
+                    public_key,
+                    passphrase,
+                    ..
+                }),
+            ) => Cred::ssh_key(
+                &self.ssh_username(username_from_url),
+                public_key.as_deref(),
+                private_key,
+                passphrase.as_deref(),
+            ),
+            (CredentialMethod::Token, Some(ResolvedCredentials::Token { username, token })) => {
+                let username = username
+                    .as_deref()
+                    .or(username_from_url)
+                    .unwrap_or(DEFAULT_TOKEN_USERNAME);
+                Cred::userpass_plaintext(username, token)
+            }
+            (CredentialMethod::CredentialHelper, _) => {
+                let config = git2::Config::open_default()?;
+                Cred::credential_helper(&config, url, username_from_url)
+            }
+            _ => Err(git2::Error::from_str("credentials do not match the method")),
+        }
+    }
+
+    fn ssh_username(&self, username_from_url: Option<&str>) -> String {
+        let configured = match &self.credentials {
+            Some(ResolvedCredentials::SshAgent { username })
+            | Some(ResolvedCredentials::SshKey { username, .. }) => username.as_deref(),
+            _ => None,
+        };
+        configured
+            .or(username_from_url)
+            .unwrap_or("git")
+            .to_string()
+    }
+}
+
+/// Callbacks shared by clone and fetch, with authentication wired in.
+fn remote_callbacks<'a>(credentials: Option<ResolvedCredentials>) -> RemoteCallbacks<'a> {
+    let mut provider = CredentialProvider::new(credentials);
+    let mut callbacks = RemoteCallbacks::new();
+    callbacks.credentials(move |url, username_from_url, allowed| {
+        provider.credentials(url, username_from_url, allowed)
+    });
+    callbacks
+}
+
+pub(crate) fn expand_home(path: &str) -> PathBuf {
+    match path.strip_prefix("~/").or(path.strip_prefix("~\\")) {
+        Some(rest) => dirs::home_dir()
+            .map(|home| home.join(rest))
+            .unwrap_or_else(|| PathBuf::from(path)),
+        None => PathBuf::from(path),
+    }
+}
+
+struct ProgressReporter {
+    progress: CloneProgress,
+    callback: Option<ProgressCallback>,
+    last_emit: Option<Instant>,
+}
+
+impl ProgressReporter {
+    fn new(clone_id: String, callback: Option<ProgressCallback>) -> Self {
+        Self {
+            progress: CloneProgress {
+                clone_id,
+                ..Default::default()
+            },
+            callback,
+            last_emit: None,
+        }
+    }
+
+    fn update(&mut self, apply: impl FnOnce(&mut CloneProgress)) {
+        let stage = self.progress.stage;
+        apply(&mut self.progress);
+
+        let due = self
+            .last_emit
+            .map_or(true, |last| last.elapsed() >= PROGRESS_INTERVAL);
+        if due || stage != self.progress.stage {
+            self.emit();
+        }
+    }
+
+    fn emit(&mut self) {
+        if let Some(callback) = self.callback.as_mut() {
+            callback(&self.progress);
+        }
+        self.last_emit = Some(Instant::now());
+    }
+}
+
+/// Cancellation flags of the clones in flight, keyed by clone id.
+#[derive(Default)]
+pub struct CloneRegistry {
+    clones: Mutex<HashMap<String, Arc<AtomicBool>>>,
+}
+
+impl CloneRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(&self, clone_id: &str) -> Result<Arc<AtomicBool>> {
+        let mut clones = self.clones.lock().unwrap_or_else(|e| e.into_inner());
+        if clones.contains_key(clone_id) {
+            return Err(Error::Git(format!("Clone {} is already running", clone_id)));
+        }
+        let cancel = Arc::new(AtomicBool::new(false));
+        clones.insert(clone_id.to_string(), cancel.clone());
+        Ok(cancel)
+    }
+
+    pub fn unregister(&self, clone_id: &str) {
+        let mut clones = self.clones.lock().unwrap_or_else(|e| e.into_inner());
+        clones.remove(clone_id);
+    }
+
+    /// Requests cancellation, returning false when no such clone is running.
+    pub fn cancel(&self, clone_id: &str) -> bool {
+        let clones = self.clones.lock().unwrap_or_else(|e| e.into_inner());
+        match clones.get(clone_id) {
+            Some(cancel) => {
+                cancel.store(true, Ordering::SeqCst);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Turns relative local paths into absolute ones so they do not depend on the
+/// process working directory; URLs are passed through unchanged.
+pub fn normalize_remote(source: &str) -> String {
+    if is_url_like(source) {
+        return source.to_string();
+    }
+
+    std::fs::canonicalize(source)
+        .map(|path| path.to_string_lossy().to_string())
+        .unwrap_or_else(|_| source.to_string())
+}
+
+/// Derives a directory name from a remote: the last path component without a
+/// `.git` suffix, for URLs, scp-style `user@host:path` remotes and local paths.
+pub fn repository_name(source: &str) -> Result<String> {
+    let path = match Url::parse(source) {
+        Ok(url) if url.scheme().len() > 1 => url.path().to_string(),
+        // Single-letter schemes are Windows drive letters, not URLs
+        _ => match scp_like_path(source) {
+            Some(path) => path.to_string(),
+            None => source.replace('\\', "/"),
+        },
+    };
+
+    let name = path
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or_default()
+        .trim_end_matches(".git");
+
+    if name.is_empty() || name == "." || name == ".." {
+        return Err(Error::InvalidUrl(format!(
+            "Cannot extract project name from {}",
+            source
+        )));
+    }
+    Ok(name.to_string())
+}
+
+/// Local paths and `file://` URLs, which libgit2 serves without a transport.
+fn is_local_remote(remote: &str) -> bool {
+    match Url::parse(remote) {
+        Ok(url) if url.scheme().len() > 1 => url.scheme() == "file",
+        _ => scp_like_path(remote).is_none(),
+    }
+}
+
+fn is_url_like(source: &str) -> bool {
+    matches!(Url::parse(source), Ok(url) if url.scheme().len() > 1)
+        || scp_like_path(source).is_some()
+}
+
+/// Returns the path of an scp-style remote such as `git@github.com:user/repo.git`.
+fn scp_like_path(source: &str) -> Option<&str> {
+    let (host, path) = source.split_once(':')?;
+    let looks_like_host = host.len() > 1 && !host.contains('/') && !host.contains('\\');
+    (looks_like_host && !path.starts_with("//")).then_some(path)
+}
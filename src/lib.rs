@@ -8,40 +8,101 @@ use tokio::sync::Mutex;
 pub use models::*;
 
 mod commands;
+pub mod composer;
+pub mod composer_manifest;
+pub mod config;
+pub mod env_file;
 mod error;
+pub mod fastcgi;
+pub mod fastcgi_server;
+pub mod framework_command;
 pub mod framework_detector;
+pub mod gateway;
+pub mod git;
 pub mod models;
 pub mod php_binary;
 pub mod php_server;
 pub mod project_manager;
+pub mod project_registry;
+pub mod project_templates;
+pub mod project_trash;
+pub mod protocol;
+pub mod proxy;
+pub mod secret_store;
+pub mod setup_pipeline;
+pub mod tls;
 
 pub use error::{Error, Result};
 
-/// Initializes the plugin.
-pub fn init<R: Runtime>() -> TauriPlugin<R, Config> {
-    Builder::<R, Config>::new("php")
+/// Initializes the plugin with the optional `plugins.php` section of
+/// `tauri.conf.json`, overridden by the config saved with `set_php_config`.
+pub fn init<R: Runtime>() -> TauriPlugin<R, Option<Config>> {
+    Builder::<R, Option<Config>>::new("php")
         .invoke_handler(tauri::generate_handler![
             commands::download_php_binary,
             commands::start_php_server,
             commands::stop_php_server,
             commands::get_server_status,
             commands::clone_project,
+            commands::cancel_clone,
+            commands::create_project,
+            commands::list_project_templates,
+            commands::setup_project,
+            commands::set_secret,
+            commands::delete_secret,
+            commands::has_secret,
             commands::detect_framework,
+            commands::rank_frameworks,
             commands::get_php_version,
+            commands::get_php_extensions,
+            commands::check_php_extensions,
             commands::list_available_php_versions,
             commands::set_php_config,
             commands::get_php_config,
             commands::list_running_servers,
             commands::list_projects,
+            commands::import_project,
+            commands::get_project,
+            commands::get_project_settings,
+            commands::update_project_settings,
             commands::remove_project,
+            commands::list_trash,
+            commands::restore_project,
+            commands::purge_trash,
+            commands::get_git_status,
+            commands::fetch_project,
+            commands::pull_project,
+            commands::switch_branch,
             commands::remove_php_binary,
+            commands::download_composer,
+            commands::install_composer_from_file,
+            commands::list_composer_versions,
+            commands::remove_composer,
+            commands::run_composer,
+            commands::get_project_dependencies,
+            commands::run_framework_command,
+            commands::cancel_framework_command,
+            commands::list_framework_commands,
             commands::get_server_logs,
             commands::find_available_port,
             commands::stop_all_servers,
             commands::get_framework_info,
             commands::validate_project_path,
             commands::get_project_info,
+            commands::get_https_ca_certificate,
+            commands::start_gateway,
+            commands::stop_gateway,
+            commands::get_gateway_status,
         ])
+        .register_asynchronous_uri_scheme_protocol(
+            protocol::URI_SCHEME,
+            |ctx, request, responder| {
+                let protocol = ctx.app_handle().state::<PhpManager>().protocol.clone();
+                tauri::async_runtime::spawn(async move {
+                    responder.respond(protocol::handle_request(&protocol, request).await);
+                });
+            },
+        )
         .setup(|app, api| {
             #[cfg(mobile)]
             let php = mobile::init(app, api)?;
@@ -59,9 +120,9 @@ mod desktop {
 
     pub fn init<R: Runtime>(
         _app: &tauri::AppHandle<R>,
-        _api: tauri::plugin::PluginApi<R, super::models::Config>,
+        api: tauri::plugin::PluginApi<R, Option<super::models::Config>>,
     ) -> crate::Result<PhpManager> {
-        Ok(PhpManager::new())
+        Ok(PhpManager::with_config(load_plugin_config(api.config())))
     }
 }
 
@@ -71,17 +132,31 @@ mod mobile {
 
     pub fn init<R: Runtime>(
         _app: &tauri::AppHandle<R>,
-        _api: tauri::plugin::PluginApi<R, super::models::Config>,
+        api: tauri::plugin::PluginApi<R, Option<super::models::Config>>,
     ) -> crate::Result<PhpManager> {
         // Mobile implementation would be different
-        Ok(PhpManager::new())
+        Ok(PhpManager::with_config(load_plugin_config(api.config())))
     }
 }
 
+fn load_plugin_config(config: &Option<Config>) -> Config {
+    config::load_config(
+        config.clone().unwrap_or_default(),
+        &config::config_file_path(),
+    )
+}
+
 pub struct PhpManager {
     pub binary_manager: Arc<Mutex<php_binary::PhpBinaryManager>>,
+    pub composer_manager: Arc<Mutex<composer::ComposerManager>>,
     pub server_manager: Arc<Mutex<php_server::PhpServerManager>>,
+    pub protocol: protocol::ProtocolState,
     pub project_manager: Arc<Mutex<project_manager::ProjectManager>>,
+    pub gateway: Arc<Mutex<Option<gateway::Gateway>>>,
+    pub clone_registry: Arc<git::CloneRegistry>,
+    pub command_registry: Arc<framework_command::CommandRegistry>,
+    pub secret_store: secret_store::SharedSecretStore,
+    pub config: Arc<Mutex<Config>>,
 }
 
 impl Default for PhpManager {
@@ -92,10 +167,88 @@ impl Default for PhpManager {
 
 impl PhpManager {
     pub fn new() -> Self {
+        Self::with_config(Config::default())
+    }
+
+    pub fn with_config(config: Config) -> Self {
+        let secret_store = secret_store::SharedSecretStore::default();
+        let mut project_manager = project_manager::ProjectManager::with_config(&config);
+        project_manager.set_secret_store(secret_store.clone());
+        let server_manager = Arc::new(Mutex::new(php_server::PhpServerManager::with_config(
+            config.server_config.clone(),
+        )));
+
         Self {
-            binary_manager: Arc::new(Mutex::new(php_binary::PhpBinaryManager::new())),
-            server_manager: Arc::new(Mutex::new(php_server::PhpServerManager::new())),
-            project_manager: Arc::new(Mutex::new(project_manager::ProjectManager::new())),
+            binary_manager: Arc::new(Mutex::new(php_binary::PhpBinaryManager::with_config(
+                &config,
+            ))),
+            composer_manager: Arc::new(Mutex::new(composer::ComposerManager::new())),
+            protocol: protocol::ProtocolState::new(server_manager.clone()),
+            server_manager,
+            project_manager: Arc::new(Mutex::new(project_manager)),
+            gateway: Arc::new(Mutex::new(None)),
+            clone_registry: Arc::new(git::CloneRegistry::new()),
+            command_registry: Arc::new(framework_command::CommandRegistry::new()),
+            secret_store,
+            config: Arc::new(Mutex::new(config)),
+        }
+    }
+
+    /// Applies `config` to the live managers. Running servers keep their
+    /// settings; servers started afterwards use the new defaults.
+    pub async fn apply_config(&self, config: Config) -> Result<()> {
+        config::validate_config(&config)?;
+        self.binary_manager.lock().await.apply_config(&config);
+        self.server_manager
+            .lock()
+            .await
+            .set_server_config(config.server_config.clone());
+        self.project_manager.lock().await.apply_config(&config);
+        *self.config.lock().await = config;
+        Ok(())
+    }
+
+    /// Removes a project folder after making sure no managed server still
+    /// uses it; with `force` those servers are stopped first.
+    pub async fn remove_project(
+        &self,
+        project_name: &str,
+        options: RemoveProjectOptions,
+    ) -> Result<RemoveProjectResult> {
+        let project_path = self
+            .project_manager
+            .lock()
+            .await
+            .project_directory(project_name)?;
+
+        let mut server_manager = self.server_manager.lock().await;
+        let server_ids = server_manager.servers_for_project(&project_path);
+        if !server_ids.is_empty() && !options.force {
+            return Err(Error::PhpServer(format!(
+                "Project {} is used by running server(s): {}",
+                project_name,
+                server_ids.join(", ")
+            )));
+        }
+        for server_id in &server_ids {
+            server_manager.stop_server(server_id).await?;
         }
+        drop(server_manager);
+
+        let trashed = self
+            .project_manager
+            .lock()
+            .await
+            .remove_project(project_name, options.trash)?;
+        Ok(RemoveProjectResult {
+            stopped_servers: server_ids,
+            trashed,
+        })
+    }
+
+    /// Keeps clone and fetch secrets in `store` (e.g. the OS keychain) instead
+    /// of the default file in the plugin's data directory.
+    pub fn set_secret_store(&self, store: Arc<dyn secret_store::SecretStore>) {
+        self.secret_store.replace(store);
     }
 }
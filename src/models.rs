@@ -1,10 +1,16 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
+/// Plugin configuration, read from `plugins.php` in `tauri.conf.json` and
+/// overridden by what `set_php_config` saved.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Config {
     pub php_versions: Vec<String>,
     pub default_php_version: Option<String>,
+    /// Release URL the `v<version>/<file>` path is appended to
     pub download_base_url: String,
+    /// Where projects are cloned and listed from, `~/tauri-php-projects` when unset
     pub projects_dir: Option<String>,
     pub server_config: ServerConfig,
 }
@@ -26,10 +32,13 @@ impl Default for Config {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ServerConfig {
+    /// First port tried when a server is started without one
     pub default_port: u16,
     pub default_host: String,
     pub auto_reload: bool,
+    /// Relative to the project root unless absolute; the root itself when unset
     pub document_root: Option<String>,
 }
 
@@ -53,6 +62,16 @@ pub struct PhpBinaryInfo {
     pub size: Option<u64>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComposerInfo {
+    pub version: String,
+    /// Path of the managed `composer.phar`
+    pub path: String,
+    pub is_installed: bool,
+    pub download_url: Option<String>,
+    pub size: Option<u64>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ServerStatus {
     pub is_running: bool,
@@ -61,6 +80,34 @@ pub struct ServerStatus {
     pub host: Option<String>,
     pub document_root: Option<String>,
     pub started_at: Option<String>,
+    /// Whether `port` is served over HTTPS by the plugin's TLS proxy
+    #[serde(default)]
+    pub https: bool,
+    #[serde(default)]
+    pub project_path: Option<String>,
+    #[serde(default)]
+    pub backend: ServerBackend,
+}
+
+/// The shared front door serving all running projects under one port.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GatewayStatus {
+    pub is_running: bool,
+    pub host: Option<String>,
+    pub port: Option<u16>,
+    pub routes: Vec<GatewayRoute>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GatewayRoute {
+    pub project: String,
+    pub server_id: String,
+    /// e.g. `http://project-a.localhost:8080/`
+    pub host_url: String,
+    /// e.g. `http://localhost:8080/project-a/`
+    pub path_url: String,
+    /// Port the project's own server listens on
+    pub upstream_port: Option<u16>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -68,12 +115,410 @@ pub struct ProjectInfo {
     pub name: String,
     pub path: String,
     pub framework: Option<Framework>,
+    /// Version of the detected framework, when it can be told
+    #[serde(default)]
+    pub framework_version: Option<FrameworkVersion>,
     pub git_url: Option<String>,
     pub php_version: Option<String>,
     pub entry_point: Option<String>,
+    /// Stable registry id, None for directories that are not registered
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default)]
+    pub settings: ProjectSettings,
+    /// What the automatic setup did, when it ran
+    #[serde(default)]
+    pub setup: Option<SetupReport>,
+}
+
+/// Per-project preferences, used as defaults when the project's server starts.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct ProjectSettings {
+    /// Replaces the directory name in project lists
+    pub display_name: Option<String>,
+    pub php_version: Option<String>,
+    pub port: Option<u16>,
+    /// Relative to the project root unless absolute
+    pub document_root: Option<String>,
+    /// Environment variables passed to the PHP processes
+    pub env: BTreeMap<String, String>,
+    /// Defaults for `run_composer`
+    pub composer: ComposerSettings,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct ComposerSettings {
+    /// Whether install, update and dump-autoload include dev packages;
+    /// Composer's default (they do) when unset
+    pub dev: Option<bool>,
+    /// Flags added to every Composer run, e.g. `--prefer-dist`
+    pub extra_args: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ComposerAction {
+    #[default]
+    Install,
+    Update,
+    Require,
+    Remove,
+    DumpAutoload,
+}
+
+impl ComposerAction {
+    /// The Composer subcommand
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ComposerAction::Install => "install",
+            ComposerAction::Update => "update",
+            ComposerAction::Require => "require",
+            ComposerAction::Remove => "remove",
+            ComposerAction::DumpAutoload => "dump-autoload",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ComposerRequest {
+    pub project_path: String,
+    pub action: ComposerAction,
+    /// Packages to require, remove or update, e.g. `monolog/monolog:^3.0`
+    #[serde(default)]
+    pub packages: Vec<String>,
+    /// For install, update and dump-autoload, false adds `--no-dev`; for
+    /// require and remove, true adds `--dev`. Falls back to the project's settings
+    #[serde(default)]
+    pub dev: Option<bool>,
+    /// Flags after the project's own `extra_args`
+    #[serde(default)]
+    pub extra_args: Vec<String>,
+    /// Keys the output events; generated when omitted
+    #[serde(default)]
+    pub run_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+/// Payload of the `php://composer-output` event, one per line.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComposerOutput {
+    pub run_id: String,
+    pub stream: OutputStream,
+    pub line: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComposerResult {
+    pub run_id: String,
+    pub action: ComposerAction,
+    /// None when Composer was killed by a signal
+    pub exit_code: Option<i32>,
+    pub success: bool,
+    pub duration_ms: u64,
+    /// Combined stdout and stderr, in the order the lines arrived
+    pub output: String,
+    pub problems: Vec<ComposerProblem>,
+}
+
+/// A command for the project's framework console, e.g. `artisan` or
+/// `bin/console`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FrameworkCommandRequest {
+    pub project_path: String,
+    /// Arguments after the console script, e.g. `["migrate", "--force"]`
+    pub args: Vec<String>,
+    /// Added to the project's environment variables
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// Keys the output events and `cancel_framework_command`; generated when omitted
+    #[serde(default)]
+    pub run_id: Option<String>,
+}
+
+/// Payload of the `php://framework-command-output` event, one per line.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FrameworkCommandOutput {
+    pub run_id: String,
+    pub stream: OutputStream,
+    pub line: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FrameworkCommandResult {
+    pub run_id: String,
+    pub framework: Framework,
+    /// The console script relative to the project, e.g. `bin/console`
+    pub script: String,
+    pub args: Vec<String>,
+    /// None when the command was killed by a signal
+    pub exit_code: Option<i32>,
+    pub success: bool,
+    pub duration_ms: u64,
+    /// Combined stdout and stderr, in the order the lines arrived
+    pub output: String,
+}
+
+/// The commands a project's framework console offers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsoleCommandList {
+    pub framework: Framework,
+    pub script: String,
+    pub commands: Vec<ConsoleCommand>,
+    /// SHA-256 of `composer.lock` the list was cached under; None without a lock file
+    pub lock_hash: Option<String>,
+    /// Served from the cache rather than by running the console
+    pub cached: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ConsoleCommand {
+    /// What to pass as the first argument, e.g. `make:controller`
+    pub name: String,
+    pub description: String,
+    /// Only known for consoles that describe themselves as JSON
+    pub arguments: Vec<ConsoleArgument>,
+    pub options: Vec<ConsoleOption>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ConsoleArgument {
+    pub name: String,
+    pub description: String,
+    pub required: bool,
+    /// Takes any number of values
+    pub is_array: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ConsoleOption {
+    /// With its dashes, e.g. `--force`
+    pub name: String,
+    pub shortcut: Option<String>,
+    pub description: String,
+    pub accepts_value: bool,
+}
+
+/// One of the problems Composer reports when it cannot resolve or install
+/// the requirements.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ComposerProblem {
+    /// The `Problem N` number; None for errors reported outside that list
+    pub number: Option<usize>,
+    pub messages: Vec<String>,
+    /// PHP extensions Composer reported as missing, e.g. `mbstring`
+    pub missing_extensions: Vec<String>,
+}
+
+/// Direct Composer requirements checked against `composer.lock`, and the
+/// platform requirements of the project and its locked packages.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectDependencies {
+    pub project_path: String,
+    pub has_lock_file: bool,
+    /// Some direct requirement is missing from the lock file or locked at a
+    /// version its constraint no longer allows; `composer update` is needed
+    pub lock_outdated: bool,
+    pub packages: Vec<DependencyInfo>,
+    /// `ext-*` requirements
+    pub extensions: Vec<PlatformRequirement>,
+    /// `php`, `lib-*` and Composer API requirements
+    pub platform: Vec<PlatformRequirement>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DependencyInfo {
+    pub name: String,
+    pub constraint: String,
+    /// Listed in `require-dev`
+    pub dev: bool,
+    pub locked_version: Option<String>,
+    pub status: DependencyStatus,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum DependencyStatus {
+    /// The locked version satisfies the constraint
+    UpToDate,
+    /// The locked version no longer satisfies the constraint
+    Outdated,
+    /// Not in the lock file, or there is no lock file
+    NotLocked,
+    /// Locked to a branch or a constraint the plugin cannot evaluate
+    Unknown,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PlatformRequirement {
+    /// Lowercase package name, e.g. `php` or `ext-intl`
+    pub name: String,
+    /// Constraint of the first package requiring it, the project's own first
+    pub constraint: String,
+    /// Only required by `require-dev` and dev packages
+    pub dev: bool,
+    /// `composer.json` for the project itself, otherwise package names
+    pub required_by: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SetupProjectRequest {
+    pub project_path: String,
+    #[serde(default)]
+    pub laravel: LaravelSetupOptions,
+    /// Report the steps that would run without running them
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LaravelSetupOptions {
+    /// Run `artisan migrate`
+    pub migrate: bool,
+    /// Seed the database while migrating
+    pub seed: bool,
+    /// Written to `.env` before migrating
+    pub database: Option<DatabaseConfig>,
+    /// Run `artisan storage:link`
+    pub storage_link: bool,
+    /// Run `artisan optimize:clear`
+    pub clear_caches: bool,
+}
+
+impl Default for LaravelSetupOptions {
+    fn default() -> Self {
+        Self {
+            migrate: false,
+            seed: false,
+            database: None,
+            storage_link: true,
+            clear_caches: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DatabaseConfig {
+    /// Laravel connection name: `sqlite`, `mysql`, `mariadb`, `pgsql` or `sqlsrv`
+    pub connection: String,
+    pub host: Option<String>,
+    pub port: Option<u16>,
+    /// Database name, or for SQLite the database file: relative to the
+    /// project, where it is created, or an absolute path to an existing one
+    pub database: Option<String>,
+    pub username: Option<String>,
+    pub password: Option<String>,
+}
+
+/// The steps a project setup went through, in order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetupReport {
+    pub framework: Framework,
+    #[serde(default)]
+    pub framework_version: Option<FrameworkVersion>,
+    pub steps: Vec<SetupStep>,
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+impl SetupReport {
+    pub fn succeeded(&self) -> bool {
+        self.steps
+            .iter()
+            .all(|step| step.status != SetupStepStatus::Failed)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetupStep {
+    pub name: String,
+    pub status: SetupStepStatus,
+    /// What was done, or why the step was skipped or failed
+    pub message: Option<String>,
+    /// Output of the command the step ran
+    #[serde(default)]
+    pub output: String,
+}
+
+impl SetupStep {
+    pub fn succeeded(name: &str, message: Option<String>) -> Self {
+        Self::new(name, SetupStepStatus::Succeeded, message)
+    }
+
+    pub fn skipped(name: &str, reason: impl Into<String>) -> Self {
+        Self::new(name, SetupStepStatus::Skipped, Some(reason.into()))
+    }
+
+    pub fn failed(name: &str, message: impl Into<String>) -> Self {
+        Self::new(name, SetupStepStatus::Failed, Some(message.into()))
+    }
+
+    pub fn planned(name: &str, description: impl Into<String>) -> Self {
+        Self::new(name, SetupStepStatus::Planned, Some(description.into()))
+    }
+
+    fn new(name: &str, status: SetupStepStatus, message: Option<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            status,
+            message,
+            output: String::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum SetupStepStatus {
+    Succeeded,
+    Skipped,
+    Failed,
+    /// Would run; only reported by dry runs
+    Planned,
+}
+
+/// A project's PHP extension requirements compared with what a PHP binary loads.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtensionReport {
+    pub php_executable: String,
+    /// Output of `php -m`, normalized like Composer's `ext-*` names
+    pub loaded: Vec<String>,
+    pub required: Vec<RequiredExtension>,
+    pub missing: Vec<RequiredExtension>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RequiredExtension {
+    /// Extension name without the `ext-` prefix, e.g. `intl`
+    pub name: String,
+    /// `composer.json`, Composer packages or the detected framework
+    pub required_by: Vec<String>,
+    /// Only needed by development dependencies
+    pub dev: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RemoveProjectOptions {
+    /// Stop the managed servers using the project instead of refusing
+    pub force: bool,
+    /// Move the folder to the plugin's trash instead of deleting it
+    pub trash: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RemoveProjectResult {
+    /// Ids of the servers stopped because of `force`
+    pub stopped_servers: Vec<String>,
+    pub trashed: Option<crate::project_trash::TrashedProject>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
 pub enum Framework {
     Laravel,
     Symfony,
@@ -85,6 +530,7 @@ pub enum Framework {
     Slim,
     Lumen,
     ThinkPHP,
+    #[default]
     Plain,
     Unknown,
 }
@@ -108,19 +554,294 @@ impl Framework {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// A framework's major and minor version as installed in a project.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FrameworkVersion {
+    pub major: u32,
+    /// Layout fingerprints only tell the major version
+    pub minor: Option<u32>,
+    pub source: VersionSource,
+}
+
+impl FrameworkVersion {
+    /// Whether this is older than `major.minor`; an unknown minor counts as 0.
+    pub fn is_before(&self, major: u32, minor: u32) -> bool {
+        (self.major, self.minor.unwrap_or(0)) < (major, minor)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum VersionSource {
+    /// The framework package's version in `composer.lock`
+    LockFile,
+    /// A version constant in the framework's source, e.g. `Application::VERSION`
+    VersionConstant,
+    /// Files and directories only one major version has
+    Layout,
+}
+
+/// A framework a project may use, scored by the evidence found for it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FrameworkCandidate {
+    pub framework: Framework,
+    /// Sum of the evidence weights
+    pub score: u32,
+    /// From 0 to 1; a locked framework package alone is 1
+    pub confidence: f32,
+    pub evidence: Vec<DetectionEvidence>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DetectionEvidence {
+    pub kind: EvidenceKind,
+    /// A Composer package name, or a path relative to the project
+    pub source: String,
+    pub weight: u32,
+}
+
+impl DetectionEvidence {
+    pub fn new(kind: EvidenceKind, source: &str, weight: u32) -> Self {
+        Self {
+            kind,
+            source: source.to_string(),
+            weight,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum EvidenceKind {
+    /// A package in `composer.lock`, the strongest signal
+    LockedPackage,
+    /// A package in `composer.json`'s `require` or `require-dev`
+    RequiredPackage,
+    /// A file or directory the framework's layout has
+    File,
+    /// A file that mentions the framework's classes
+    FileContent,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct StartServerRequest {
     pub project_path: String,
     pub port: Option<u16>,
     pub host: Option<String>,
     pub php_version: Option<String>,
     pub document_root: Option<String>,
+    /// Serve the project over HTTPS through a TLS-terminating proxy on `port`,
+    /// with `php -S` listening on an internal loopback port
+    #[serde(default)]
+    pub https: bool,
+    #[serde(default)]
+    pub backend: ServerBackend,
+    /// Worker pool settings, only used by the FastCGI backends
+    #[serde(default)]
+    pub fastcgi: Option<FastCgiOptions>,
+    /// Registered project whose settings fill in the fields left unset
+    #[serde(default)]
+    pub project_id: Option<String>,
+    /// Environment variables for the PHP processes, on top of the project's
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// Refuse to start when the PHP binary lacks an extension the project
+    /// needs outside development; otherwise missing extensions are only logged
+    #[serde(default)]
+    pub require_extensions: bool,
+}
+
+/// How PHP requests of a server are executed.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ServerBackend {
+    /// `php -S`, single-threaded, the default for development
+    #[default]
+    BuiltIn,
+    /// A pool of `php-cgi` workers behind the plugin's HTTP front end
+    PhpCgi,
+    /// A `php-fpm` master from the installed PHP build
+    PhpFpm,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FastCgiOptions {
+    pub workers: usize,
+    /// Workers are restarted after this many requests to contain memory leaks
+    pub max_requests_per_worker: u32,
+    /// Seconds a request may wait for a worker and its response before the
+    /// front end answers 504
+    pub request_timeout_secs: u64,
+}
+
+impl Default for FastCgiOptions {
+    fn default() -> Self {
+        Self {
+            workers: 4,
+            max_requests_per_worker: 500,
+            request_timeout_secs: 60,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct CloneProjectRequest {
     pub git_url: String,
     pub destination: Option<String>,
     pub branch: Option<String>,
     pub auto_setup: bool,
+    /// Keys progress events and `cancel_clone`; generated when omitted
+    #[serde(default)]
+    pub clone_id: Option<String>,
+    /// How to authenticate; the SSH agent and git credential helper are tried when omitted
+    #[serde(default)]
+    pub credentials: Option<GitCredentials>,
+    /// Check out this tag (detached) instead of a branch
+    #[serde(default)]
+    pub tag: Option<String>,
+    /// Check out this commit (detached); it must be reachable from the fetched history
+    #[serde(default)]
+    pub commit: Option<String>,
+    /// Fetch only the last `depth` commits; ignored for local repositories
+    #[serde(default)]
+    pub depth: Option<u32>,
+    /// Fetch only the requested branch or tag instead of every branch
+    #[serde(default)]
+    pub single_branch: bool,
+    /// Initialise and check out submodules, recursively
+    #[serde(default)]
+    pub recurse_submodules: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CreateProjectRequest {
+    /// Folder name inside the projects directory unless `destination` is given
+    pub name: String,
+    pub framework: Framework,
+    #[serde(default)]
+    pub destination: Option<String>,
+    /// Composer version constraint, or the tag checked out from a git template
+    #[serde(default)]
+    pub version: Option<String>,
+    /// Git repository to use instead of the framework's template
+    #[serde(default)]
+    pub template_url: Option<String>,
+    #[serde(default)]
+    pub auto_setup: bool,
+}
+
+/// Where `create_project` can get a framework's starter project from, tried
+/// in this order: Composer `create-project`, the git repository, then the
+/// skeleton bundled with the plugin.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ProjectTemplate {
+    pub framework: Framework,
+    pub composer_package: Option<String>,
+    pub git_url: Option<String>,
+    pub bundled: bool,
+}
+
+/// Authentication for clone and fetch. Passphrases and tokens are never sent
+/// with a request; they are referenced by the id they were saved under with
+/// `set_secret`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type")]
+pub enum GitCredentials {
+    /// Keys loaded into the running ssh-agent (Pageant on Windows)
+    SshAgent {
+        #[serde(default)]
+        username: Option<String>,
+    },
+    /// A private key file, optionally protected by a stored passphrase
+    SshKey {
+        #[serde(default)]
+        username: Option<String>,
+        private_key_path: String,
+        #[serde(default)]
+        public_key_path: Option<String>,
+        #[serde(default)]
+        passphrase_secret_id: Option<String>,
+    },
+    /// HTTPS username and password or personal access token
+    Token {
+        #[serde(default)]
+        username: Option<String>,
+        token_secret_id: String,
+    },
+    /// Whatever `credential.helper` the user configured for git
+    CredentialHelper,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum CloneStage {
+    #[default]
+    Receiving,
+    Resolving,
+    CheckingOut,
+    Submodules,
+    Done,
+}
+
+/// Payload of the `php://clone-progress` event.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CloneProgress {
+    pub clone_id: String,
+    pub stage: CloneStage,
+    pub received_objects: usize,
+    pub indexed_objects: usize,
+    pub total_objects: usize,
+    pub local_objects: usize,
+    pub received_bytes: usize,
+    pub indexed_deltas: usize,
+    pub total_deltas: usize,
+    pub checkout_completed: usize,
+    pub checkout_total: usize,
+    pub submodules_completed: usize,
+    pub submodules_total: usize,
+}
+
+/// Working tree state of a project's git repository.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GitStatus {
+    /// Current branch, None when HEAD is detached or unborn
+    pub branch: Option<String>,
+    pub head_commit: Option<String>,
+    pub detached: bool,
+    /// Upstream of the current branch, e.g. `origin/main`
+    pub upstream: Option<String>,
+    pub remote_url: Option<String>,
+    /// Commits on the branch that are not on its upstream
+    pub ahead: usize,
+    /// Commits on the upstream that are not on the branch
+    pub behind: usize,
+    /// True when any file is modified, staged, untracked or conflicted
+    pub is_dirty: bool,
+    pub files: Vec<GitFileStatus>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct GitFileStatus {
+    pub path: String,
+    /// Change staged in the index
+    pub index: Option<GitChange>,
+    /// Change in the working tree that is not staged
+    pub worktree: Option<GitChange>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum GitChange {
+    Added,
+    Modified,
+    Deleted,
+    Renamed,
+    TypeChange,
+    Untracked,
+    Conflicted,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitPullResult {
+    /// False when the branch was already up to date
+    pub updated: bool,
+    pub previous_commit: Option<String>,
+    pub current_commit: Option<String>,
+    pub status: GitStatus,
 }
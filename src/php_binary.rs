@@ -1,10 +1,13 @@
-use crate::models::PhpBinaryInfo;
+use crate::models::{Config, ExtensionReport, PhpBinaryInfo, RequiredExtension};
 use crate::{Error, Result};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
+#[derive(Debug, Clone)]
 pub struct PhpBinaryManager {
     php_dir: PathBuf,
+    download_base_url: String,
+    default_version: Option<String>,
 }
 
 impl Default for PhpBinaryManager {
@@ -15,9 +18,50 @@ impl Default for PhpBinaryManager {
 
 impl PhpBinaryManager {
     pub fn new() -> Self {
-        let php_dir = Self::get_php_directory();
+        Self::with_config(&Config::default())
+    }
+
+    pub fn with_config(config: &Config) -> Self {
+        let mut manager = Self {
+            php_dir: Self::get_php_directory(),
+            download_base_url: String::new(),
+            default_version: None,
+        };
+        manager.apply_config(config);
+        manager
+    }
+
+    pub fn apply_config(&mut self, config: &Config) {
+        self.download_base_url = config.download_base_url.trim_end_matches('/').to_string();
+        self.default_version = config.default_php_version.clone();
+    }
+
+    pub fn set_php_directory(&mut self, path: PathBuf) {
+        self.php_dir = path;
+    }
+
+    /// Path of an installed PHP for `version`: the exact version, or else the
+    /// newest installed patch release of the same minor version.
+    pub fn resolve_php_executable(&self, version: &str) -> Option<PathBuf> {
+        let exact = self.get_php_executable_path(version);
+        if exact.is_file() {
+            return Some(exact);
+        }
+
+        let minor = |v: &str| v.split('.').take(2).collect::<Vec<_>>().join(".");
+        let mut candidates: Vec<PhpBinaryInfo> = self
+            .list_installed_versions()
+            .ok()?
+            .into_iter()
+            .filter(|info| info.is_downloaded && minor(&info.version) == minor(version))
+            .collect();
+        candidates.sort_by(|a, b| crate::composer::compare_versions(&b.version, &a.version));
+        candidates.first().map(|info| PathBuf::from(&info.path))
+    }
 
-        Self { php_dir }
+    /// Version used when a request does not name one.
+    pub fn default_version(&self) -> &str {
+        self.default_version.as_deref().unwrap_or("8.3.0")
     }
 
     fn get_php_directory() -> PathBuf {
@@ -63,7 +107,7 @@ impl PhpBinaryManager {
         })
     }
 
-    fn get_download_url(&self, version: &str) -> Result<String> {
+    pub fn get_download_url(&self, version: &str) -> Result<String> {
         let os = std::env::consts::OS;
         let arch = std::env::consts::ARCH;
 
@@ -85,10 +129,7 @@ impl PhpBinaryManager {
 
         // NativePHP binary naming convention
         let filename = format!("php-{}-{}-{}.{}", version, os_name, arch_name, extension);
-        let url = format!(
-            "https://github.com/NativePHP/php-bin/releases/download/v{}/{}",
-            version, filename
-        );
+        let url = format!("{}/v{}/{}", self.download_base_url, version, filename);
 
         Ok(url)
     }
@@ -170,6 +211,14 @@ impl PhpBinaryManager {
         }
     }
 
+    /// Lists the extensions loaded by an installed PHP version.
+    pub async fn get_loaded_extensions(&self, version: &str) -> Result<Vec<String>> {
+        let php_executable = self
+            .resolve_php_executable(version)
+            .ok_or(Error::PhpBinaryNotFound)?;
+        loaded_extensions(&php_executable).await
+    }
+
     pub fn remove_php_binary(&self, version: &str) -> Result<()> {
         let version_dir = self.get_version_directory(version);
         if version_dir.exists() {
@@ -178,3 +227,69 @@ impl PhpBinaryManager {
         Ok(())
     }
 }
+
+/// Runs `php -m` and returns the loaded extensions, named the way Composer's
+/// `ext-*` requirements name them.
+pub async fn loaded_extensions(php_executable: &Path) -> Result<Vec<String>> {
+    let output = tokio::process::Command::new(php_executable)
+        .arg("-m")
+        .output()
+        .await
+        .map_err(|e| {
+            Error::Process(format!(
+                "Failed to run {} -m: {}",
+                php_executable.display(),
+                e
+            ))
+        })?;
+
+    if !output.status.success() {
+        return Err(Error::Process(format!(
+            "Failed to list PHP extensions: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(parse_module_list(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Checks `required` extensions against those loaded by `php_executable`.
+pub async fn check_extensions(
+    php_executable: &Path,
+    required: Vec<RequiredExtension>,
+) -> Result<ExtensionReport> {
+    let loaded = loaded_extensions(php_executable).await?;
+    let missing = required
+        .iter()
+        .filter(|extension| !loaded.contains(&extension.name))
+        .cloned()
+        .collect();
+    Ok(ExtensionReport {
+        php_executable: php_executable.to_string_lossy().to_string(),
+        loaded,
+        required,
+        missing,
+    })
+}
+
+/// Parses the output of `php -m`, skipping the `[PHP Modules]` and
+/// `[Zend Modules]` headings. Names are lowercased with spaces turned into
+/// dashes, so `Zend OPcache` becomes `zend-opcache`.
+pub fn parse_module_list(output: &str) -> Vec<String> {
+    let mut extensions: Vec<String> = output
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('['))
+        .map(normalize_extension_name)
+        .collect();
+    extensions.sort();
+    extensions.dedup();
+    extensions
+}
+
+/// Normalizes an extension name as Composer does, dropping any `ext-` prefix.
+pub fn normalize_extension_name(name: &str) -> String {
+    let name = name.trim().to_lowercase().replace(' ', "-");
+    name.strip_prefix("ext-")
+        .map(str::to_string)
+        .unwrap_or(name)
+}
@@ -1,24 +1,69 @@
-use crate::models::{ServerStatus, StartServerRequest};
+use crate::fastcgi_server::{FastCgiServer, FastCgiServerConfig};
+use crate::models::{ServerBackend, ServerConfig, ServerStatus, StartServerRequest};
+use crate::proxy::HttpsProxy;
+use crate::tls::{self, LocalCertificateAuthority};
 use crate::{Error, Result};
 use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::ops::RangeInclusive;
 use std::path::{Path, PathBuf};
 use std::process::Stdio;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::process::{Child, Command};
 
+/// Ports searched when a server is started without an explicit port; the
+/// range moves along with `ServerConfig::default_port`.
+pub const DEFAULT_PORT_RANGE: RangeInclusive<u16> = 8000..=8999;
+
+/// How long a port handed out by `reserve_port` is held back from other
+/// callers before it is considered free again.
+const PORT_RESERVATION_TTL: Duration = Duration::from_secs(30);
+
 pub struct PhpServerManager {
     servers: HashMap<String, ServerInstance>,
+    reserved_ports: HashMap<u16, Instant>,
+    certs_dir: PathBuf,
+    certificate_authority: Option<Arc<LocalCertificateAuthority>>,
+    server_config: ServerConfig,
 }
 
 struct ServerInstance {
     #[allow(dead_code)]
     id: String,
-    child: Child,
+    process: ServerProcess,
     port: u16,
+    /// Port `php -S` or the FastCGI front end listens on; differs from `port`
+    /// when an HTTPS proxy fronts the server
+    backend_port: u16,
     host: String,
+    project_path: String,
     document_root: String,
     started_at: String,
     #[allow(dead_code)]
     php_version: String,
+    https_proxy: Option<HttpsProxy>,
+}
+
+enum ServerProcess {
+    BuiltIn(Child),
+    FastCgi(FastCgiServer),
+}
+
+impl ServerProcess {
+    fn pid(&self) -> Option<u32> {
+        match self {
+            ServerProcess::BuiltIn(child) => child.id(),
+            ServerProcess::FastCgi(server) => server.pid(),
+        }
+    }
+
+    fn backend(&self) -> ServerBackend {
+        match self {
+            ServerProcess::BuiltIn(_) => ServerBackend::BuiltIn,
+            ServerProcess::FastCgi(server) => server.backend(),
+        }
+    }
 }
 
 impl Default for PhpServerManager {
@@ -29,9 +74,56 @@ impl Default for PhpServerManager {
 
 impl PhpServerManager {
     pub fn new() -> Self {
+        Self::with_config(ServerConfig::default())
+    }
+
+    pub fn with_config(server_config: ServerConfig) -> Self {
         Self {
             servers: HashMap::new(),
+            reserved_ports: HashMap::new(),
+            certs_dir: Self::get_certs_directory(),
+            certificate_authority: None,
+            server_config,
+        }
+    }
+
+    /// Defaults for servers started from now on; running servers keep theirs.
+    pub fn set_server_config(&mut self, server_config: ServerConfig) {
+        self.server_config = server_config;
+    }
+
+    pub fn server_config(&self) -> &ServerConfig {
+        &self.server_config
+    }
+
+    /// Ports tried for servers started without one, from the configured default port.
+    pub fn default_port_range(&self) -> RangeInclusive<u16> {
+        let start = self.server_config.default_port;
+        let span = DEFAULT_PORT_RANGE.end() - DEFAULT_PORT_RANGE.start();
+        start..=start.saturating_add(span)
+    }
+
+    fn get_certs_directory() -> PathBuf {
+        let mut dir = dirs::data_dir().unwrap_or_else(|| PathBuf::from("."));
+        dir.push("tauri-php-plugin");
+        dir.push("certs");
+        dir
+    }
+
+    pub fn set_certificates_directory(&mut self, path: PathBuf) {
+        self.certs_dir = path;
+        self.certificate_authority = None;
+    }
+
+    /// Returns the local CA used for HTTPS servers, creating it on first use.
+    pub fn certificate_authority(&mut self) -> Result<Arc<LocalCertificateAuthority>> {
+        if let Some(authority) = &self.certificate_authority {
+            return Ok(authority.clone());
         }
+
+        let authority = Arc::new(LocalCertificateAuthority::load_or_create(&self.certs_dir)?);
+        self.certificate_authority = Some(authority.clone());
+        Ok(authority)
     }
 
     pub async fn start_server(
@@ -40,11 +132,24 @@ impl PhpServerManager {
         php_executable_path: PathBuf,
     ) -> Result<String> {
         let server_id = uuid::Uuid::new_v4().to_string();
-        let port = request.port.unwrap_or(8000);
-        let host = request.host.unwrap_or_else(|| "127.0.0.1".to_string());
-        let document_root = request
-            .document_root
-            .unwrap_or(request.project_path.clone());
+        let port = match request.port {
+            Some(port) => port,
+            None => self.reserve_port(self.default_port_range(), &[])?,
+        };
+        // The server is about to own this port, so any reservation has served its purpose
+        self.release_port(port);
+        let https = request.https;
+        let host = request
+            .host
+            .unwrap_or_else(|| self.server_config.default_host.clone());
+        let document_root = match (&request.document_root, &self.server_config.document_root) {
+            (Some(document_root), _) => document_root.clone(),
+            (None, Some(document_root)) => Path::new(&request.project_path)
+                .join(document_root)
+                .to_string_lossy()
+                .to_string(),
+            (None, None) => request.project_path.clone(),
+        };
 
         // Check if port is available
         if self.is_port_in_use(port) {
@@ -60,32 +165,71 @@ impl PhpServerManager {
             )));
         }
 
-        // Build PHP server command
-        let mut cmd = Command::new(&php_executable_path);
-        cmd.arg("-S")
-            .arg(format!("{}:{}", host, port))
-            .arg("-t")
-            .arg(&document_root)
-            .current_dir(&request.project_path)
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped());
-
-        // Add router script if it exists (for frameworks like Laravel)
+        // With HTTPS the proxy owns the public port and php -S only listens on loopback
+        let (backend_host, backend_port) = if https {
+            let backend_port = self.reserve_port(self.default_port_range(), &[port])?;
+            self.release_port(backend_port);
+            ("127.0.0.1".to_string(), backend_port)
+        } else {
+            (host.clone(), port)
+        };
+
+        // Router script if it exists (for frameworks like Laravel)
         let router_script = self.detect_router_script(&doc_root_path)?;
-        if let Some(router) = router_script {
-            cmd.arg(router);
-        }
 
-        // Start the server process
-        let child = cmd
-            .spawn()
-            .map_err(|e| Error::PhpServer(format!("Failed to start PHP server: {}", e)))?;
+        let process = match request.backend {
+            ServerBackend::BuiltIn => {
+                // Build PHP server command
+                let mut cmd = Command::new(&php_executable_path);
+                cmd.arg("-S")
+                    .arg(format!("{}:{}", backend_host, backend_port))
+                    .arg("-t")
+                    .arg(&document_root)
+                    .envs(&request.env)
+                    .current_dir(&request.project_path)
+                    .stdout(Stdio::piped())
+                    .stderr(Stdio::piped());
+
+                if let Some(router) = router_script {
+                    cmd.arg(router);
+                }
+
+                // Start the server process
+                let child = cmd
+                    .spawn()
+                    .map_err(|e| Error::PhpServer(format!("Failed to start PHP server: {}", e)))?;
+                ServerProcess::BuiltIn(child)
+            }
+            backend => {
+                let listen_addr = tokio::net::lookup_host((backend_host.as_str(), backend_port))
+                    .await?
+                    .next()
+                    .ok_or_else(|| {
+                        Error::PhpServer(format!("Cannot resolve host: {}", backend_host))
+                    })?;
+
+                let server = FastCgiServer::start(FastCgiServerConfig {
+                    backend,
+                    php_executable: php_executable_path.clone(),
+                    listen_addr,
+                    project_path: PathBuf::from(&request.project_path),
+                    document_root: doc_root_path.clone(),
+                    router_script,
+                    options: request.fastcgi.clone().unwrap_or_default(),
+                    env: request.env.clone(),
+                })
+                .await?;
+                ServerProcess::FastCgi(server)
+            }
+        };
 
         let server_instance = ServerInstance {
             id: server_id.clone(),
-            child,
+            process,
             port,
+            backend_port,
             host: host.clone(),
+            project_path: request.project_path.clone(),
             document_root: document_root.clone(),
             started_at: std::time::SystemTime::now()
                 .duration_since(std::time::UNIX_EPOCH)
@@ -93,6 +237,7 @@ impl PhpServerManager {
                 .as_secs()
                 .to_string(),
             php_version: request.php_version.unwrap_or_else(|| "unknown".to_string()),
+            https_proxy: None,
         };
 
         self.servers.insert(server_id.clone(), server_instance);
@@ -106,25 +251,69 @@ impl PhpServerManager {
             return Err(Error::PhpServer("Failed to start PHP server".to_string()));
         }
 
+        if https {
+            if let Err(e) = self
+                .start_https_proxy(&server_id, &host, port, backend_port)
+                .await
+            {
+                let _ = self.stop_server(&server_id).await;
+                return Err(e);
+            }
+        }
+
         log::info!(
-            "PHP server started on {}:{} with document root: {}",
+            "PHP server started on {}://{}:{} ({:?}) with document root: {}",
+            if https { "https" } else { "http" },
             host,
             port,
+            request.backend,
             document_root
         );
         Ok(server_id)
     }
 
-    pub async fn stop_server(&mut self, server_id: &str) -> Result<()> {
-        if let Some(mut server) = self.servers.remove(server_id) {
-            // Try to kill the process gracefully
-            if let Err(e) = server.child.kill().await {
-                log::warn!("Failed to kill PHP server process: {}", e);
-            }
+    async fn start_https_proxy(
+        &mut self,
+        server_id: &str,
+        host: &str,
+        port: u16,
+        backend_port: u16,
+    ) -> Result<()> {
+        // Wildcard listen addresses are not valid certificate names
+        let certificate_host = match host {
+            "0.0.0.0" | "::" => "localhost",
+            _ => host,
+        };
+        let tls_config = tls::server_config(self.certificate_authority()?, certificate_host)?;
+
+        let listen_addr = tokio::net::lookup_host((host, port))
+            .await?
+            .next()
+            .ok_or_else(|| Error::PhpServer(format!("Cannot resolve host: {}", host)))?;
+        let upstream = SocketAddr::from(([127, 0, 0, 1], backend_port));
 
-            // Wait for the process to exit
-            if let Err(e) = server.child.wait().await {
-                log::warn!("Failed to wait for PHP server process: {}", e);
+        let proxy = HttpsProxy::start(listen_addr, upstream, tls_config).await?;
+        if let Some(server) = self.servers.get_mut(server_id) {
+            server.https_proxy = Some(proxy);
+        }
+        Ok(())
+    }
+
+    pub async fn stop_server(&mut self, server_id: &str) -> Result<()> {
+        if let Some(server) = self.servers.remove(server_id) {
+            match server.process {
+                ServerProcess::BuiltIn(mut child) => {
+                    // Try to kill the process gracefully
+                    if let Err(e) = child.kill().await {
+                        log::warn!("Failed to kill PHP server process: {}", e);
+                    }
+
+                    // Wait for the process to exit
+                    if let Err(e) = child.wait().await {
+                        log::warn!("Failed to wait for PHP server process: {}", e);
+                    }
+                }
+                ServerProcess::FastCgi(mut fastcgi_server) => fastcgi_server.shutdown().await,
             }
 
             log::info!("PHP server {} stopped", server_id);
@@ -138,9 +327,14 @@ impl PhpServerManager {
         let server_ids: Vec<String> = self.servers.keys().cloned().collect();
 
         for server_id in server_ids {
-            if let Some(mut server) = self.servers.remove(&server_id) {
+            if let Some(server) = self.servers.remove(&server_id) {
                 // Kill the process (non-async version)
-                let _ = server.child.start_kill();
+                match server.process {
+                    ServerProcess::BuiltIn(mut child) => {
+                        let _ = child.start_kill();
+                    }
+                    ServerProcess::FastCgi(mut fastcgi_server) => fastcgi_server.start_shutdown(),
+                }
             }
         }
 
@@ -152,18 +346,17 @@ impl PhpServerManager {
         if let Some(server) = self.servers.get(server_id) {
             // 文件内容无需更改，建议清理 target 目录后重新编译。
             // 直接在此处判断进程是否存活，避免可变借用
-            let is_running = if let Some(pid) = server.child.id() {
-                self.is_process_running(pid)
-            } else {
-                false
-            };
+            let is_running = self.is_instance_running(server);
             Ok(ServerStatus {
                 is_running,
-                pid: server.child.id(),
+                pid: server.process.pid(),
                 port: Some(server.port),
                 host: Some(server.host.clone()),
                 document_root: Some(server.document_root.clone()),
                 started_at: Some(server.started_at.clone()),
+                https: server.https_proxy.is_some(),
+                project_path: Some(server.project_path.clone()),
+                backend: server.process.backend(),
             })
         } else {
             Ok(ServerStatus {
@@ -173,6 +366,9 @@ impl PhpServerManager {
                 host: None,
                 document_root: None,
                 started_at: None,
+                https: false,
+                project_path: None,
+                backend: ServerBackend::BuiltIn,
             })
         }
     }
@@ -181,37 +377,80 @@ impl PhpServerManager {
         let mut result = Vec::new();
         for (id, server) in &self.servers {
             // 直接在此处判断进程是否存活，避免可变借用
-            let is_running = if let Some(pid) = server.child.id() {
-                self.is_process_running(pid)
-            } else {
-                false
-            };
+            let is_running = self.is_instance_running(server);
             let status = ServerStatus {
                 is_running,
-                pid: server.child.id(),
+                pid: server.process.pid(),
                 port: Some(server.port),
                 host: Some(server.host.clone()),
                 document_root: Some(server.document_root.clone()),
                 started_at: Some(server.started_at.clone()),
+                https: server.https_proxy.is_some(),
+                project_path: Some(server.project_path.clone()),
+                backend: server.process.backend(),
             };
             result.push((id.clone(), status));
         }
         result
     }
 
+    /// Ids of the servers whose project or document root lies inside `path`.
+    pub fn servers_for_project(&self, path: &Path) -> Vec<String> {
+        let path = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        let inside = |dir: &str| {
+            std::fs::canonicalize(dir)
+                .unwrap_or_else(|_| PathBuf::from(dir))
+                .starts_with(&path)
+        };
+        self.servers
+            .iter()
+            .filter(|(_, server)| inside(&server.project_path) || inside(&server.document_root))
+            .map(|(id, _)| id.clone())
+            .collect()
+    }
+
+    /// Returns the plain HTTP address of the server started for the project whose
+    /// directory is named `project_name`, bypassing any HTTPS proxy in front of it.
+    pub fn find_project_backend(&self, project_name: &str) -> Option<SocketAddr> {
+        self.servers
+            .values()
+            .find(|server| {
+                Path::new(&server.project_path)
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|name| name.eq_ignore_ascii_case(project_name))
+            })
+            .map(|server| {
+                let backend_host = if server.https_proxy.is_some() || server.host == "0.0.0.0" {
+                    "127.0.0.1"
+                } else {
+                    server.host.as_str()
+                };
+                let ip = backend_host
+                    .parse()
+                    .unwrap_or(std::net::IpAddr::from([127, 0, 0, 1]));
+                SocketAddr::new(ip, server.backend_port)
+            })
+    }
+
     fn is_server_running(&mut self, server_id: &str) -> bool {
-        if let Some(server) = self.servers.get_mut(server_id) {
-            // Check if the process is still alive using PID
-            if let Some(pid) = server.child.id() {
-                self.is_process_running(pid)
-            } else {
-                false
-            }
+        if let Some(server) = self.servers.get(server_id) {
+            self.is_instance_running(server)
         } else {
             false
         }
     }
 
+    fn is_instance_running(&self, server: &ServerInstance) -> bool {
+        match &server.process {
+            // Check if the process is still alive using PID
+            ServerProcess::BuiltIn(child) => {
+                child.id().is_some_and(|pid| self.is_process_running(pid))
+            }
+            ServerProcess::FastCgi(fastcgi_server) => fastcgi_server.is_running(),
+        }
+    }
+
     fn is_process_running(&self, pid: u32) -> bool {
         // On Unix systems, we can check if a process is running by sending signal 0
         #[cfg(unix)]
@@ -275,37 +514,45 @@ impl PhpServerManager {
             Ok(format!(
                 "Logs for server {} (PID: {:?})",
                 server_id,
-                server.child.id()
+                server.process.pid()
             ))
         } else {
             Err(Error::PhpServer(format!("Server {} not found", server_id)))
         }
     }
 
-    pub fn find_available_port(&self, start_port: u16) -> u16 {
-        let mut port = start_port;
-        let max_attempts = 65535 - start_port + 1;
-        let mut attempts = 0;
+    /// Finds a free port in `range`, skipping `exclude`, ports used by managed
+    /// servers and ports currently reserved. Each port is tried at most once.
+    pub fn find_available_port(&self, range: RangeInclusive<u16>, exclude: &[u16]) -> Result<u16> {
+        range
+            .clone()
+            .filter(|port| !exclude.contains(port) && !self.is_port_reserved(*port))
+            .find(|port| !self.is_port_in_use(*port))
+            .ok_or_else(|| Error::NoAvailablePort(*range.start(), *range.end()))
+    }
 
-        while self.is_port_in_use(port) || self.is_port_system_in_use(port) {
-            attempts += 1;
-            if attempts >= max_attempts {
-                // If we've tried all ports from start_port to 65535, panic
-                panic!("No available port found in range {}..65535", start_port);
-            }
+    /// Finds a free port like `find_available_port` and reserves it, so that
+    /// concurrent callers are not handed the same port before a server binds it.
+    /// The reservation is dropped when a server starts on the port, when it is
+    /// released, or after `PORT_RESERVATION_TTL`.
+    pub fn reserve_port(&mut self, range: RangeInclusive<u16>, exclude: &[u16]) -> Result<u16> {
+        let now = Instant::now();
+        self.reserved_ports
+            .retain(|_, reserved_at| now.duration_since(*reserved_at) < PORT_RESERVATION_TTL);
+
+        let port = self.find_available_port(range, exclude)?;
+        self.reserved_ports.insert(port, now);
+        Ok(port)
+    }
 
-            port = port.saturating_add(1);
-            if port == 65535 && attempts < max_attempts {
-                // If we reach the maximum port, wrap around to a reasonable starting point
-                port = 8000;
-            }
-        }
-        port
+    pub fn release_port(&mut self, port: u16) {
+        self.reserved_ports.remove(&port);
     }
 
-    fn is_port_system_in_use(&self, port: u16) -> bool {
-        // Try to bind to the port to check if it's available
-        std::net::TcpListener::bind(("127.0.0.1", port)).is_err()
+    pub fn is_port_reserved(&self, port: u16) -> bool {
+        self.reserved_ports
+            .get(&port)
+            .is_some_and(|reserved_at| reserved_at.elapsed() < PORT_RESERVATION_TTL)
     }
 }
 
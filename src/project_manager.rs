@@ -1,13 +1,42 @@
-use crate::framework_detector::FrameworkDetector;
-use crate::models::{CloneProjectRequest, Framework, ProjectInfo};
+use crate::composer::{self, ComposerLauncher, ComposerManager, ComposerRun, OutputCallback};
+use crate::composer_manifest::{self, ComposerJson, ComposerLock};
+use crate::env_file;
+use crate::framework_command::{self, CommandCache, FrameworkCommand};
+use crate::framework_detector::{self, FrameworkDetector};
+use crate::git;
+use crate::models::{
+    CloneProjectRequest, ComposerAction, ComposerRequest, ComposerResult, Config,
+    ConsoleCommandList, CreateProjectRequest, Framework, FrameworkCandidate,
+    FrameworkCommandRequest, FrameworkVersion, GitCredentials, GitPullResult, GitStatus,
+    LaravelSetupOptions, ProjectInfo, ProjectSettings, RequiredExtension, SetupProjectRequest,
+    SetupReport, SetupStep, SetupStepStatus, StartServerRequest,
+};
+use crate::php_binary::{self, PhpBinaryManager};
+use crate::project_registry::{ProjectRegistry, RegisteredProject};
+use crate::project_templates;
+use crate::project_trash::{ProjectTrash, TrashedProject};
+use crate::secret_store::SharedSecretStore;
+use crate::setup_pipeline::{self, PipelineStep, SetupAction, SetupPipeline};
 use crate::{Error, Result};
+use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
-use url::Url;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
 
+/// Clones share the registry, trash and command cache, so a command can take
+/// a snapshot under the `PhpManager` lock and run a long operation such as a
+/// clone or setup without holding it.
+#[derive(Clone)]
 pub struct ProjectManager {
     projects_dir: PathBuf,
     framework_detector: FrameworkDetector,
+    secret_store: SharedSecretStore,
+    registry: Arc<ProjectRegistry>,
+    trash: Arc<ProjectTrash>,
+    php_binaries: PhpBinaryManager,
+    composer: ComposerManager,
+    console_commands: Arc<CommandCache>,
 }
 
 impl Default for ProjectManager {
@@ -18,25 +47,427 @@ impl Default for ProjectManager {
 
 impl ProjectManager {
     pub fn new() -> Self {
-        let projects_dir = dirs::home_dir()
-            .unwrap_or_else(|| PathBuf::from("."))
-            .join("tauri-php-projects");
-
         Self {
-            projects_dir,
+            projects_dir: Self::default_projects_directory(),
             framework_detector: FrameworkDetector::new(),
+            secret_store: SharedSecretStore::default(),
+            registry: Arc::new(ProjectRegistry::new()),
+            trash: Arc::new(ProjectTrash::new()),
+            php_binaries: PhpBinaryManager::new(),
+            composer: ComposerManager::new(),
+            console_commands: Arc::new(CommandCache::new()),
         }
     }
 
+    pub fn with_config(config: &Config) -> Self {
+        let mut manager = Self::new();
+        manager.apply_config(config);
+        manager
+    }
+
+    fn default_projects_directory() -> PathBuf {
+        dirs::home_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("tauri-php-projects")
+    }
+
     pub fn set_projects_directory(&mut self, path: PathBuf) {
         self.projects_dir = path;
     }
 
+    pub fn projects_directory(&self) -> &Path {
+        &self.projects_dir
+    }
+
+    /// Switches to the configured projects directory, or back to the default one.
+    pub fn apply_config(&mut self, config: &Config) {
+        self.projects_dir = crate::config::projects_directory(config)
+            .unwrap_or_else(Self::default_projects_directory);
+        self.php_binaries.apply_config(config);
+    }
+
+    /// Where the PHP binaries that run Composer are looked up.
+    pub fn set_php_binaries(&mut self, php_binaries: PhpBinaryManager) {
+        self.php_binaries = php_binaries;
+    }
+
+    pub fn set_composer(&mut self, composer: ComposerManager) {
+        self.composer = composer;
+    }
+
+    /// Resolves the PHP binary for running a project's tools: the project's
+    /// configured PHP version, then its `composer.json` requirement, then the
+    /// default version. Without a project only the default version is considered.
+    pub fn project_php_executable(&self, project_path: Option<&Path>) -> Result<PathBuf> {
+        let mut versions = Vec::new();
+        if let Some(project_path) = project_path {
+            if let Some(version) = self
+                .find_registered_project(project_path)
+                .and_then(|project| project.settings.php_version)
+            {
+                versions.push(version);
+            }
+            if let Ok(Some(version)) = self.detect_php_version_requirement(project_path) {
+                versions.push(version);
+            }
+        }
+        versions.push(self.php_binaries.default_version().to_string());
+
+        versions
+            .iter()
+            .find_map(|version| self.php_binaries.resolve_php_executable(version))
+            .ok_or(Error::PhpBinaryNotFound)
+    }
+
+    /// Resolves the managed `composer.phar` and the PHP binary to run it with,
+    /// chosen as in [`Self::project_php_executable`].
+    pub fn composer_launcher(&self, project_path: Option<&Path>) -> Result<ComposerLauncher> {
+        let composer_phar = self.composer.default_composer().ok_or_else(|| {
+            Error::Config("Composer is not installed; download it first".to_string())
+        })?;
+        Ok(ComposerLauncher {
+            php_executable: self.project_php_executable(project_path)?,
+            composer_phar,
+        })
+    }
+
+    /// Runs a Composer action in a project with the project's PHP, streaming
+    /// its output to `on_output`.
+    pub async fn run_composer(
+        &self,
+        request: ComposerRequest,
+        on_output: Option<OutputCallback>,
+    ) -> Result<ComposerResult> {
+        self.composer_run(&request)?.run(on_output).await
+    }
+
+    /// Resolves the arguments, PHP binary and `composer.phar` for `request`
+    /// from the project's settings.
+    pub fn composer_run(&self, request: &ComposerRequest) -> Result<ComposerRun> {
+        let project_path = PathBuf::from(&request.project_path);
+        if !project_path.join("composer.json").is_file() {
+            return Err(Error::Config(format!(
+                "composer.json not found in {}",
+                project_path.display()
+            )));
+        }
+
+        let settings = self
+            .find_registered_project(&project_path)
+            .map(|project| project.settings.composer)
+            .unwrap_or_default();
+        Ok(ComposerRun {
+            args: composer::composer_arguments(request, &settings)?,
+            launcher: self.composer_launcher(Some(&project_path))?,
+            project_path,
+            action: request.action,
+            run_id: request
+                .run_id
+                .clone()
+                .unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+        })
+    }
+
+    /// Resolves the console script of the project's framework and the PHP
+    /// binary and environment to run `request` with.
+    pub fn framework_command(&self, request: &FrameworkCommandRequest) -> Result<FrameworkCommand> {
+        let project_path = PathBuf::from(&request.project_path);
+        if !project_path.is_dir() {
+            return Err(Error::Config(format!(
+                "Project directory not found: {}",
+                project_path.display()
+            )));
+        }
+
+        let framework = self.framework_detector.detect_framework(&project_path)?;
+        let version = self.detect_framework_version(&project_path, &framework);
+        let script =
+            framework_detector::console_script(&framework, version.as_ref()).ok_or_else(|| {
+                Error::Config(format!("{:?} projects have no console script", framework))
+            })?;
+        if !project_path.join(script).is_file() {
+            return Err(Error::Config(format!(
+                "{} not found in {}",
+                script,
+                project_path.display()
+            )));
+        }
+
+        let mut env = self
+            .find_registered_project(&project_path)
+            .map(|project| project.settings.env)
+            .unwrap_or_default();
+        env.extend(request.env.clone());
+
+        Ok(FrameworkCommand {
+            php_executable: self.project_php_executable(Some(&project_path))?,
+            project_path,
+            framework,
+            script: script.to_string(),
+            args: request.args.clone(),
+            env,
+        })
+    }
+
+    /// Lists the commands of the project's framework console. Lists are
+    /// cached per project until `composer.lock` changes; `refresh` runs the
+    /// console anyway, e.g. after a command was added to the application.
+    pub async fn list_framework_commands(
+        &self,
+        project_path: &Path,
+        refresh: bool,
+    ) -> Result<ConsoleCommandList> {
+        let command = self.framework_command(&FrameworkCommandRequest {
+            project_path: project_path.to_string_lossy().to_string(),
+            ..Default::default()
+        })?;
+        let framework = command.framework.clone();
+        let script = command.script.clone();
+        let lock_hash = ComposerLock::file_hash(project_path)?;
+
+        let cached = match &lock_hash {
+            Some(hash) if !refresh => self.console_commands.get(project_path, hash),
+            _ => None,
+        };
+        let (commands, cached) = match cached {
+            Some(commands) => (commands, true),
+            None => {
+                let commands = framework_command::list_commands(command).await?;
+                if let Some(hash) = &lock_hash {
+                    self.console_commands
+                        .insert(project_path, hash.clone(), commands.clone());
+                }
+                (commands, false)
+            }
+        };
+
+        Ok(ConsoleCommandList {
+            framework,
+            script,
+            commands,
+            lock_hash,
+            cached,
+        })
+    }
+
+    /// Runs Composer as a setup step; a missing Composer or PHP skips the
+    /// step and a failing run is reported rather than failing the setup.
+    async fn composer_step(&self, name: &str, request: ComposerRequest) -> SetupStep {
+        log::info!("Running composer {} for setup...", request.action.as_str());
+        match self.run_composer(request, None).await {
+            Ok(result) => {
+                let mut step = if result.success {
+                    SetupStep::succeeded(name, None)
+                } else {
+                    log::warn!("Composer failed: {}", result.output);
+                    SetupStep::failed(
+                        name,
+                        result
+                            .problems
+                            .first()
+                            .and_then(|problem| problem.messages.first().cloned())
+                            .unwrap_or_else(|| {
+                                format!("Composer exited with {:?}", result.exit_code)
+                            }),
+                    )
+                };
+                step.output = result.output;
+                step
+            }
+            Err(e) => {
+                log::warn!("Skipping Composer: {}", e);
+                SetupStep::skipped(name, e.to_string())
+            }
+        }
+    }
+
+    /// Replaces the project registry, e.g. with one stored elsewhere.
+    pub fn set_registry(&mut self, registry: ProjectRegistry) {
+        self.registry = Arc::new(registry);
+    }
+
+    /// Keeps removed projects somewhere other than the plugin's data directory.
+    pub fn set_trash(&mut self, trash: ProjectTrash) {
+        self.trash = Arc::new(trash);
+    }
+
+    /// Registers an existing directory as a project without moving it, so
+    /// `list_projects` reports it alongside the projects directory.
+    pub fn import_project(&self, path: &Path, name: Option<&str>) -> Result<ProjectInfo> {
+        if !path.is_dir() {
+            return Err(Error::Config(format!(
+                "Project directory not found: {}",
+                path.display()
+            )));
+        }
+        let path = fs::canonicalize(path)?;
+
+        let name = match name.map(str::trim) {
+            Some(name) if !name.is_empty() => name.to_string(),
+            Some(_) => return Err(Error::Config("Project name must not be empty".to_string())),
+            None => path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .map(str::to_string)
+                .ok_or_else(|| {
+                    Error::Config(format!(
+                        "Cannot derive a project name from {}",
+                        path.display()
+                    ))
+                })?,
+        };
+
+        let registered = self.registry.register(&name, &path)?;
+        log::info!(
+            "Imported project {} from {}",
+            registered.name,
+            registered.path
+        );
+        Ok(self.describe_project(&registered.name, &path, Some(&registered)))
+    }
+
+    fn describe_project(
+        &self,
+        name: &str,
+        path: &Path,
+        registered: Option<&RegisteredProject>,
+    ) -> ProjectInfo {
+        let settings = registered
+            .map(|project| project.settings.clone())
+            .unwrap_or_default();
+        let name = settings
+            .display_name
+            .clone()
+            // Only imports are named by the user; other entries follow the directory
+            .or_else(|| {
+                registered
+                    .filter(|project| project.imported)
+                    .map(|project| project.name.clone())
+            })
+            .unwrap_or_else(|| name.to_string());
+
+        let framework = self.framework_detector.detect_framework(path).ok();
+        ProjectInfo {
+            name,
+            path: path.to_string_lossy().to_string(),
+            framework_version: framework
+                .as_ref()
+                .and_then(|framework| self.detect_framework_version(path, framework)),
+            framework,
+            git_url: git::remote_url(path),
+            php_version: self.detect_php_version_requirement(path).ok().flatten(),
+            entry_point: None, // Could be detected based on framework
+            id: registered.map(|project| project.id.clone()),
+            setup: None,
+            settings,
+        }
+    }
+
+    fn registered_project(&self, project_id: &str) -> Result<RegisteredProject> {
+        self.registry
+            .find(project_id)
+            .ok_or_else(|| Error::Config(format!("Project not found: {}", project_id)))
+    }
+
+    /// Registry entry of the project rooted at `path`, if it has one.
+    pub fn find_registered_project(&self, path: &Path) -> Option<RegisteredProject> {
+        let path = fs::canonicalize(path).ok()?;
+        self.registry.find_by_path(&path)
+    }
+
+    pub fn get_project(&self, project_id: &str) -> Result<ProjectInfo> {
+        let registered = self.registered_project(project_id)?;
+        let path = PathBuf::from(&registered.path);
+        Ok(self.describe_project(&registered.name, &path, Some(&registered)))
+    }
+
+    pub fn get_project_settings(&self, project_id: &str) -> Result<ProjectSettings> {
+        Ok(self.registered_project(project_id)?.settings)
+    }
+
+    /// Replaces a project's settings after validating them.
+    pub fn update_project_settings(
+        &self,
+        project_id: &str,
+        mut settings: ProjectSettings,
+    ) -> Result<ProjectInfo> {
+        settings.display_name = settings
+            .display_name
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty());
+        if settings.port == Some(0) {
+            return Err(Error::Config("Port must not be 0".to_string()));
+        }
+        if let Some(key) = settings
+            .env
+            .keys()
+            .find(|key| key.is_empty() || key.contains('=') || key.contains('\0'))
+        {
+            return Err(Error::Config(format!(
+                "Invalid environment variable name: {:?}",
+                key
+            )));
+        }
+
+        let registered = self.registry.update_settings(project_id, settings)?;
+        let path = PathBuf::from(&registered.path);
+        Ok(self.describe_project(&registered.name, &path, Some(&registered)))
+    }
+
+    /// Fills the fields `request` leaves unset from the settings of its
+    /// project, found by `project_id` or else by `project_path`.
+    pub fn apply_project_settings(&self, request: &mut StartServerRequest) -> Result<()> {
+        let registered = match request.project_id.as_deref() {
+            Some(project_id) => Some(self.registered_project(project_id)?),
+            None => self.find_registered_project(Path::new(&request.project_path)),
+        };
+        let Some(registered) = registered else {
+            return Ok(());
+        };
+
+        if request.project_path.is_empty() {
+            request.project_path = registered.path.clone();
+        }
+        let settings = registered.settings;
+        if request.php_version.is_none() {
+            request.php_version = settings.php_version;
+        }
+        if request.port.is_none() {
+            request.port = settings.port;
+        }
+        if request.document_root.is_none() {
+            request.document_root = settings.document_root.map(|root| {
+                Path::new(&request.project_path)
+                    .join(root)
+                    .to_string_lossy()
+                    .to_string()
+            });
+        }
+        for (key, value) in settings.env {
+            request.env.entry(key).or_insert(value);
+        }
+        Ok(())
+    }
+
+    /// Sets where the secrets referenced by clone credentials are looked up.
+    pub fn set_secret_store(&mut self, store: SharedSecretStore) {
+        self.secret_store = store;
+    }
+
     pub async fn clone_project(&self, request: CloneProjectRequest) -> Result<ProjectInfo> {
-        // Validate Git URL
-        let _url = Url::parse(&request.git_url)?;
+        self.clone_project_with_progress(request, Arc::new(AtomicBool::new(false)), None)
+            .await
+    }
 
-        // Extract project name from URL
+    /// Clones like `clone_project`, reporting transfer and checkout progress to
+    /// `on_progress` and aborting once `cancel` is set.
+    pub async fn clone_project_with_progress(
+        &self,
+        request: CloneProjectRequest,
+        cancel: Arc<AtomicBool>,
+        on_progress: Option<git::ProgressCallback>,
+    ) -> Result<ProjectInfo> {
+        // Extract project name from URL or local path
         let project_name = self.extract_project_name(&request.git_url)?;
 
         // Determine destination path
@@ -46,126 +477,331 @@ impl ProjectManager {
             self.projects_dir.join(&project_name)
         };
 
+        // Missing secrets fail here, before anything is written
+        let credentials = self.resolve_credentials(request.credentials.as_ref())?;
+
         // Create projects directory if it doesn't exist
         if let Some(parent) = destination.parent() {
             fs::create_dir_all(parent)?;
         }
 
-        // Check if destination already exists
-        if destination.exists() {
+        // libgit2 blocks, so keep it off the async runtime
+        let git_url = request.git_url.clone();
+        let options = git::CloneOptions {
+            clone_id: request.clone_id.clone().unwrap_or_default(),
+            branch: request.branch.clone(),
+            tag: request.tag.clone(),
+            commit: request.commit.clone(),
+            depth: request.depth,
+            single_branch: request.single_branch,
+            recurse_submodules: request.recurse_submodules,
+            cancel: Some(cancel),
+            progress: on_progress,
+            credentials,
+        };
+        let clone_destination = destination.clone();
+        tokio::task::spawn_blocking(move || {
+            git::clone_repository(&git_url, &clone_destination, options).map(|_| ())
+        })
+        .await
+        .map_err(|e| Error::Git(format!("Clone task failed: {}", e)))??;
+
+        let project_info = self
+            .finish_new_project(
+                project_name,
+                &destination,
+                Some(request.git_url.clone()),
+                request.auto_setup,
+            )
+            .await?;
+
+        log::info!("Successfully cloned project: {}", project_info.name);
+        Ok(project_info)
+    }
+
+    /// Scaffolds a new project from the framework's starter template: Composer
+    /// `create-project` when the managed Composer and a PHP binary are installed, otherwise the template's
+    /// git repository or the skeleton bundled with the plugin. A source that
+    /// fails is cleaned up and the next one is tried.
+    pub async fn create_project(&self, request: CreateProjectRequest) -> Result<ProjectInfo> {
+        validate_project_name(&request.name)?;
+        let destination = match &request.destination {
+            Some(destination) => PathBuf::from(destination),
+            None => self.projects_dir.join(&request.name),
+        };
+        if destination.exists() && fs::read_dir(&destination)?.next().is_some() {
             return Err(Error::Config(format!(
-                "Destination already exists: {}",
+                "Destination is not empty: {}",
                 destination.display()
             )));
         }
 
-        // For testing purposes, create a mock project structure
-        log::info!("Creating mock project at {}", destination.display());
-        fs::create_dir_all(&destination)?;
+        let template = project_templates::template_for(&request.framework);
+        let mut sources = Vec::new();
+        match &request.template_url {
+            Some(url) => sources.push(TemplateSource::Git(url.clone())),
+            None => {
+                if let Some(template) = &template {
+                    if let Some(package) = &template.composer_package {
+                        if self.composer_launcher(None).is_ok() {
+                            sources.push(TemplateSource::Composer(package.clone()));
+                        }
+                    }
+                    if let Some(url) = &template.git_url {
+                        sources.push(TemplateSource::Git(url.clone()));
+                    }
+                    if template.bundled {
+                        sources.push(TemplateSource::Bundled);
+                    }
+                }
+            }
+        }
+        if sources.is_empty() {
+            return Err(Error::Config(format!(
+                "No project template for {}",
+                request.framework.as_str()
+            )));
+        }
 
-        // Create a basic PHP project structure based on the URL
-        if request.git_url.contains("laravel") {
-            self.create_mock_laravel_project(&destination)?;
-        } else if request.git_url.contains("symfony") {
-            self.create_mock_symfony_project(&destination)?;
-        } else if request.git_url.contains("thinkphp") || request.git_url.contains("think") {
-            self.create_mock_thinkphp_project(&destination)?;
-        } else {
-            self.create_mock_plain_project(&destination)?;
+        // An existing (empty) destination is the user's folder: a failed
+        // template only clears it rather than removing it
+        let existed = destination.exists();
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut last_error = None;
+        for source in sources {
+            match self.scaffold(&source, &request, &destination).await {
+                Ok(()) => {
+                    log::info!("Created {} from {:?}", destination.display(), source);
+                    last_error = None;
+                    break;
+                }
+                Err(e) => {
+                    log::warn!("Project template {:?} failed: {}", source, e);
+                    let cleanup = if existed {
+                        clear_directory(&destination)
+                    } else if destination.exists() {
+                        fs::remove_dir_all(&destination)
+                    } else {
+                        Ok(())
+                    };
+                    if let Err(cleanup_error) = cleanup {
+                        log::warn!(
+                            "Failed to clean up {}: {}",
+                            destination.display(),
+                            cleanup_error
+                        );
+                    }
+                    last_error = Some(e);
+                }
+            }
+        }
+        if let Some(e) = last_error {
+            return Err(e);
+        }
+
+        self.finish_new_project(request.name, &destination, None, request.auto_setup)
+            .await
+    }
+
+    async fn scaffold(
+        &self,
+        source: &TemplateSource,
+        request: &CreateProjectRequest,
+        destination: &Path,
+    ) -> Result<()> {
+        match source {
+            TemplateSource::Composer(package) => {
+                let package = match &request.version {
+                    Some(version) => format!("{}:{}", package, version),
+                    None => package.clone(),
+                };
+                let working_dir = destination.parent().unwrap_or(destination);
+                let output = self
+                    .composer_launcher(None)?
+                    .command(working_dir)
+                    .arg("create-project")
+                    .arg(&package)
+                    .arg(destination)
+                    .arg("--prefer-dist")
+                    .output()
+                    .await?;
+                if !output.status.success() {
+                    return Err(Error::Process(format!(
+                        "composer create-project {} failed: {}",
+                        package,
+                        String::from_utf8_lossy(&output.stderr).trim()
+                    )));
+                }
+                Ok(())
+            }
+            TemplateSource::Git(url) => {
+                let url = url.clone();
+                // The version is a Composer constraint; only an exact version
+                // names a tag
+                let tag = request
+                    .version
+                    .as_deref()
+                    .filter(|version| is_exact_version(version))
+                    .map(str::to_string);
+                if tag.is_none() {
+                    if let Some(version) = &request.version {
+                        log::warn!(
+                            "Version {} is not a tag, cloning the default branch",
+                            version
+                        );
+                    }
+                }
+                let options = git::CloneOptions {
+                    tag,
+                    depth: Some(1),
+                    single_branch: true,
+                    ..Default::default()
+                };
+                // Git refuses an existing destination, so an existing empty
+                // folder receives the clone from a staging directory this call
+                // creates next to it
+                let staging_root = if destination.exists() {
+                    let parent = destination.parent().unwrap_or(destination);
+                    let root = parent.join(format!(".php-template-{}", uuid::Uuid::new_v4()));
+                    fs::create_dir(&root)?;
+                    Some(root)
+                } else {
+                    None
+                };
+                let staging = match &staging_root {
+                    Some(root) => root.join("template"),
+                    None => destination.to_path_buf(),
+                };
+                let clone_destination = staging.clone();
+                let cloned = tokio::task::spawn_blocking(move || {
+                    git::clone_repository(&url, &clone_destination, options).map(|_| ())
+                })
+                .await
+                .map_err(|e| Error::Git(format!("Clone task failed: {}", e)))
+                .and_then(|result| result);
+                let result = cloned.and_then(|()| {
+                    // A new project starts without the template's history
+                    fs::remove_dir_all(staging.join(".git"))?;
+                    if staging_root.is_some() {
+                        move_entries(&staging, destination)?;
+                    }
+                    Ok(())
+                });
+                if let Some(root) = &staging_root {
+                    if let Err(e) = fs::remove_dir_all(root) {
+                        log::warn!("Failed to remove {}: {}", root.display(), e);
+                    }
+                }
+                result
+            }
+            TemplateSource::Bundled => project_templates::write_bundled_skeleton(
+                &request.framework,
+                destination,
+                &request.name,
+            ),
         }
+    }
 
+    /// Detects, registers and optionally sets up a project that was just
+    /// cloned or created.
+    async fn finish_new_project(
+        &self,
+        project_name: String,
+        destination: &Path,
+        git_url: Option<String>,
+        auto_setup: bool,
+    ) -> Result<ProjectInfo> {
         // Detect framework
-        let framework = self.framework_detector.detect_framework(&destination)?;
+        let framework = self.framework_detector.detect_framework(destination)?;
+
+        // The project stands even if the registry cannot be written. Projects
+        // outside the projects directory are only listed as imports.
+        let registered = fs::canonicalize(destination)
+            .map_err(Error::from)
+            .and_then(|path| {
+                let in_projects_dir = fs::canonicalize(&self.projects_dir)
+                    .is_ok_and(|projects_dir| path.parent() == Some(projects_dir.as_path()));
+                if in_projects_dir {
+                    self.registry.ensure(&project_name, &path)
+                } else {
+                    self.registry.register(&project_name, &path)
+                }
+            })
+            .map_err(|e| log::warn!("Failed to register {}: {}", project_name, e))
+            .ok();
 
         // Create project info
         let mut project_info = ProjectInfo {
             name: project_name,
             path: destination.to_string_lossy().to_string(),
-            framework: Some(framework.clone()),
-            git_url: Some(request.git_url.clone()),
+            framework_version: self.detect_framework_version(destination, &framework),
+            framework: Some(framework),
+            git_url,
             php_version: None,
             entry_point: None,
+            id: registered.as_ref().map(|project| project.id.clone()),
+            settings: registered
+                .map(|project| project.settings)
+                .unwrap_or_default(),
+            setup: None,
         };
 
         // Auto-setup if requested
-        if request.auto_setup {
-            project_info = self.auto_setup_project(project_info, &destination).await?;
+        if auto_setup {
+            project_info = self.auto_setup_project(project_info, destination).await?;
         }
 
-        log::info!("Successfully created mock project: {}", project_info.name);
         Ok(project_info)
     }
 
-    fn create_mock_laravel_project(&self, destination: &Path) -> Result<()> {
-        fs::write(
-            destination.join("artisan"),
-            "#!/usr/bin/env php\n<?php\n// Laravel artisan",
-        )?;
-        fs::create_dir_all(destination.join("app/Http"))?;
-        fs::write(
-            destination.join("app/Http/Kernel.php"),
-            "<?php\nnamespace App\\Http;\nclass Kernel {}",
-        )?;
-        fs::create_dir_all(destination.join("public"))?;
-        fs::write(
-            destination.join("public/index.php"),
-            "<?php\nrequire_once __DIR__.'/../vendor/autoload.php';",
-        )?;
-        fs::write(
-            destination.join("composer.json"),
-            r#"{"require": {"laravel/framework": "^10.0"}}"#,
-        )?;
-        Ok(())
+    fn resolve_credentials(
+        &self,
+        credentials: Option<&GitCredentials>,
+    ) -> Result<Option<git::ResolvedCredentials>> {
+        credentials
+            .map(|credentials| git::ResolvedCredentials::resolve(credentials, &self.secret_store))
+            .transpose()
     }
 
-    fn create_mock_symfony_project(&self, destination: &Path) -> Result<()> {
-        fs::create_dir_all(destination.join("bin"))?;
-        fs::write(
-            destination.join("bin/console"),
-            "#!/usr/bin/env php\n<?php\n// Symfony console",
-        )?;
-        fs::create_dir_all(destination.join("config"))?;
-        fs::write(destination.join("config/bundles.php"), "<?php\nreturn [];")?;
-        fs::create_dir_all(destination.join("public"))?;
-        fs::write(
-            destination.join("public/index.php"),
-            "<?php\nuse App\\Kernel;",
-        )?;
-        fs::write(
-            destination.join("composer.json"),
-            r#"{"require": {"symfony/framework-bundle": "^6.0"}}"#,
-        )?;
-        Ok(())
+    pub fn git_status(&self, project_path: &Path) -> Result<GitStatus> {
+        git::repository_status(project_path)
     }
 
-    fn create_mock_thinkphp_project(&self, destination: &Path) -> Result<()> {
-        fs::write(
-            destination.join("think"),
-            "#!/usr/bin/env php\n<?php\nuse think\\Console;",
-        )?;
-        fs::create_dir_all(destination.join("config"))?;
-        fs::write(
-            destination.join("config/app.php"),
-            "<?php\nreturn ['app_name' => 'ThinkPHP'];",
-        )?;
-        fs::create_dir_all(destination.join("public"))?;
-        fs::write(
-            destination.join("public/index.php"),
-            "<?php\nuse think\\App;\n$app = new App();",
-        )?;
-        fs::write(
-            destination.join("composer.json"),
-            r#"{"require": {"topthink/framework": "^6.0"}}"#,
-        )?;
-        Ok(())
+    /// Fetches the current branch's upstream remote (`origin` otherwise) and
+    /// returns the refreshed status.
+    pub async fn fetch_project(
+        &self,
+        project_path: &Path,
+        credentials: Option<&GitCredentials>,
+    ) -> Result<GitStatus> {
+        let credentials = self.resolve_credentials(credentials)?;
+        let project_path = project_path.to_path_buf();
+        tokio::task::spawn_blocking(move || {
+            let remote = upstream_remote(&project_path).unwrap_or_else(|| "origin".to_string());
+            git::fetch_repository(&project_path, &remote, credentials)?;
+            git::repository_status(&project_path)
+        })
+        .await
+        .map_err(|e| Error::Git(format!("Fetch task failed: {}", e)))?
     }
 
-    fn create_mock_plain_project(&self, destination: &Path) -> Result<()> {
-        fs::write(
-            destination.join("index.php"),
-            "<?php\necho 'Hello, World!';",
-        )?;
-        Ok(())
+    /// Fetches and fast-forwards the current branch; diverged branches are an error.
+    pub async fn pull_project(
+        &self,
+        project_path: &Path,
+        credentials: Option<&GitCredentials>,
+    ) -> Result<GitPullResult> {
+        let credentials = self.resolve_credentials(credentials)?;
+        let project_path = project_path.to_path_buf();
+        tokio::task::spawn_blocking(move || git::pull_fast_forward(&project_path, credentials))
+            .await
+            .map_err(|e| Error::Git(format!("Pull task failed: {}", e)))?
+    }
+
+    pub fn switch_branch(&self, project_path: &Path, branch: &str) -> Result<GitStatus> {
+        git::switch_branch(project_path, branch)
     }
 
     async fn auto_setup_project(
@@ -173,248 +809,219 @@ impl ProjectManager {
         mut project_info: ProjectInfo,
         project_path: &Path,
     ) -> Result<ProjectInfo> {
-        match &project_info.framework {
-            Some(Framework::Laravel) => {
-                self.setup_laravel_project(project_path).await?;
-                project_info.entry_point = Some("public/index.php".to_string());
-            }
-            Some(Framework::Symfony) => {
-                self.setup_symfony_project(project_path).await?;
-                project_info.entry_point = Some("public/index.php".to_string());
-            }
-            Some(Framework::CodeIgniter) => {
-                project_info.entry_point = Some("index.php".to_string());
-            }
-            Some(Framework::CakePHP) => {
-                project_info.entry_point = Some("webroot/index.php".to_string());
-            }
-            Some(Framework::ThinkPHP) => {
-                self.setup_thinkphp_project(project_path).await?;
-                // ThinkPHP entry point depends on version
-                if project_path.join("public").join("index.php").exists() {
-                    project_info.entry_point = Some("public/index.php".to_string());
-                } else if project_path.join("index.php").exists() {
-                    project_info.entry_point = Some("index.php".to_string());
-                }
-            }
-            _ => {
-                // For other frameworks or plain PHP, try to find index.php
-                if project_path.join("index.php").exists() {
-                    project_info.entry_point = Some("index.php".to_string());
-                } else if project_path.join("public").join("index.php").exists() {
-                    project_info.entry_point = Some("public/index.php".to_string());
-                }
-            }
-        }
+        let report = self
+            .setup_project(SetupProjectRequest {
+                project_path: project_path.to_string_lossy().to_string(),
+                ..Default::default()
+            })
+            .await?;
+
+        // The entry point of the detected version, or else whichever index.php exists
+        let framework = project_info.framework.clone().unwrap_or(Framework::Unknown);
+        let info = self
+            .framework_detector
+            .framework_info(&framework, project_info.framework_version.as_ref());
+        project_info.entry_point = [info.default_entry_point, "index.php", "public/index.php"]
+            .into_iter()
+            .find(|entry_point| project_path.join(entry_point).is_file())
+            .map(str::to_string);
 
         // Detect PHP version requirement
         project_info.php_version = self.detect_php_version_requirement(project_path)?;
+        project_info.setup = Some(report);
 
         Ok(project_info)
     }
 
-    async fn setup_laravel_project(&self, project_path: &Path) -> Result<()> {
-        log::info!("Setting up Laravel project...");
-
-        // Check if composer.json exists
-        let composer_json = project_path.join("composer.json");
-        if !composer_json.exists() {
-            return Err(Error::Config(
-                "composer.json not found in Laravel project".to_string(),
-            ));
+    /// Runs the setup of the project's framework, reporting each step. A dry
+    /// run reports the steps that would run as planned without running them.
+    pub async fn setup_project(&self, request: SetupProjectRequest) -> Result<SetupReport> {
+        let project_path = PathBuf::from(&request.project_path);
+        if !project_path.is_dir() {
+            return Err(Error::Config(format!(
+                "Project directory not found: {}",
+                project_path.display()
+            )));
         }
-
-        // Install dependencies with Composer (if available)
-        if self.is_composer_available().await {
-            log::info!("Installing Composer dependencies...");
-            let output = tokio::process::Command::new("composer")
-                .arg("install")
-                .arg("--no-dev")
-                .current_dir(project_path)
-                .output()
-                .await?;
-
-            if !output.status.success() {
-                log::warn!(
-                    "Composer install failed: {}",
-                    String::from_utf8_lossy(&output.stderr)
-                );
+        if let Some(database) = &request.laravel.database {
+            if database.connection.trim().is_empty() {
+                return Err(Error::Config("Database connection is required".to_string()));
             }
         }
 
-        // Copy .env.example to .env if it doesn't exist
-        let env_example = project_path.join(".env.example");
-        let env_file = project_path.join(".env");
-
-        if env_example.exists() && !env_file.exists() {
-            fs::copy(&env_example, &env_file)?;
-            log::info!("Created .env file from .env.example");
-        }
-
-        // Generate application key (simplified version)
-        if env_file.exists() {
-            let env_content = fs::read_to_string(&env_file)?;
-            if env_content.contains("APP_KEY=") && !env_content.contains("APP_KEY=base64:") {
-                log::info!(
-                    "APP_KEY needs to be generated. Run 'php artisan key:generate' manually."
-                );
+        let framework = self.framework_detector.detect_framework(&project_path)?;
+        let version = self.detect_framework_version(&project_path, &framework);
+        let pipeline = self.setup_pipeline(
+            &project_path,
+            &framework,
+            version.as_ref(),
+            &request.laravel,
+        )?;
+        let console_script = framework_detector::console_script(&framework, version.as_ref());
+        log::info!(
+            "Setting up {:?} {} project with {} steps...",
+            framework,
+            version
+                .map(|version| version.major.to_string())
+                .unwrap_or_default(),
+            pipeline.steps.len()
+        );
+
+        let mut steps = Vec::new();
+        let mut planned = PlannedFiles::default();
+        for step in &pipeline.steps {
+            let result = self
+                .run_setup_step(
+                    &project_path,
+                    step,
+                    console_script,
+                    request.dry_run,
+                    &planned,
+                )
+                .await;
+            if result.status == SetupStepStatus::Planned {
+                planned.record(step);
             }
+            steps.push(result);
         }
-
-        Ok(())
+        Ok(SetupReport {
+            framework,
+            framework_version: version,
+            steps,
+            dry_run: request.dry_run,
+        })
     }
 
-    async fn setup_symfony_project(&self, project_path: &Path) -> Result<()> {
-        log::info!("Setting up Symfony project...");
-
-        // Check if composer.json exists
-        let composer_json = project_path.join("composer.json");
-        if !composer_json.exists() {
-            return Err(Error::Config(
-                "composer.json not found in Symfony project".to_string(),
-            ));
-        }
-
-        // Install dependencies with Composer (if available)
-        if self.is_composer_available().await {
-            log::info!("Installing Composer dependencies...");
-            let output = tokio::process::Command::new("composer")
-                .arg("install")
-                .arg("--no-dev")
-                .current_dir(project_path)
-                .output()
-                .await?;
-
-            if !output.status.success() {
-                log::warn!(
-                    "Composer install failed: {}",
-                    String::from_utf8_lossy(&output.stderr)
-                );
-            }
+    /// The framework's default pipeline with the project's `php-setup.json`
+    /// applied.
+    pub fn setup_pipeline(
+        &self,
+        project_path: &Path,
+        framework: &Framework,
+        version: Option<&FrameworkVersion>,
+        options: &LaravelSetupOptions,
+    ) -> Result<SetupPipeline> {
+        let mut pipeline = setup_pipeline::default_pipeline(framework, version, options);
+        if let Some(overrides) = setup_pipeline::load_overrides(project_path)? {
+            pipeline.apply_overrides(overrides)?;
         }
-
-        Ok(())
+        // The defaults carry paths from the request, such as the SQLite file
+        pipeline.validate()?;
+        Ok(pipeline)
     }
 
-    async fn setup_thinkphp_project(&self, project_path: &Path) -> Result<()> {
-        log::info!("Setting up ThinkPHP project...");
-
-        // Check if composer.json exists
-        let composer_json = project_path.join("composer.json");
-        if composer_json.exists() {
-            // Install dependencies with Composer (if available)
-            if self.is_composer_available().await {
-                log::info!("Installing Composer dependencies...");
-                let output = tokio::process::Command::new("composer")
-                    .arg("install")
-                    .arg("--no-dev")
-                    .current_dir(project_path)
-                    .output()
-                    .await?;
+    /// Runs one pipeline step; failures are reported in the step rather than
+    /// stopping the setup.
+    async fn run_setup_step(
+        &self,
+        project_path: &Path,
+        step: &PipelineStep,
+        console_script: Option<&str>,
+        dry_run: bool,
+        planned: &PlannedFiles,
+    ) -> SetupStep {
+        let name = step.display_name();
+        // Checked before dry runs too, as a symlink can change between runs
+        if let Err(e) = step.check_resolved_paths(project_path) {
+            return SetupStep::failed(&name, e.to_string());
+        }
+        if let Some(reason) = skip_reason(project_path, step, console_script, planned) {
+            return SetupStep::skipped(&name, reason);
+        }
+        if dry_run {
+            return SetupStep::planned(&name, step.describe(console_script));
+        }
 
-                if !output.status.success() {
-                    log::warn!(
-                        "Composer install failed: {}",
-                        String::from_utf8_lossy(&output.stderr)
-                    );
+        match &step.action {
+            SetupAction::CopyFile { from, to } => {
+                match fs::copy(project_path.join(from), project_path.join(to)) {
+                    Ok(_) => {
+                        SetupStep::succeeded(&name, Some(format!("Created {} from {}", to, from)))
+                    }
+                    Err(e) => SetupStep::failed(&name, format!("Failed to copy {}: {}", from, e)),
                 }
             }
-        }
-
-        // Set up ThinkPHP-specific configurations
-        // Check for different ThinkPHP versions and set up accordingly
-
-        // ThinkPHP 6.x setup
-        if project_path.join("config").join("app.php").exists() {
-            log::info!("Detected ThinkPHP 6.x");
-
-            // Create .env file if it doesn't exist
-            let env_example = project_path.join(".example.env");
-            let env_file = project_path.join(".env");
-
-            if env_example.exists() && !env_file.exists() {
-                fs::copy(&env_example, &env_file)?;
-                log::info!("Created .env file from .example.env");
+            SetupAction::Composer { action, dev, args } => {
+                let (extra_args, packages) =
+                    args.iter().cloned().partition(|arg| arg.starts_with('-'));
+                let request = ComposerRequest {
+                    project_path: project_path.to_string_lossy().to_string(),
+                    action: *action,
+                    dev: *dev,
+                    packages,
+                    extra_args,
+                    ..Default::default()
+                };
+                self.composer_step(&name, request).await
             }
-        }
-        // ThinkPHP 5.x setup
-        else if project_path.join("application").join("config.php").exists() {
-            log::info!("Detected ThinkPHP 5.x");
-
-            // Check if runtime directory exists and is writable
-            let runtime_dir = project_path.join("runtime");
-            if !runtime_dir.exists() {
-                fs::create_dir_all(&runtime_dir)?;
-                log::info!("Created runtime directory");
+            SetupAction::Console { args } => {
+                let request = FrameworkCommandRequest {
+                    project_path: project_path.to_string_lossy().to_string(),
+                    args: args.clone(),
+                    ..Default::default()
+                };
+                match self.framework_command(&request) {
+                    Ok(command) => console_step(&name, &command).await,
+                    Err(e) => SetupStep::skipped(&name, e.to_string()),
+                }
             }
-        }
-        // ThinkPHP 3.x setup
-        else if project_path.join("Application").exists() {
-            log::info!("Detected ThinkPHP 3.x");
-
-            // Check if Runtime directory exists and is writable
-            let runtime_dir = project_path.join("Application").join("Runtime");
-            if !runtime_dir.exists() {
-                fs::create_dir_all(&runtime_dir)?;
-                log::info!("Created Runtime directory");
+            SetupAction::WritableDir { path } => match make_writable(&project_path.join(path)) {
+                Ok(()) => SetupStep::succeeded(&name, Some(format!("{} is writable", path))),
+                Err(e) => SetupStep::failed(&name, format!("Failed to prepare {}: {}", path, e)),
+            },
+            SetupAction::CreateFile { path } => {
+                let file = project_path.join(path);
+                let created = match file.parent() {
+                    Some(parent) => fs::create_dir_all(parent),
+                    None => Ok(()),
+                }
+                .and_then(|_| fs::File::create(&file));
+                match created {
+                    Ok(_) => SetupStep::succeeded(&name, Some(format!("Created {}", path))),
+                    Err(e) => SetupStep::failed(&name, format!("Failed to create {}: {}", path, e)),
+                }
             }
-        }
-
-        // Set permissions for cache and log directories (Unix only)
-        #[cfg(unix)]
-        {
-            use std::os::unix::fs::PermissionsExt;
-
-            let cache_dirs = [
-                project_path.join("runtime"),
-                project_path.join("Application").join("Runtime"),
-                project_path.join("app").join("runtime"),
-            ];
-
-            for cache_dir in &cache_dirs {
-                if cache_dir.exists() {
-                    let mut perms = fs::metadata(cache_dir)?.permissions();
-                    perms.set_mode(0o755);
-                    fs::set_permissions(cache_dir, perms)?;
-                    log::info!(
-                        "Set permissions for cache directory: {}",
-                        cache_dir.display()
-                    );
+            SetupAction::SetEnv { file, values } => {
+                let file = file.as_deref().unwrap_or(".env");
+                let project_dir = project_path.to_string_lossy();
+                let values: Vec<(&str, String)> = values
+                    .iter()
+                    .map(|(key, value)| {
+                        (
+                            key.as_str(),
+                            value.replace(setup_pipeline::PROJECT_DIR, &project_dir),
+                        )
+                    })
+                    .collect();
+                match env_file::set_values(&project_path.join(file), &values) {
+                    Ok(()) => SetupStep::succeeded(
+                        &name,
+                        Some(format!(
+                            "Set {} in {}",
+                            values
+                                .iter()
+                                .map(|(key, _)| *key)
+                                .collect::<Vec<_>>()
+                                .join(", "),
+                            file
+                        )),
+                    ),
+                    Err(e) => SetupStep::failed(&name, e.to_string()),
                 }
             }
         }
-
-        Ok(())
-    }
-
-    async fn is_composer_available(&self) -> bool {
-        tokio::process::Command::new("composer")
-            .arg("--version")
-            .output()
-            .await
-            .map(|output| output.status.success())
-            .unwrap_or(false)
     }
 
     fn detect_php_version_requirement(&self, project_path: &Path) -> Result<Option<String>> {
         // Check composer.json for PHP version requirement
-        let composer_json = project_path.join("composer.json");
-        if composer_json.exists() {
-            let content = fs::read_to_string(&composer_json)?;
-            let json: serde_json::Value = serde_json::from_str(&content)?;
-
-            if let Some(require) = json.get("require") {
-                if let Some(php_version) = require.get("php") {
-                    if let Some(version_str) = php_version.as_str() {
-                        // Parse version constraint (simplified)
-                        let version = self.parse_php_version_constraint(version_str)?;
-                        return Ok(version);
-                    }
-                }
-            }
+        let Some(manifest) = ComposerJson::load(project_path)? else {
+            return Ok(None);
+        };
+        match manifest.require.get("php") {
+            // Parse version constraint (simplified)
+            Some(constraint) => self.parse_php_version_constraint(constraint),
+            None => Ok(None),
         }
-
-        Ok(None)
     }
 
     fn parse_php_version_constraint(&self, constraint: &str) -> Result<Option<String>> {
@@ -436,67 +1043,155 @@ impl ProjectManager {
     }
 
     fn extract_project_name(&self, git_url: &str) -> Result<String> {
-        let url = Url::parse(git_url)?;
+        git::repository_name(git_url)
+    }
 
-        let path = url.path();
-        let name = path
-            .trim_start_matches('/')
-            .trim_end_matches(".git")
-            .split('/')
-            .next_back()
-            .ok_or_else(|| Error::InvalidUrl("Cannot extract project name from URL".to_string()))?;
+    /// Lists the directories under the projects directory followed by the
+    /// imported projects that still exist.
+    pub fn list_projects(&self) -> Result<Vec<ProjectInfo>> {
+        let mut projects = Vec::new();
+        let mut seen = Vec::new();
+
+        if self.projects_dir.exists() {
+            for entry in fs::read_dir(&self.projects_dir)? {
+                let entry = entry?;
+                let path = entry.path();
+
+                if path.is_dir() {
+                    if let Some(project_name) = path.file_name().and_then(|n| n.to_str()) {
+                        let canonical = fs::canonicalize(&path).unwrap_or_else(|_| path.clone());
+                        // First sight of a project gives it a registry id for its settings
+                        let registered = self
+                            .registry
+                            .ensure(project_name, &canonical)
+                            .map_err(|e| log::warn!("Failed to register {}: {}", project_name, e))
+                            .ok();
+                        projects.push(self.describe_project(
+                            project_name,
+                            &path,
+                            registered.as_ref(),
+                        ));
+                        seen.push(canonical);
+                    }
+                }
+            }
+        }
 
-        if name.is_empty() {
-            return Err(Error::InvalidUrl("Empty project name".to_string()));
+        for registered in self.registry.projects() {
+            let path = PathBuf::from(&registered.path);
+            if !registered.imported || seen.contains(&path) {
+                continue;
+            }
+            if !path.is_dir() {
+                log::warn!(
+                    "Imported project {} is missing: {}",
+                    registered.name,
+                    registered.path
+                );
+                continue;
+            }
+            projects.push(self.describe_project(&registered.name, &path, Some(&registered)));
         }
 
-        Ok(name.to_string())
+        Ok(projects)
     }
 
-    pub fn list_projects(&self) -> Result<Vec<ProjectInfo>> {
-        let mut projects = Vec::new();
+    /// Resolves a project name to its folder directly inside the projects
+    /// directory, refusing names and symlinks that lead anywhere else.
+    pub fn project_directory(&self, project_name: &str) -> Result<PathBuf> {
+        validate_project_name(project_name)?;
 
-        if !self.projects_dir.exists() {
-            return Ok(projects);
+        let project_path = self.projects_dir.join(project_name);
+        let metadata = fs::symlink_metadata(&project_path)
+            .map_err(|_| Error::Config(format!("Project not found: {}", project_name)))?;
+        if metadata.file_type().is_symlink() || !metadata.is_dir() {
+            return Err(Error::PermissionDenied(format!(
+                "{} is not a project folder",
+                project_path.display()
+            )));
         }
 
-        for entry in fs::read_dir(&self.projects_dir)? {
-            let entry = entry?;
-            let path = entry.path();
-
-            if path.is_dir() {
-                if let Some(project_name) = path.file_name().and_then(|n| n.to_str()) {
-                    let framework = self.framework_detector.detect_framework(&path).ok();
+        let projects_dir = fs::canonicalize(&self.projects_dir)?;
+        let project_path = fs::canonicalize(&project_path)?;
+        if project_path.parent() != Some(projects_dir.as_path()) {
+            return Err(Error::PermissionDenied(format!(
+                "{} is outside the projects directory",
+                project_path.display()
+            )));
+        }
+        Ok(project_path)
+    }
 
-                    let project_info = ProjectInfo {
-                        name: project_name.to_string(),
-                        path: path.to_string_lossy().to_string(),
-                        framework,
-                        git_url: None, // Could be detected from .git/config
-                        php_version: self.detect_php_version_requirement(&path).ok().flatten(),
-                        entry_point: None, // Could be detected based on framework
-                    };
+    /// Deletes a project folder, or moves it to the trash when `trash` is set,
+    /// and forgets its registry entry. Running servers are the caller's concern.
+    pub fn remove_project(
+        &self,
+        project_name: &str,
+        trash: bool,
+    ) -> Result<Option<TrashedProject>> {
+        let project_path = self.project_directory(project_name)?;
+        let registered = self.registry.find_by_path(&project_path);
+
+        let trashed = if trash {
+            Some(self.trash.trash(project_name, &project_path, registered)?)
+        } else {
+            fs::remove_dir_all(&project_path)?;
+            None
+        };
+        if let Err(e) = self.registry.unregister(&project_path) {
+            log::warn!("Failed to unregister {}: {}", project_name, e);
+        }
 
-                    projects.push(project_info);
-                }
+        log::info!(
+            "Removed project: {}{}",
+            project_name,
+            if trashed.is_some() {
+                " (moved to trash)"
+            } else {
+                ""
             }
-        }
+        );
+        Ok(trashed)
+    }
 
-        Ok(projects)
+    pub fn list_trash(&self) -> Vec<TrashedProject> {
+        self.trash.projects()
     }
 
-    pub fn remove_project(&self, project_name: &str) -> Result<()> {
-        let project_path = self.projects_dir.join(project_name);
+    /// Moves a trashed project back into the projects directory, under
+    /// `name` or its original name, with its registry id and settings.
+    pub fn restore_project(&self, trash_id: &str, name: Option<&str>) -> Result<ProjectInfo> {
+        let trashed = self
+            .trash
+            .projects()
+            .into_iter()
+            .find(|project| project.trash_id == trash_id)
+            .ok_or_else(|| Error::Config(format!("Trashed project not found: {}", trash_id)))?;
+        let name = name.unwrap_or(&trashed.name);
+        validate_project_name(name)?;
+
+        fs::create_dir_all(&self.projects_dir)?;
+        let destination = self.projects_dir.join(name);
+        let trashed = self.trash.restore(trash_id, &destination)?;
+        let path = fs::canonicalize(&destination)?;
+
+        let registered = match trashed.registered {
+            Some(mut registered) => {
+                registered.path = path.to_string_lossy().to_string();
+                registered.name = name.to_string();
+                self.registry.restore(registered.clone())?;
+                registered
+            }
+            None => self.registry.ensure(name, &path)?,
+        };
+        Ok(self.describe_project(name, &path, Some(&registered)))
+    }
 
-        if project_path.exists() {
-            fs::remove_dir_all(&project_path)?;
-            log::info!("Removed project: {}", project_name);
-            Ok(())
-        } else {
-            Err(Error::Config(format!(
-                "Project not found: {}",
-                project_name
-            )))
+    /// Deletes one trashed project, or all of them, and returns how many were deleted.
+    pub fn purge_trash(&self, trash_id: Option<&str>) -> Result<usize> {
+        match trash_id {
+            Some(trash_id) => self.trash.purge(trash_id).map(|_| 1),
+            None => self.trash.purge_all(),
         }
     }
 
@@ -505,6 +1200,19 @@ impl ProjectManager {
         self.framework_detector.detect_framework(project_path)
     }
 
+    pub fn detect_framework_version(
+        &self,
+        project_path: &Path,
+        framework: &Framework,
+    ) -> Option<FrameworkVersion> {
+        self.framework_detector
+            .detect_version(project_path, framework)
+    }
+
+    pub fn rank_frameworks(&self, project_path: &Path) -> Result<Vec<FrameworkCandidate>> {
+        self.framework_detector.rank_frameworks(project_path)
+    }
+
     pub fn get_framework_info(
         &self,
         framework: &Framework,
@@ -512,6 +1220,51 @@ impl ProjectManager {
         self.framework_detector.get_framework_info(framework)
     }
 
+    pub fn framework_info(
+        &self,
+        framework: &Framework,
+        version: Option<&FrameworkVersion>,
+    ) -> crate::framework_detector::FrameworkInfo {
+        self.framework_detector.framework_info(framework, version)
+    }
+
+    /// Collects the PHP extensions a project needs: the `ext-*` requirements
+    /// of composer.json and its locked packages, and those of its framework.
+    pub fn required_extensions(&self, project_path: &Path) -> Vec<RequiredExtension> {
+        let mut required: Vec<RequiredExtension> = Vec::new();
+        let mut add = |name: &str, source: &str, dev: bool| {
+            let name = php_binary::normalize_extension_name(name);
+            match required.iter_mut().find(|extension| extension.name == name) {
+                Some(extension) => {
+                    extension.dev &= dev;
+                    if !extension.required_by.iter().any(|s| s == source) {
+                        extension.required_by.push(source.to_string());
+                    }
+                }
+                None => required.push(RequiredExtension {
+                    name,
+                    required_by: vec![source.to_string()],
+                    dev,
+                }),
+            }
+        };
+
+        if let Ok(dependencies) = composer_manifest::project_dependencies(project_path) {
+            for extension in &dependencies.extensions {
+                for source in &extension.required_by {
+                    add(&extension.name, source, extension.dev);
+                }
+            }
+        }
+        if let Ok(framework) = self.detect_framework(project_path) {
+            let info = self.get_framework_info(&framework);
+            for name in &info.required_extensions {
+                add(name, info.name, false);
+            }
+        }
+        required
+    }
+
     pub fn detect_php_version_requirement_public(
         &self,
         project_path: &Path,
@@ -519,3 +1272,204 @@ impl ProjectManager {
         self.detect_php_version_requirement(project_path)
     }
 }
+
+/// Name of the remote the current branch tracks, if it tracks one.
+fn upstream_remote(project_path: &Path) -> Option<String> {
+    let repo = git2::Repository::open(project_path).ok()?;
+    let head = repo.head().ok()?;
+    let remote = repo.branch_upstream_remote(head.name()?).ok()?;
+    remote.as_str().map(str::to_string)
+}
+
+/// Accepts a plain folder name: no separators, no `.` or `..`, nothing hidden.
+fn validate_project_name(name: &str) -> Result<()> {
+    let valid = !name.trim().is_empty()
+        && !name.starts_with('.')
+        && !name.contains(['/', '\\', ':', '\0'])
+        && Path::new(name).components().count() == 1;
+    if valid {
+        Ok(())
+    } else {
+        Err(Error::Config(format!("Invalid project name: {:?}", name)))
+    }
+}
+
+/// An exact version such as `11.0.3` or `v1.0.0`, as opposed to a Composer
+/// constraint like `^11.0`.
+fn is_exact_version(version: &str) -> bool {
+    let digits = version.strip_prefix('v').unwrap_or(version);
+    !digits.is_empty()
+        && digits
+            .split('.')
+            .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Removes everything inside `dir`, keeping the directory itself.
+fn clear_directory(dir: &Path) -> std::io::Result<()> {
+    if !dir.exists() {
+        return fs::create_dir_all(dir);
+    }
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            fs::remove_dir_all(entry.path())?;
+        } else {
+            fs::remove_file(entry.path())?;
+        }
+    }
+    Ok(())
+}
+
+/// Moves the entries of `from` into `to`; both are on the same filesystem.
+fn move_entries(from: &Path, to: &Path) -> Result<()> {
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        fs::rename(entry.path(), to.join(entry.file_name()))?;
+    }
+    Ok(())
+}
+
+/// A place `create_project` gets a starter project from.
+#[derive(Debug)]
+enum TemplateSource {
+    /// Composer package for `composer create-project`
+    Composer(String),
+    Git(String),
+    Bundled,
+}
+
+/// Files the planned steps of a dry run would create, each with the file its
+/// content would be copied from, so later steps are checked as if they ran.
+#[derive(Debug, Default)]
+struct PlannedFiles(HashMap<String, Option<String>>);
+
+impl PlannedFiles {
+    fn record(&mut self, step: &PipelineStep) {
+        match &step.action {
+            SetupAction::CopyFile { from, to } => {
+                self.0.insert(to.clone(), Some(from.clone()));
+            }
+            SetupAction::CreateFile { path } => {
+                self.0.insert(path.clone(), None);
+            }
+            SetupAction::Composer {
+                action: ComposerAction::Install | ComposerAction::Update,
+                ..
+            } => {
+                self.0.insert("vendor/autoload.php".to_string(), None);
+            }
+            _ => {}
+        }
+    }
+
+    fn contains(&self, path: &str) -> bool {
+        self.0.contains_key(path)
+    }
+
+    /// The content of `path`, as planned or as on disk.
+    fn read(&self, project_path: &Path, path: &str) -> std::io::Result<String> {
+        match self.0.get(path) {
+            Some(Some(from)) => fs::read_to_string(project_path.join(from)),
+            Some(None) => Ok(String::new()),
+            None => fs::read_to_string(project_path.join(path)),
+        }
+    }
+}
+
+/// Why a setup step does not apply to the project, checked before dry runs
+/// too: its conditions, an existing target or a console that cannot run yet.
+fn skip_reason(
+    project_path: &Path,
+    step: &PipelineStep,
+    console_script: Option<&str>,
+    planned: &PlannedFiles,
+) -> Option<String> {
+    let exists = |path: &str| {
+        planned.contains(path) || fs::symlink_metadata(project_path.join(path)).is_ok()
+    };
+
+    if let Some(path) = step.if_exists.as_deref().filter(|path| !exists(path)) {
+        return Some(format!("{} does not exist", path));
+    }
+    if let Some(path) = step.unless_exists.as_deref().filter(|path| exists(path)) {
+        return Some(format!("{} already exists", path));
+    }
+    if let Some(key) = &step.unless_env {
+        let Ok(content) = planned.read(project_path, ".env") else {
+            return Some("No .env file".to_string());
+        };
+        if env_file::get_value(&content, key).is_some_and(|value| !value.is_empty()) {
+            return Some(format!("{} is already set", key));
+        }
+    }
+
+    match &step.action {
+        SetupAction::CopyFile { from, to } => {
+            if exists(to) {
+                Some(format!("{} already exists", to))
+            } else if !exists(from) {
+                Some(format!("No {} to copy", from))
+            } else {
+                None
+            }
+        }
+        SetupAction::CreateFile { path } if exists(path) => {
+            Some(format!("{} already exists", path))
+        }
+        SetupAction::Console { .. } => match console_script {
+            None => Some("The framework has no console script".to_string()),
+            Some(script) if !project_path.join(script).is_file() => {
+                Some(format!("{} not found", script))
+            }
+            // The console boots the framework from the installed dependencies
+            Some(_)
+                if project_path.join("composer.json").is_file()
+                    && !planned.contains("vendor/autoload.php")
+                    && !project_path.join("vendor").join("autoload.php").is_file() =>
+            {
+                Some("Dependencies are not installed (vendor/autoload.php is missing)".to_string())
+            }
+            Some(_) => None,
+        },
+        _ => None,
+    }
+}
+
+/// Runs a framework console command as a setup step.
+async fn console_step(name: &str, command: &FrameworkCommand) -> SetupStep {
+    let run_id = uuid::Uuid::new_v4().to_string();
+    match command.run(&run_id, Arc::default(), None).await {
+        Ok(result) => {
+            let mut step = if result.success {
+                SetupStep::succeeded(name, None)
+            } else {
+                SetupStep::failed(
+                    name,
+                    format!(
+                        "{} {} exited with {:?}",
+                        result.script,
+                        result.args.join(" "),
+                        result.exit_code
+                    ),
+                )
+            };
+            step.output = result.output;
+            step
+        }
+        Err(e) => SetupStep::failed(name, e.to_string()),
+    }
+}
+
+/// Creates a directory the application writes to, owner-writable on Unix.
+fn make_writable(dir: &Path) -> Result<()> {
+    fs::create_dir_all(dir)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        let mut perms = fs::metadata(dir)?.permissions();
+        perms.set_mode(perms.mode() | 0o755);
+        fs::set_permissions(dir, perms)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,206 @@
+use crate::models::ProjectSettings;
+use crate::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// A project the plugin knows about: cloned, imported or found in the
+/// projects directory.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RegisteredProject {
+    /// Stable id that survives renames and settings changes
+    pub id: String,
+    pub name: String,
+    /// Canonical absolute path of the project root
+    pub path: String,
+    /// Seconds since the Unix epoch
+    pub registered_at: u64,
+    /// Added with `import_project` rather than found in the projects directory
+    #[serde(default)]
+    pub imported: bool,
+    #[serde(default)]
+    pub settings: ProjectSettings,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct RegistryFile {
+    #[serde(default)]
+    projects: Vec<RegisteredProject>,
+}
+
+/// Known projects, persisted as `projects.json` in the plugin's data directory.
+pub struct ProjectRegistry {
+    path: PathBuf,
+    projects: Mutex<Vec<RegisteredProject>>,
+}
+
+impl Default for ProjectRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProjectRegistry {
+    pub fn new() -> Self {
+        let mut dir = dirs::data_dir().unwrap_or_else(|| PathBuf::from("."));
+        dir.push("tauri-php-plugin");
+        Self::load(dir.join("projects.json"))
+    }
+
+    /// Loads the registry at `path`; a missing or corrupt file starts empty.
+    pub fn load(path: PathBuf) -> Self {
+        let projects = match std::fs::read_to_string(&path) {
+            Ok(content) => match serde_json::from_str::<RegistryFile>(&content) {
+                Ok(file) => file.projects,
+                Err(e) => {
+                    // Keep the damaged file for inspection instead of overwriting it on save
+                    let backup = path.with_extension("json.corrupt");
+                    log::warn!(
+                        "Project registry {} is corrupt ({}); moved to {}",
+                        path.display(),
+                        e,
+                        backup.display()
+                    );
+                    let _ = std::fs::rename(&path, &backup);
+                    Vec::new()
+                }
+            },
+            Err(_) => Vec::new(),
+        };
+
+        Self {
+            path,
+            projects: Mutex::new(projects),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn projects(&self) -> Vec<RegisteredProject> {
+        self.lock().clone()
+    }
+
+    pub fn find(&self, id: &str) -> Option<RegisteredProject> {
+        self.lock().iter().find(|project| project.id == id).cloned()
+    }
+
+    pub fn find_by_path(&self, path: &Path) -> Option<RegisteredProject> {
+        let path = path.to_string_lossy();
+        self.lock()
+            .iter()
+            .find(|project| project.path == path)
+            .cloned()
+    }
+
+    /// Imports the project at `path`, or renames it when it is already known.
+    pub fn register(&self, name: &str, path: &Path) -> Result<RegisteredProject> {
+        self.upsert(name, path, true)
+    }
+
+    /// Records a project found in the projects directory unless it is already
+    /// known, keeping its name.
+    pub fn ensure(&self, name: &str, path: &Path) -> Result<RegisteredProject> {
+        self.upsert(name, path, false)
+    }
+
+    pub fn update_settings(
+        &self,
+        id: &str,
+        settings: ProjectSettings,
+    ) -> Result<RegisteredProject> {
+        let mut projects = self.lock();
+        let project = projects
+            .iter_mut()
+            .find(|project| project.id == id)
+            .ok_or_else(|| Error::Config(format!("Project not found: {}", id)))?;
+        project.settings = settings;
+        let project = project.clone();
+
+        self.save(&projects)?;
+        Ok(project)
+    }
+
+    /// Puts back an entry removed earlier, keeping its id and settings.
+    pub fn restore(&self, project: RegisteredProject) -> Result<()> {
+        let mut projects = self.lock();
+        projects.retain(|p| p.id != project.id && p.path != project.path);
+        projects.push(project);
+        self.save(&projects)
+    }
+
+    /// Forgets the project at `path` without touching its files.
+    pub fn unregister(&self, path: &Path) -> Result<bool> {
+        let path = path.to_string_lossy();
+        let mut projects = self.lock();
+        let before = projects.len();
+        projects.retain(|project| project.path != path);
+        if projects.len() == before {
+            return Ok(false);
+        }
+        self.save(&projects)?;
+        Ok(true)
+    }
+
+    fn upsert(&self, name: &str, path: &Path, import: bool) -> Result<RegisteredProject> {
+        let path_str = path.to_string_lossy().to_string();
+        let mut projects = self.lock();
+
+        let project = match projects.iter_mut().find(|p| p.path == path_str) {
+            Some(existing) if !import || (existing.imported && existing.name == name) => {
+                return Ok(existing.clone())
+            }
+            Some(existing) => {
+                existing.name = name.to_string();
+                existing.imported = true;
+                existing.clone()
+            }
+            None => {
+                let project = RegisteredProject {
+                    id: uuid::Uuid::new_v4().to_string(),
+                    name: name.to_string(),
+                    path: path_str,
+                    registered_at: std::time::SystemTime::now()
+                        .duration_since(std::time::UNIX_EPOCH)
+                        .map(|d| d.as_secs())
+                        .unwrap_or_default(),
+                    imported: import,
+                    settings: ProjectSettings::default(),
+                };
+                projects.push(project.clone());
+                project
+            }
+        };
+
+        self.save(&projects)?;
+        Ok(project)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<RegisteredProject>> {
+        self.projects.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn save(&self, projects: &[RegisteredProject]) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let file = RegistryFile {
+            projects: projects.to_vec(),
+        };
+        // Written beside the target and renamed so a crash never truncates it
+        let temp_path = self
+            .path
+            .with_extension(format!("json.{}.tmp", uuid::Uuid::new_v4()));
+        std::fs::write(&temp_path, serde_json::to_string_pretty(&file)?)?;
+        std::fs::rename(&temp_path, &self.path).map_err(|e| {
+            let _ = std::fs::remove_file(&temp_path);
+            Error::Io(format!(
+                "Failed to save project registry {}: {}",
+                self.path.display(),
+                e
+            ))
+        })
+    }
+}
@@ -0,0 +1,98 @@
+use crate::models::{Framework, ProjectTemplate};
+use crate::Result;
+use std::fs;
+use std::path::Path;
+
+/// Starter templates offered by `create_project`, one per framework.
+pub fn templates() -> Vec<ProjectTemplate> {
+    [
+        Framework::Laravel,
+        Framework::Symfony,
+        Framework::Slim,
+        Framework::CodeIgniter,
+        Framework::CakePHP,
+        Framework::Yii,
+        Framework::Lumen,
+        Framework::ThinkPHP,
+        Framework::Zend,
+        Framework::Plain,
+    ]
+    .iter()
+    .filter_map(template_for)
+    .collect()
+}
+
+pub fn template_for(framework: &Framework) -> Option<ProjectTemplate> {
+    let (composer_package, git_url) = match framework {
+        Framework::Laravel => (
+            Some("laravel/laravel"),
+            Some("https://github.com/laravel/laravel.git"),
+        ),
+        Framework::Symfony => (
+            Some("symfony/skeleton"),
+            Some("https://github.com/symfony/skeleton.git"),
+        ),
+        Framework::Slim => (
+            Some("slim/slim-skeleton"),
+            Some("https://github.com/slimphp/Slim-Skeleton.git"),
+        ),
+        Framework::CodeIgniter => (
+            Some("codeigniter4/appstarter"),
+            Some("https://github.com/codeigniter4/appstarter.git"),
+        ),
+        Framework::CakePHP => (
+            Some("cakephp/app"),
+            Some("https://github.com/cakephp/app.git"),
+        ),
+        Framework::Yii => (
+            Some("yiisoft/yii2-app-basic"),
+            Some("https://github.com/yiisoft/yii2-app-basic.git"),
+        ),
+        Framework::Lumen => (
+            Some("laravel/lumen"),
+            Some("https://github.com/laravel/lumen.git"),
+        ),
+        Framework::ThinkPHP => (
+            Some("topthink/think"),
+            Some("https://github.com/top-think/think.git"),
+        ),
+        Framework::Zend => (
+            Some("laminas/laminas-mvc-skeleton"),
+            Some("https://github.com/laminas/laminas-mvc-skeleton.git"),
+        ),
+        Framework::Plain => (None, None),
+        Framework::Phalcon | Framework::Unknown => return None,
+    };
+
+    Some(ProjectTemplate {
+        framework: framework.clone(),
+        composer_package: composer_package.map(str::to_string),
+        git_url: git_url.map(str::to_string),
+        bundled: has_bundled_skeleton(framework),
+    })
+}
+
+fn has_bundled_skeleton(framework: &Framework) -> bool {
+    matches!(framework, Framework::Plain)
+}
+
+/// Writes the skeleton shipped with the plugin into the empty `destination`.
+pub fn write_bundled_skeleton(framework: &Framework, destination: &Path, name: &str) -> Result<()> {
+    if !has_bundled_skeleton(framework) {
+        return Err(crate::Error::Config(format!(
+            "No bundled skeleton for {}",
+            framework.as_str()
+        )));
+    }
+
+    fs::create_dir_all(destination)?;
+    fs::write(
+        destination.join("index.php"),
+        format!(
+            "<?php\n\n$name = '{}';\n?>\n<!DOCTYPE html>\n<html>\n<head>\n    <meta charset=\"utf-8\">\n    <title><?= htmlspecialchars($name) ?></title>\n</head>\n<body>\n    <h1><?= htmlspecialchars($name) ?></h1>\n    <p>PHP <?= PHP_VERSION ?></p>\n</body>\n</html>\n",
+            name.replace('\\', "\\\\").replace('\'', "\\'")
+        ),
+    )?;
+    fs::write(destination.join(".gitignore"), "/vendor/\n.env\n")?;
+    Ok(())
+}
@@ -0,0 +1,229 @@
+use crate::project_registry::RegisteredProject;
+use crate::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// A removed project kept in the plugin's trash until it is restored or purged.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TrashedProject {
+    pub trash_id: String,
+    pub name: String,
+    pub original_path: String,
+    /// Seconds since the Unix epoch
+    pub trashed_at: u64,
+    /// Registry entry at removal time, so a restore keeps the id and settings
+    #[serde(default)]
+    pub registered: Option<RegisteredProject>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct TrashIndex {
+    #[serde(default)]
+    projects: Vec<TrashedProject>,
+}
+
+/// Removed project folders, moved to `trash/<trash_id>` in the plugin's data
+/// directory and listed in `trash/trash.json`.
+pub struct ProjectTrash {
+    dir: PathBuf,
+    projects: Mutex<Vec<TrashedProject>>,
+}
+
+impl Default for ProjectTrash {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProjectTrash {
+    pub fn new() -> Self {
+        let mut dir = dirs::data_dir().unwrap_or_else(|| PathBuf::from("."));
+        dir.push("tauri-php-plugin");
+        Self::load(dir.join("trash"))
+    }
+
+    /// Loads the trash kept in `dir`; entries whose folder is gone are dropped.
+    pub fn load(dir: PathBuf) -> Self {
+        let projects = fs::read_to_string(dir.join("trash.json"))
+            .ok()
+            .and_then(
+                |content| match serde_json::from_str::<TrashIndex>(&content) {
+                    Ok(index) => Some(index.projects),
+                    Err(e) => {
+                        log::warn!("Ignoring corrupt trash index in {}: {}", dir.display(), e);
+                        None
+                    }
+                },
+            )
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|project| dir.join(&project.trash_id).is_dir())
+            .collect();
+
+        Self {
+            dir,
+            projects: Mutex::new(projects),
+        }
+    }
+
+    pub fn directory(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn projects(&self) -> Vec<TrashedProject> {
+        self.lock().clone()
+    }
+
+    /// Moves the folder at `path` into the trash.
+    pub fn trash(
+        &self,
+        name: &str,
+        path: &Path,
+        registered: Option<RegisteredProject>,
+    ) -> Result<TrashedProject> {
+        let trash_id = uuid::Uuid::new_v4().to_string();
+        fs::create_dir_all(&self.dir)?;
+        move_dir(path, &self.dir.join(&trash_id))?;
+
+        let project = TrashedProject {
+            trash_id,
+            name: name.to_string(),
+            original_path: path.to_string_lossy().to_string(),
+            trashed_at: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+            registered,
+        };
+
+        let mut projects = self.lock();
+        projects.push(project.clone());
+        self.save(&projects)?;
+        Ok(project)
+    }
+
+    /// Moves a trashed folder to `destination`, which must not exist.
+    pub fn restore(&self, trash_id: &str, destination: &Path) -> Result<TrashedProject> {
+        let mut projects = self.lock();
+        let index = Self::position(&projects, trash_id)?;
+        if destination.exists() {
+            return Err(Error::Config(format!(
+                "Restore destination already exists: {}",
+                destination.display()
+            )));
+        }
+
+        move_dir(&self.dir.join(trash_id), destination)?;
+        let project = projects.remove(index);
+        self.save(&projects)?;
+        Ok(project)
+    }
+
+    /// Deletes a trashed folder for good.
+    pub fn purge(&self, trash_id: &str) -> Result<TrashedProject> {
+        let mut projects = self.lock();
+        let index = Self::position(&projects, trash_id)?;
+
+        let folder = self.dir.join(trash_id);
+        if folder.exists() {
+            fs::remove_dir_all(&folder)?;
+        }
+        let project = projects.remove(index);
+        self.save(&projects)?;
+        Ok(project)
+    }
+
+    /// Deletes every trashed folder and returns how many there were.
+    pub fn purge_all(&self) -> Result<usize> {
+        let trash_ids: Vec<String> = self
+            .projects()
+            .into_iter()
+            .map(|project| project.trash_id)
+            .collect();
+        for trash_id in &trash_ids {
+            self.purge(trash_id)?;
+        }
+        Ok(trash_ids.len())
+    }
+
+    fn position(projects: &[TrashedProject], trash_id: &str) -> Result<usize> {
+        projects
+            .iter()
+            .position(|project| project.trash_id == trash_id)
+            .ok_or_else(|| Error::Config(format!("Trashed project not found: {}", trash_id)))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<TrashedProject>> {
+        self.projects.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn save(&self, projects: &[TrashedProject]) -> Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let path = self.dir.join("trash.json");
+        let index = TrashIndex {
+            projects: projects.to_vec(),
+        };
+        let temp_path = path.with_extension(format!("json.{}.tmp", uuid::Uuid::new_v4()));
+        fs::write(&temp_path, serde_json::to_string_pretty(&index)?)?;
+        fs::rename(&temp_path, &path).map_err(|e| {
+            let _ = fs::remove_file(&temp_path);
+            Error::Io(format!(
+                "Failed to save trash index {}: {}",
+                path.display(),
+                e
+            ))
+        })
+    }
+}
+
+/// Renames `from` to `to`, copying and deleting when they are on different
+/// file systems.
+fn move_dir(from: &Path, to: &Path) -> Result<()> {
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+
+    if let Err(e) = copy_dir(from, to) {
+        let _ = fs::remove_dir_all(to);
+        return Err(Error::Io(format!(
+            "Failed to move {} to {}: {}",
+            from.display(),
+            to.display(),
+            e
+        )));
+    }
+    fs::remove_dir_all(from)?;
+    Ok(())
+}
+
+fn copy_dir(from: &Path, to: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else if file_type.is_symlink() {
+            copy_symlink(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn copy_symlink(from: &Path, to: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(fs::read_link(from)?, to)
+}
+
+#[cfg(not(unix))]
+fn copy_symlink(from: &Path, to: &Path) -> std::io::Result<()> {
+    fs::copy(from, to).map(|_| ())
+}
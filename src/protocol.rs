@@ -0,0 +1,269 @@
+use crate::php_server::PhpServerManager;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tauri::http::header::{HeaderValue, CONTENT_TYPE, HOST, LOCATION};
+use tauri::http::{Request, Response, StatusCode, Uri};
+use tokio::sync::Mutex;
+
+/// Scheme registered by the plugin, e.g. `php://my-project/index.php`.
+pub const URI_SCHEME: &str = "php";
+
+/// Headers that describe a single connection and must not be copied between
+/// the upstream response and the webview.
+const HOP_BY_HOP_HEADERS: [&str; 6] = [
+    "connection",
+    "keep-alive",
+    "proxy-connection",
+    "transfer-encoding",
+    "upgrade",
+    "te",
+];
+
+/// A request to the custom scheme, split into the project it targets and the
+/// path that is forwarded to the project's server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProtocolTarget {
+    pub project: String,
+    pub path_and_query: String,
+    /// Origin (plus path prefix) the webview sees, used to rewrite redirects
+    pub public_base: String,
+    /// Set when the project was taken from the first path segment
+    pub prefix: Option<String>,
+}
+
+/// Resolves the project a custom-scheme URI refers to.
+///
+/// On macOS and Linux the webview sends `php://project-a/path`, so the host is
+/// the project. Windows and Android rewrite custom schemes to
+/// `http://php.localhost/path`, where the host carries no project, so the first
+/// path segment is used instead (`php://localhost/project-a/path` works on all
+/// platforms).
+pub fn parse_protocol_uri(uri: &Uri) -> Option<ProtocolTarget> {
+    let scheme = uri.scheme_str().unwrap_or(URI_SCHEME);
+    let host = uri.host().unwrap_or("localhost");
+    let authority = uri
+        .authority()
+        .map(|a| a.as_str())
+        .unwrap_or(host)
+        .to_string();
+    let path = uri.path();
+    let query = uri.query().map(|q| format!("?{}", q)).unwrap_or_default();
+
+    let host_carries_project =
+        !(host == "localhost" || host == format!("{}.localhost", URI_SCHEME) || host.is_empty());
+
+    if host_carries_project {
+        return Some(ProtocolTarget {
+            project: host.to_string(),
+            path_and_query: format!("{}{}", path, query),
+            public_base: format!("{}://{}", scheme, authority),
+            prefix: None,
+        });
+    }
+
+    let trimmed = path.trim_start_matches('/');
+    let (project, rest) = match trimmed.split_once('/') {
+        Some((project, rest)) => (project, format!("/{}", rest)),
+        None => (trimmed, "/".to_string()),
+    };
+    if project.is_empty() {
+        return None;
+    }
+
+    let prefix = format!("/{}", project);
+    Some(ProtocolTarget {
+        project: project.to_string(),
+        path_and_query: format!("{}{}", rest, query),
+        public_base: format!("{}://{}{}", scheme, authority, prefix),
+        prefix: Some(prefix),
+    })
+}
+
+/// Responses are buffered for Tauri's responder; larger ones are refused.
+pub const DEFAULT_MAX_RESPONSE_BYTES: usize = 256 * 1024 * 1024;
+
+/// What the custom scheme handler needs: the servers to forward to and one
+/// HTTP client, so connections to them are pooled across requests.
+#[derive(Clone)]
+pub struct ProtocolState {
+    server_manager: Arc<Mutex<PhpServerManager>>,
+    /// None when the client could not be built; requests then answer 500
+    client: Option<reqwest::Client>,
+    max_response_bytes: usize,
+}
+
+impl ProtocolState {
+    pub fn new(server_manager: Arc<Mutex<PhpServerManager>>) -> Self {
+        // Redirects go back to the webview, rewritten to the custom scheme
+        let client = reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .map_err(|e| log::error!("Failed to create the {}:// client: {}", URI_SCHEME, e))
+            .ok();
+        Self {
+            server_manager,
+            client,
+            max_response_bytes: DEFAULT_MAX_RESPONSE_BYTES,
+        }
+    }
+
+    pub fn set_max_response_bytes(&mut self, max_response_bytes: usize) {
+        self.max_response_bytes = max_response_bytes;
+    }
+}
+
+/// Why a request could not be forwarded.
+enum ForwardError {
+    Upstream(reqwest::Error),
+    TooLarge,
+}
+
+impl From<reqwest::Error> for ForwardError {
+    fn from(e: reqwest::Error) -> Self {
+        ForwardError::Upstream(e)
+    }
+}
+
+/// Handles one webview request to the custom scheme by forwarding it to the
+/// managed server of the target project.
+///
+/// Tauri's custom protocol responder takes a complete body, so the upstream
+/// response is read fully before it is handed to the webview.
+pub async fn handle_request(state: &ProtocolState, request: Request<Vec<u8>>) -> Response<Vec<u8>> {
+    let Some(client) = &state.client else {
+        return text_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "The HTTP client is not available",
+        );
+    };
+    let Some(target) = parse_protocol_uri(request.uri()) else {
+        return text_response(StatusCode::BAD_REQUEST, "Missing project name in URL");
+    };
+
+    let backend = state
+        .server_manager
+        .lock()
+        .await
+        .find_project_backend(&target.project);
+    let Some(backend) = backend else {
+        return text_response(
+            StatusCode::SERVICE_UNAVAILABLE,
+            &format!("No running PHP server for project '{}'", target.project),
+        );
+    };
+
+    match forward(client, backend, &target, request, state.max_response_bytes).await {
+        Ok(response) => response,
+        Err(ForwardError::TooLarge) => {
+            log::warn!(
+                "{}:// response for project {} exceeds {} bytes",
+                URI_SCHEME,
+                target.project,
+                state.max_response_bytes
+            );
+            text_response(
+                StatusCode::BAD_GATEWAY,
+                "Upstream response is too large to buffer",
+            )
+        }
+        Err(ForwardError::Upstream(e)) => {
+            log::warn!(
+                "{}:// request for project {} failed: {}",
+                URI_SCHEME,
+                target.project,
+                e
+            );
+            text_response(
+                StatusCode::BAD_GATEWAY,
+                "Upstream PHP server is not reachable",
+            )
+        }
+    }
+}
+
+async fn forward(
+    client: &reqwest::Client,
+    backend: SocketAddr,
+    target: &ProtocolTarget,
+    request: Request<Vec<u8>>,
+    max_response_bytes: usize,
+) -> std::result::Result<Response<Vec<u8>>, ForwardError> {
+    let (parts, body) = request.into_parts();
+    let mut headers = parts.headers;
+    headers.remove(HOST);
+    for name in HOP_BY_HOP_HEADERS {
+        headers.remove(name);
+    }
+    if let Ok(value) = HeaderValue::from_str(&target.project) {
+        headers.insert("x-forwarded-host", value);
+    }
+    headers.insert("x-forwarded-proto", HeaderValue::from_static(URI_SCHEME));
+    if let Some(prefix) = target.prefix.as_deref() {
+        if let Ok(value) = HeaderValue::from_str(prefix) {
+            headers.insert("x-forwarded-prefix", value);
+        }
+    }
+
+    let mut upstream_response = client
+        .request(
+            parts.method,
+            format!("http://{}{}", backend, target.path_and_query),
+        )
+        .headers(headers)
+        .body(body)
+        .send()
+        .await?;
+
+    let status = upstream_response.status();
+    let upstream_headers = upstream_response.headers().clone();
+    if upstream_response
+        .content_length()
+        .is_some_and(|length| length > max_response_bytes as u64)
+    {
+        return Err(ForwardError::TooLarge);
+    }
+    let mut body = Vec::new();
+    while let Some(chunk) = upstream_response.chunk().await? {
+        if body.len() + chunk.len() > max_response_bytes {
+            return Err(ForwardError::TooLarge);
+        }
+        body.extend_from_slice(&chunk);
+    }
+
+    let mut response = Response::new(body);
+    *response.status_mut() = status;
+    let upstream_origin = format!("http://{}", backend);
+    for (name, value) in upstream_headers.iter() {
+        if HOP_BY_HOP_HEADERS.contains(&name.as_str()) {
+            continue;
+        }
+        let value = if name == LOCATION {
+            rewrite_location(value, &upstream_origin, &target.public_base)
+        } else {
+            value.clone()
+        };
+        response.headers_mut().append(name.clone(), value);
+    }
+
+    Ok(response)
+}
+
+/// Points redirects at the custom scheme instead of the loopback port.
+fn rewrite_location(value: &HeaderValue, upstream_origin: &str, public_base: &str) -> HeaderValue {
+    value
+        .to_str()
+        .ok()
+        .and_then(|location| location.strip_prefix(upstream_origin))
+        .and_then(|rest| HeaderValue::from_str(&format!("{}{}", public_base, rest)).ok())
+        .unwrap_or_else(|| value.clone())
+}
+
+fn text_response(status: StatusCode, message: &str) -> Response<Vec<u8>> {
+    let mut response = Response::new(message.as_bytes().to_vec());
+    *response.status_mut() = status;
+    response.headers_mut().insert(
+        CONTENT_TYPE,
+        HeaderValue::from_static("text/plain; charset=utf-8"),
+    );
+    response
+}
@@ -0,0 +1,178 @@
+use crate::{Error, Result};
+use bytes::Bytes;
+use http_body_util::{combinators::BoxBody, BodyExt, Full};
+use hyper::body::Incoming;
+use hyper::header::{HeaderValue, HOST};
+use hyper::service::service_fn;
+use hyper::{Request, Response, StatusCode, Uri};
+use hyper_util::client::legacy::connect::HttpConnector;
+use hyper_util::client::legacy::Client;
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+use tokio_rustls::rustls::ServerConfig;
+use tokio_rustls::TlsAcceptor;
+
+pub type ProxyBody = BoxBody<Bytes, hyper::Error>;
+pub type UpstreamClient = Client<HttpConnector, Incoming>;
+
+pub fn upstream_client() -> UpstreamClient {
+    Client::builder(TokioExecutor::new()).build_http()
+}
+
+/// Forwards `req` to the plain HTTP server at `upstream`, streaming both bodies.
+///
+/// The original `Host` header is preserved and `X-Forwarded-*` headers are
+/// added so frameworks behind the proxy (e.g. Laravel's TrustProxies) can
+/// reconstruct the public URL. Upstream failures become a `502 Bad Gateway`.
+pub async fn forward_request(
+    client: &UpstreamClient,
+    upstream: SocketAddr,
+    client_addr: SocketAddr,
+    forwarded_proto: &'static str,
+    mut req: Request<Incoming>,
+) -> Response<ProxyBody> {
+    let path_and_query = req
+        .uri()
+        .path_and_query()
+        .map(|p| p.as_str())
+        .unwrap_or("/");
+    let upstream_uri = match format!("http://{}{}", upstream, path_and_query).parse::<Uri>() {
+        Ok(uri) => uri,
+        Err(e) => return error_response(StatusCode::BAD_REQUEST, &e.to_string()),
+    };
+    *req.uri_mut() = upstream_uri;
+
+    let headers = req.headers_mut();
+    if let Some(host) = headers.get(HOST).cloned() {
+        headers.insert("x-forwarded-host", host);
+    }
+    headers.insert(
+        "x-forwarded-proto",
+        HeaderValue::from_static(forwarded_proto),
+    );
+    if let Ok(client_ip) = HeaderValue::from_str(&client_addr.ip().to_string()) {
+        headers.insert("x-forwarded-for", client_ip);
+    }
+
+    match client.request(req).await {
+        Ok(response) => response.map(|body| body.boxed()),
+        Err(e) => {
+            log::warn!("Proxy request to {} failed: {}", upstream, e);
+            error_response(
+                StatusCode::BAD_GATEWAY,
+                "Upstream PHP server is not reachable",
+            )
+        }
+    }
+}
+
+pub fn error_response(status: StatusCode, message: &str) -> Response<ProxyBody> {
+    let body = Full::new(Bytes::from(message.to_string()))
+        .map_err(|never| match never {})
+        .boxed();
+    let mut response = Response::new(body);
+    *response.status_mut() = status;
+    response
+}
+
+/// A TLS-terminating reverse proxy in front of a single `php -S` instance.
+///
+/// The proxy runs on the Tokio runtime until `shutdown` is called or the value
+/// is dropped.
+pub struct HttpsProxy {
+    local_addr: SocketAddr,
+    shutdown_tx: Option<oneshot::Sender<()>>,
+    task: JoinHandle<()>,
+}
+
+impl HttpsProxy {
+    pub async fn start(
+        listen_addr: SocketAddr,
+        upstream: SocketAddr,
+        tls_config: Arc<ServerConfig>,
+    ) -> Result<Self> {
+        let listener = TcpListener::bind(listen_addr).await.map_err(|e| {
+            Error::PhpServer(format!(
+                "Failed to bind HTTPS proxy on {}: {}",
+                listen_addr, e
+            ))
+        })?;
+        let local_addr = listener.local_addr()?;
+        let acceptor = TlsAcceptor::from(tls_config);
+        let client = upstream_client();
+        let (shutdown_tx, mut shutdown_rx) = oneshot::channel::<()>();
+
+        let task = tokio::spawn(async move {
+            loop {
+                let (stream, client_addr) = tokio::select! {
+                    _ = &mut shutdown_rx => break,
+                    accepted = listener.accept() => match accepted {
+                        Ok(accepted) => accepted,
+                        Err(e) => {
+                            log::warn!("HTTPS proxy failed to accept connection: {}", e);
+                            continue;
+                        }
+                    },
+                };
+
+                let acceptor = acceptor.clone();
+                let client = client.clone();
+                tokio::spawn(async move {
+                    let tls_stream = match acceptor.accept(stream).await {
+                        Ok(tls_stream) => tls_stream,
+                        Err(e) => {
+                            log::debug!("TLS handshake with {} failed: {}", client_addr, e);
+                            return;
+                        }
+                    };
+
+                    let service = service_fn(move |req| {
+                        let client = client.clone();
+                        async move {
+                            Ok::<_, Infallible>(
+                                forward_request(&client, upstream, client_addr, "https", req).await,
+                            )
+                        }
+                    });
+
+                    if let Err(e) = hyper::server::conn::http1::Builder::new()
+                        .serve_connection(TokioIo::new(tls_stream), service)
+                        .with_upgrades()
+                        .await
+                    {
+                        log::debug!("HTTPS proxy connection from {} ended: {}", client_addr, e);
+                    }
+                });
+            }
+        });
+
+        log::info!("HTTPS proxy listening on {} for {}", local_addr, upstream);
+        Ok(Self {
+            local_addr,
+            shutdown_tx: Some(shutdown_tx),
+            task,
+        })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    pub fn shutdown(&mut self) {
+        if let Some(shutdown_tx) = self.shutdown_tx.take() {
+            let _ = shutdown_tx.send(());
+        }
+    }
+}
+
+impl Drop for HttpsProxy {
+    fn drop(&mut self) {
+        self.shutdown();
+        self.task.abort();
+    }
+}
@@ -0,0 +1,210 @@
+use crate::{Error, Result};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, RwLock};
+
+/// Storage for credentials such as SSH key passphrases and HTTPS tokens.
+///
+/// Secrets are kept apart from the plugin's `config.json`. Applications that
+/// want the OS keychain (or Stronghold, a vault, ...) implement this trait and
+/// install it with `PhpManager::set_secret_store`.
+pub trait SecretStore: Send + Sync {
+    fn get(&self, id: &str) -> Result<Option<String>>;
+
+    fn set(&self, id: &str, secret: &str) -> Result<()>;
+
+    /// Removes a secret, returning false when it did not exist.
+    fn delete(&self, id: &str) -> Result<bool>;
+}
+
+/// Keeps secrets for the lifetime of the process only.
+#[derive(Default)]
+pub struct MemorySecretStore {
+    secrets: Mutex<HashMap<String, String>>,
+}
+
+impl MemorySecretStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl SecretStore for MemorySecretStore {
+    fn get(&self, id: &str) -> Result<Option<String>> {
+        let secrets = self.secrets.lock().unwrap_or_else(|e| e.into_inner());
+        Ok(secrets.get(id).cloned())
+    }
+
+    fn set(&self, id: &str, secret: &str) -> Result<()> {
+        validate_secret_id(id)?;
+        let mut secrets = self.secrets.lock().unwrap_or_else(|e| e.into_inner());
+        secrets.insert(id.to_string(), secret.to_string());
+        Ok(())
+    }
+
+    fn delete(&self, id: &str) -> Result<bool> {
+        let mut secrets = self.secrets.lock().unwrap_or_else(|e| e.into_inner());
+        Ok(secrets.remove(id).is_some())
+    }
+}
+
+/// The default store: a JSON file in the plugin's data directory that only
+/// the current user can read. It is not encrypted; install a keychain-backed
+/// store where that matters.
+pub struct FileSecretStore {
+    path: PathBuf,
+    lock: Mutex<()>,
+}
+
+impl Default for FileSecretStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FileSecretStore {
+    pub fn new() -> Self {
+        let mut dir = dirs::data_dir().unwrap_or_else(|| PathBuf::from("."));
+        dir.push("tauri-php-plugin");
+        Self::with_path(dir.join("secrets.json"))
+    }
+
+    pub fn with_path(path: PathBuf) -> Self {
+        Self {
+            path,
+            lock: Mutex::new(()),
+        }
+    }
+
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    fn load(&self) -> Result<BTreeMap<String, String>> {
+        match std::fs::read_to_string(&self.path) {
+            Ok(content) => Ok(serde_json::from_str(&content)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn save(&self, secrets: &BTreeMap<String, String>) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        // Written next to the target and renamed so a crash never truncates the store
+        let temp_path = self.path.with_extension("json.tmp");
+        write_private(
+            &temp_path,
+            serde_json::to_string_pretty(secrets)?.as_bytes(),
+        )?;
+        std::fs::rename(&temp_path, &self.path)?;
+        Ok(())
+    }
+}
+
+impl SecretStore for FileSecretStore {
+    fn get(&self, id: &str) -> Result<Option<String>> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        Ok(self.load()?.remove(id))
+    }
+
+    fn set(&self, id: &str, secret: &str) -> Result<()> {
+        validate_secret_id(id)?;
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        let mut secrets = self.load()?;
+        secrets.insert(id.to_string(), secret.to_string());
+        self.save(&secrets)
+    }
+
+    fn delete(&self, id: &str) -> Result<bool> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        let mut secrets = self.load()?;
+        if secrets.remove(id).is_none() {
+            return Ok(false);
+        }
+        self.save(&secrets)?;
+        Ok(true)
+    }
+}
+
+/// A store that can be swapped after the managers holding it were created.
+#[derive(Clone)]
+pub struct SharedSecretStore {
+    inner: Arc<RwLock<Arc<dyn SecretStore>>>,
+}
+
+impl Default for SharedSecretStore {
+    fn default() -> Self {
+        Self::new(Arc::new(FileSecretStore::new()))
+    }
+}
+
+impl SharedSecretStore {
+    pub fn new(store: Arc<dyn SecretStore>) -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(store)),
+        }
+    }
+
+    /// Replaces the backing store for every holder of this handle.
+    pub fn replace(&self, store: Arc<dyn SecretStore>) {
+        *self.inner.write().unwrap_or_else(|e| e.into_inner()) = store;
+    }
+
+    fn current(&self) -> Arc<dyn SecretStore> {
+        self.inner.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+impl SecretStore for SharedSecretStore {
+    fn get(&self, id: &str) -> Result<Option<String>> {
+        self.current().get(id)
+    }
+
+    fn set(&self, id: &str, secret: &str) -> Result<()> {
+        self.current().set(id, secret)
+    }
+
+    fn delete(&self, id: &str) -> Result<bool> {
+        self.current().delete(id)
+    }
+}
+
+/// Looks up a secret that a request refers to, failing when it was never saved.
+pub fn require_secret(store: &dyn SecretStore, id: &str) -> Result<String> {
+    store
+        .get(id)?
+        .ok_or_else(|| Error::Config(format!("Secret not found: {}", id)))
+}
+
+fn validate_secret_id(id: &str) -> Result<()> {
+    if id.trim().is_empty() {
+        return Err(Error::Config("Secret id must not be empty".to_string()));
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn write_private(path: &std::path::Path, content: &[u8]) -> Result<()> {
+    use std::io::Write;
+    use std::os::unix::fs::OpenOptionsExt;
+
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)?;
+    file.write_all(content)?;
+    file.sync_all()?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn write_private(path: &std::path::Path, content: &[u8]) -> Result<()> {
+    // The per-user data directory is already private on Windows
+    std::fs::write(path, content)?;
+    Ok(())
+}
@@ -0,0 +1,445 @@
+use crate::models::{
+    ComposerAction, DatabaseConfig, Framework, FrameworkVersion, LaravelSetupOptions,
+};
+use crate::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Component, Path};
+
+/// File in a project's root that adjusts its setup pipeline.
+pub const SETUP_FILE: &str = "php-setup.json";
+
+/// Replaced by the project's absolute path in `set_env` values.
+pub const PROJECT_DIR: &str = "{project}";
+
+/// Ordered steps that prepare a project after it is cloned or created.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct SetupPipeline {
+    pub steps: Vec<PipelineStep>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PipelineStep {
+    /// Shown in the setup report and matched by `skip`; derived from the
+    /// action when omitted
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(flatten)]
+    pub action: SetupAction,
+    /// Run only when this project path exists
+    #[serde(default)]
+    pub if_exists: Option<String>,
+    /// Skip when this project path exists
+    #[serde(default)]
+    pub unless_exists: Option<String>,
+    /// Skip when this `.env` key has a value
+    #[serde(default)]
+    pub unless_env: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SetupAction {
+    /// Copies `from` to `to` unless `to` exists
+    CopyFile { from: String, to: String },
+    /// Runs Composer with the project's PHP; `args` holds options and packages
+    Composer {
+        #[serde(default)]
+        action: ComposerAction,
+        #[serde(default)]
+        dev: Option<bool>,
+        #[serde(default)]
+        args: Vec<String>,
+    },
+    /// Runs the framework's console script, e.g. `artisan`
+    Console { args: Vec<String> },
+    /// Creates a directory the application writes to
+    WritableDir { path: String },
+    /// Creates an empty file unless it exists
+    CreateFile { path: String },
+    /// Sets keys in a dotenv file, `.env` by default; `{project}` in a value
+    /// stands for the project's absolute path
+    SetEnv {
+        #[serde(default)]
+        file: Option<String>,
+        values: BTreeMap<String, String>,
+    },
+}
+
+/// Changes a project makes to its framework's pipeline in `php-setup.json`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SetupOverrides {
+    /// Replaces the framework's steps entirely
+    pub steps: Option<Vec<PipelineStep>>,
+    /// Names of steps to drop
+    pub skip: Vec<String>,
+    /// Steps to run after the others
+    pub append: Vec<PipelineStep>,
+}
+
+impl PipelineStep {
+    pub fn new(action: SetupAction) -> Self {
+        Self {
+            name: None,
+            action,
+            if_exists: None,
+            unless_exists: None,
+            unless_env: None,
+        }
+    }
+
+    fn named(mut self, name: &str) -> Self {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    fn if_exists(mut self, path: &str) -> Self {
+        self.if_exists = Some(path.to_string());
+        self
+    }
+
+    fn unless_exists(mut self, path: &str) -> Self {
+        self.unless_exists = Some(path.to_string());
+        self
+    }
+
+    fn unless_env(mut self, key: &str) -> Self {
+        self.unless_env = Some(key.to_string());
+        self
+    }
+
+    pub fn display_name(&self) -> String {
+        if let Some(name) = &self.name {
+            return name.clone();
+        }
+        match &self.action {
+            SetupAction::CopyFile { to, .. } => to.clone(),
+            SetupAction::Composer { action, .. } => {
+                format!("composer {}", action.as_str())
+            }
+            SetupAction::Console { args } => args.first().cloned().unwrap_or_default(),
+            SetupAction::WritableDir { path } | SetupAction::CreateFile { path } => path.clone(),
+            SetupAction::SetEnv { .. } => "env".to_string(),
+        }
+    }
+
+    /// A shell-like summary of the step, for dry runs and `FrameworkInfo`.
+    pub fn describe(&self, console_script: Option<&str>) -> String {
+        let mut description = match &self.action {
+            SetupAction::CopyFile { from, to } => format!("cp {} {}", from, to),
+            SetupAction::Composer { action, dev, args } => {
+                let mut parts = vec!["composer", action.as_str()];
+                match (action, dev) {
+                    (ComposerAction::Require | ComposerAction::Remove, Some(true)) => {
+                        parts.push("--dev")
+                    }
+                    (ComposerAction::Require | ComposerAction::Remove, _) => {}
+                    (_, Some(false)) => parts.push("--no-dev"),
+                    _ => {}
+                }
+                parts.extend(args.iter().map(String::as_str));
+                parts.join(" ")
+            }
+            SetupAction::Console { args } => format!(
+                "php {} {}",
+                console_script.unwrap_or("<console>"),
+                args.join(" ")
+            ),
+            SetupAction::WritableDir { path } => format!("mkdir -p {}", path),
+            SetupAction::CreateFile { path } => format!("touch {}", path),
+            SetupAction::SetEnv { file, values } => format!(
+                "set {} in {}",
+                values.keys().cloned().collect::<Vec<_>>().join(", "),
+                file.as_deref().unwrap_or(".env")
+            ),
+        };
+
+        let mut conditions = Vec::new();
+        if let Some(path) = &self.if_exists {
+            conditions.push(format!("if {} exists", path));
+        }
+        if let Some(path) = &self.unless_exists {
+            conditions.push(format!("unless {} exists", path));
+        }
+        if let Some(key) = &self.unless_env {
+            conditions.push(format!("unless {} is set", key));
+        }
+        if !conditions.is_empty() {
+            description.push_str(&format!(" ({})", conditions.join(", ")));
+        }
+        description
+    }
+
+    /// Rejects a step whose files resolve outside the project through a
+    /// symlink, which the textual check in `validate` cannot see.
+    pub fn check_resolved_paths(&self, project_path: &Path) -> Result<()> {
+        let root = fs::canonicalize(project_path)?;
+        let paths: Vec<&str> = match &self.action {
+            SetupAction::CopyFile { from, to } => vec![from, to],
+            SetupAction::WritableDir { path } | SetupAction::CreateFile { path } => vec![path],
+            SetupAction::SetEnv { file, .. } => vec![file.as_deref().unwrap_or(".env")],
+            SetupAction::Composer { .. } | SetupAction::Console { .. } => Vec::new(),
+        };
+        match paths.into_iter().find(|path| !resolves_inside(&root, path)) {
+            Some(path) => Err(Error::Config(format!(
+                "Setup step {} uses a path that leads outside the project: {}",
+                self.display_name(),
+                path
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// Rejects paths that are absolute or leave the project.
+    fn validate(&self) -> Result<()> {
+        let mut paths: Vec<&str> = [&self.if_exists, &self.unless_exists]
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+            .collect();
+        match &self.action {
+            SetupAction::CopyFile { from, to } => paths.extend([from.as_str(), to.as_str()]),
+            SetupAction::WritableDir { path } | SetupAction::CreateFile { path } => {
+                paths.push(path)
+            }
+            SetupAction::SetEnv { file, .. } => paths.extend(file.as_deref()),
+            SetupAction::Composer { .. } | SetupAction::Console { .. } => {}
+        }
+
+        for path in paths {
+            let relative = Path::new(path);
+            let inside = !path.is_empty()
+                && relative
+                    .components()
+                    .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+            if !inside {
+                return Err(Error::Config(format!(
+                    "Setup step {} uses a path outside the project: {}",
+                    self.display_name(),
+                    path
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl SetupPipeline {
+    pub fn describe(&self, console_script: Option<&str>) -> Vec<String> {
+        self.steps
+            .iter()
+            .map(|step| step.describe(console_script))
+            .collect()
+    }
+
+    /// Applies a project's `php-setup.json` to this pipeline.
+    pub fn apply_overrides(&mut self, overrides: SetupOverrides) -> Result<()> {
+        if let Some(steps) = overrides.steps {
+            self.steps = steps;
+        }
+        self.steps
+            .retain(|step| !overrides.skip.contains(&step.display_name()));
+        self.steps.extend(overrides.append);
+        self.validate()
+    }
+
+    /// Rejects steps with paths that are absolute or leave the project.
+    pub fn validate(&self) -> Result<()> {
+        self.steps.iter().try_for_each(PipelineStep::validate)
+    }
+}
+
+/// Whether `relative` stays below the canonical `root` with every existing
+/// component resolved; a dangling symlink counts as leaving it.
+fn resolves_inside(root: &Path, relative: &str) -> bool {
+    let mut current = root.to_path_buf();
+    for component in Path::new(relative).components() {
+        match component {
+            Component::Normal(name) => current.push(name),
+            Component::CurDir => continue,
+            _ => return false,
+        }
+        if fs::symlink_metadata(&current).is_ok() {
+            match fs::canonicalize(&current) {
+                Ok(resolved) => current = resolved,
+                Err(_) => return false,
+            }
+        }
+    }
+    current.starts_with(root)
+}
+
+/// Reads `php-setup.json` from a project; None when there is none.
+pub fn load_overrides(project_path: &Path) -> Result<Option<SetupOverrides>> {
+    let path = project_path.join(SETUP_FILE);
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    serde_json::from_str(&content)
+        .map(Some)
+        .map_err(|e| Error::Config(format!("Invalid {}: {}", SETUP_FILE, e)))
+}
+
+/// The framework's own setup steps. Laravel's database and artisan steps
+/// follow `options`; steps that depend on the framework's version are chosen
+/// from `version`, or left to the project's layout when it is unknown.
+pub fn default_pipeline(
+    framework: &Framework,
+    version: Option<&FrameworkVersion>,
+    options: &LaravelSetupOptions,
+) -> SetupPipeline {
+    let composer_install = || {
+        PipelineStep::new(SetupAction::Composer {
+            action: ComposerAction::Install,
+            dev: Some(false),
+            args: Vec::new(),
+        })
+        .if_exists("composer.json")
+    };
+    let console = |args: &[&str]| {
+        PipelineStep::new(SetupAction::Console {
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+        })
+    };
+    let copy = |from: &str, to: &str| {
+        PipelineStep::new(SetupAction::CopyFile {
+            from: from.to_string(),
+            to: to.to_string(),
+        })
+        .if_exists(from)
+    };
+    let writable = |path: &str| {
+        PipelineStep::new(SetupAction::WritableDir {
+            path: path.to_string(),
+        })
+    };
+
+    let before = |major, minor| version.is_some_and(|version| version.is_before(major, minor));
+
+    let steps = match framework {
+        Framework::Laravel => {
+            let mut steps = vec![composer_install(), copy(".env.example", ".env")];
+            if let Some(database) = &options.database {
+                steps.extend(database_steps(database));
+            }
+            steps.push(console(&["key:generate", "--force"]).unless_env("APP_KEY"));
+            // storage:link came with Laravel 5.3 and optimize:clear with 5.7
+            if options.storage_link && !before(5, 3) {
+                steps.push(console(&["storage:link"]).unless_exists("public/storage"));
+            }
+            if options.migrate {
+                let mut args = vec!["migrate", "--force"];
+                if options.seed {
+                    args.push("--seed");
+                }
+                steps.push(console(&args));
+            }
+            if options.clear_caches && !before(5, 7) {
+                steps.push(console(&["optimize:clear"]));
+            }
+            steps
+        }
+        Framework::Lumen => vec![composer_install(), copy(".env.example", ".env")],
+        Framework::Symfony => vec![composer_install()],
+        // ThinkPHP 3.x keeps its runtime files under Application/
+        Framework::ThinkPHP if before(5, 0) => {
+            vec![composer_install(), writable("Application/Runtime")]
+        }
+        Framework::ThinkPHP if version.is_some() => vec![
+            composer_install(),
+            copy(".example.env", ".env"),
+            writable("runtime"),
+        ],
+        Framework::ThinkPHP => vec![
+            composer_install(),
+            copy(".example.env", ".env"),
+            writable("Application/Runtime").if_exists("Application"),
+            writable("runtime").unless_exists("Application"),
+        ],
+        Framework::CodeIgniter if before(4, 0) => {
+            vec![composer_install(), writable("application/cache")]
+        }
+        Framework::CodeIgniter if version.is_some() => vec![
+            composer_install(),
+            copy("env", ".env"),
+            writable("writable"),
+        ],
+        Framework::CodeIgniter => vec![
+            composer_install(),
+            copy("env", ".env"),
+            writable("writable").if_exists("spark"),
+        ],
+        Framework::CakePHP if before(3, 0) => vec![composer_install(), writable("app/tmp")],
+        Framework::CakePHP => vec![
+            composer_install(),
+            copy("config/app_local.example.php", "config/app_local.php"),
+            writable("tmp"),
+            writable("logs"),
+        ],
+        Framework::Yii if before(2, 0) => vec![
+            composer_install(),
+            writable("protected/runtime"),
+            writable("assets"),
+        ],
+        Framework::Yii => vec![
+            composer_install(),
+            writable("runtime"),
+            writable("web/assets").if_exists("web"),
+        ],
+        Framework::Zend
+        | Framework::Slim
+        | Framework::Phalcon
+        | Framework::Plain
+        | Framework::Unknown => vec![composer_install()],
+    };
+    SetupPipeline { steps }
+}
+
+/// Writes a Laravel database connection into `.env`; SQLite also gets its
+/// database file.
+fn database_steps(database: &DatabaseConfig) -> Vec<PipelineStep> {
+    let connection = database.connection.trim().to_string();
+    let mut values = BTreeMap::new();
+    values.insert("DB_CONNECTION".to_string(), connection.clone());
+    let mut steps = Vec::new();
+
+    if connection == "sqlite" {
+        let file = database
+            .database
+            .clone()
+            .unwrap_or_else(|| "database/database.sqlite".to_string());
+        if Path::new(&file).is_absolute() {
+            // A database outside the project is used as is, not created
+            values.insert("DB_DATABASE".to_string(), file);
+        } else {
+            // Absolute, as relative paths resolve against the server's working directory
+            values.insert(
+                "DB_DATABASE".to_string(),
+                format!("{}/{}", PROJECT_DIR, file),
+            );
+            steps.push(
+                PipelineStep::new(SetupAction::CreateFile { path: file }).named("database file"),
+            );
+        }
+    } else {
+        let fields = [
+            ("DB_HOST", database.host.clone()),
+            ("DB_PORT", database.port.map(|port| port.to_string())),
+            ("DB_DATABASE", database.database.clone()),
+            ("DB_USERNAME", database.username.clone()),
+            ("DB_PASSWORD", database.password.clone()),
+        ];
+        for (key, value) in fields {
+            if let Some(value) = value {
+                values.insert(key.to_string(), value);
+            }
+        }
+    }
+
+    steps.push(PipelineStep::new(SetupAction::SetEnv { file: None, values }).named("database"));
+    steps
+}
@@ -0,0 +1,227 @@
+use crate::{Error, Result};
+use rcgen::{
+    BasicConstraints, Certificate, CertificateParams, DistinguishedName, DnType,
+    ExtendedKeyUsagePurpose, IsCa, KeyPair, KeyUsagePurpose,
+};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tokio_rustls::rustls::crypto::ring as ring_provider;
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer};
+use tokio_rustls::rustls::server::{ClientHello, ResolvesServerCert};
+use tokio_rustls::rustls::sign::CertifiedKey;
+use tokio_rustls::rustls::ServerConfig;
+
+const CA_COMMON_NAME: &str = "Tauri PHP Plugin Local CA";
+const CA_CERT_FILE: &str = "ca.pem";
+const CA_KEY_FILE: &str = "ca-key.pem";
+
+/// Leaf certificates are kept short-lived because some platforms (macOS, iOS)
+/// reject TLS server certificates valid for more than 825 days, even when they
+/// chain to a user-trusted root.
+const LEAF_VALIDITY_DAYS: i64 = 365;
+
+/// A self-signed certificate authority stored in the plugin data directory.
+///
+/// The CA is generated on first use and reused afterwards, so users only have
+/// to trust `ca.pem` once. Server certificates for individual host names are
+/// issued on demand and kept in memory.
+pub struct LocalCertificateAuthority {
+    certs_dir: PathBuf,
+    ca_cert: Certificate,
+    ca_cert_pem: String,
+    ca_key: KeyPair,
+}
+
+impl LocalCertificateAuthority {
+    /// Loads the CA from `certs_dir`, generating and saving a new one if none exists yet.
+    pub fn load_or_create(certs_dir: &Path) -> Result<Self> {
+        let cert_path = certs_dir.join(CA_CERT_FILE);
+        let key_path = certs_dir.join(CA_KEY_FILE);
+
+        let (ca_key, ca_cert_pem) = if cert_path.exists() && key_path.exists() {
+            let key_pem = fs::read_to_string(&key_path)?;
+            let ca_key = KeyPair::from_pem(&key_pem).map_err(tls_error)?;
+            (ca_key, fs::read_to_string(&cert_path)?)
+        } else {
+            log::info!(
+                "Generating local certificate authority in {}",
+                certs_dir.display()
+            );
+            fs::create_dir_all(certs_dir)?;
+
+            let ca_key = KeyPair::generate().map_err(tls_error)?;
+            let ca_cert = Self::ca_params().self_signed(&ca_key).map_err(tls_error)?;
+            let ca_cert_pem = ca_cert.pem();
+
+            fs::write(&cert_path, &ca_cert_pem)?;
+            fs::write(&key_path, ca_key.serialize_pem())?;
+
+            // The CA key can sign certificates for any host, keep it private
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                fs::set_permissions(&key_path, fs::Permissions::from_mode(0o600))?;
+            }
+
+            (ca_key, ca_cert_pem)
+        };
+
+        // Re-signing the same parameters with the stored key yields an issuer with
+        // the subject and key identifier of the certificate saved on disk
+        let ca_cert = Self::ca_params().self_signed(&ca_key).map_err(tls_error)?;
+
+        Ok(Self {
+            certs_dir: certs_dir.to_path_buf(),
+            ca_cert,
+            ca_cert_pem,
+            ca_key,
+        })
+    }
+
+    fn ca_params() -> CertificateParams {
+        let mut params = CertificateParams::default();
+        let mut distinguished_name = DistinguishedName::new();
+        distinguished_name.push(DnType::CommonName, CA_COMMON_NAME);
+        params.distinguished_name = distinguished_name;
+        params.is_ca = IsCa::Ca(BasicConstraints::Constrained(0));
+        params.key_usages = vec![
+            KeyUsagePurpose::KeyCertSign,
+            KeyUsagePurpose::CrlSign,
+            KeyUsagePurpose::DigitalSignature,
+        ];
+        params
+    }
+
+    /// Path of the CA certificate that has to be trusted by browsers and the OS.
+    pub fn ca_cert_path(&self) -> PathBuf {
+        self.certs_dir.join(CA_CERT_FILE)
+    }
+
+    pub fn ca_cert_pem(&self) -> &str {
+        &self.ca_cert_pem
+    }
+
+    /// Issues a server certificate for `host`, valid for `LEAF_VALIDITY_DAYS`.
+    ///
+    /// `localhost` and the loopback addresses are always included as subject
+    /// alternative names so the certificate also works when a page is opened
+    /// through a different loopback name.
+    pub fn issue_server_certificate(&self, host: &str) -> Result<CertifiedKey> {
+        let mut names = vec![host.to_string()];
+        for loopback in ["localhost", "127.0.0.1", "::1"] {
+            if !names.iter().any(|name| name == loopback) {
+                names.push(loopback.to_string());
+            }
+        }
+
+        let mut params = CertificateParams::new(names).map_err(tls_error)?;
+        let mut distinguished_name = DistinguishedName::new();
+        distinguished_name.push(DnType::CommonName, host);
+        params.distinguished_name = distinguished_name;
+        params.use_authority_key_identifier_extension = true;
+        params.key_usages = vec![
+            KeyUsagePurpose::DigitalSignature,
+            KeyUsagePurpose::KeyEncipherment,
+        ];
+        params.extended_key_usages = vec![ExtendedKeyUsagePurpose::ServerAuth];
+
+        let now = time::OffsetDateTime::now_utc();
+        params.not_before = now - time::Duration::days(1);
+        params.not_after = now + time::Duration::days(LEAF_VALIDITY_DAYS);
+
+        let leaf_key = KeyPair::generate().map_err(tls_error)?;
+        let leaf_cert = params
+            .signed_by(&leaf_key, &self.ca_cert, &self.ca_key)
+            .map_err(tls_error)?;
+
+        let private_key = PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(leaf_key.serialize_der()));
+        let signing_key = ring_provider::sign::any_supported_type(&private_key)
+            .map_err(|e| Error::Tls(e.to_string()))?;
+
+        Ok(CertifiedKey::new(
+            vec![
+                leaf_cert.der().clone(),
+                CertificateDer::from(self.ca_cert.der().to_vec()),
+            ],
+            signing_key,
+        ))
+    }
+}
+
+/// Picks a certificate by the SNI name of each connection, issuing and caching
+/// one per host on first use. Clients that send no SNI (for example when
+/// connecting to an IP address) get the certificate for `default_host`.
+pub struct PerHostCertResolver {
+    authority: Arc<LocalCertificateAuthority>,
+    default_host: String,
+    issued: Mutex<HashMap<String, Arc<CertifiedKey>>>,
+}
+
+impl PerHostCertResolver {
+    pub fn new(authority: Arc<LocalCertificateAuthority>, default_host: &str) -> Self {
+        Self {
+            authority,
+            default_host: default_host.to_string(),
+            issued: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn certificate_for(&self, host: &str) -> Option<Arc<CertifiedKey>> {
+        let mut issued = self.issued.lock().ok()?;
+        if let Some(certified_key) = issued.get(host) {
+            return Some(certified_key.clone());
+        }
+
+        match self.authority.issue_server_certificate(host) {
+            Ok(certified_key) => {
+                let certified_key = Arc::new(certified_key);
+                issued.insert(host.to_string(), certified_key.clone());
+                Some(certified_key)
+            }
+            Err(e) => {
+                log::warn!("Failed to issue certificate for {}: {}", host, e);
+                None
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for PerHostCertResolver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PerHostCertResolver")
+            .field("default_host", &self.default_host)
+            .finish()
+    }
+}
+
+impl ResolvesServerCert for PerHostCertResolver {
+    fn resolve(&self, client_hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        let host = client_hello
+            .server_name()
+            .map(|name| name.to_ascii_lowercase())
+            .unwrap_or_else(|| self.default_host.clone());
+        self.certificate_for(&host)
+    }
+}
+
+/// Builds a rustls server configuration that serves certificates issued by
+/// `authority`. Only HTTP/1.1 is offered since that is what the proxy speaks.
+pub fn server_config(
+    authority: Arc<LocalCertificateAuthority>,
+    default_host: &str,
+) -> Result<Arc<ServerConfig>> {
+    let mut config =
+        ServerConfig::builder_with_provider(Arc::new(ring_provider::default_provider()))
+            .with_safe_default_protocol_versions()
+            .map_err(|e| Error::Tls(e.to_string()))?
+            .with_no_client_auth()
+            .with_cert_resolver(Arc::new(PerHostCertResolver::new(authority, default_host)));
+    config.alpn_protocols = vec![b"http/1.1".to_vec()];
+    Ok(Arc::new(config))
+}
+
+fn tls_error(err: rcgen::Error) -> Error {
+    Error::Tls(err.to_string())
+}
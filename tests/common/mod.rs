@@ -2,6 +2,15 @@ use std::cell::RefCell;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
+use std::sync::Arc;
+use tauri_plugin_php::composer::ComposerManager;
+use tauri_plugin_php::models::Config;
+use tauri_plugin_php::php_binary::PhpBinaryManager;
+use tauri_plugin_php::project_manager::ProjectManager;
+use tauri_plugin_php::project_registry::ProjectRegistry;
+use tauri_plugin_php::project_trash::ProjectTrash;
+use tauri_plugin_php::secret_store::{FileSecretStore, SharedSecretStore};
+use tauri_plugin_php::PhpManager;
 use tempfile::TempDir;
 
 /// 创建临时测试目录
@@ -9,6 +18,53 @@ pub fn create_temp_dir() -> TempDir {
     tempfile::tempdir().expect("Failed to create temp directory")
 }
 
+/// 创建只使用 `base` 的项目管理器：项目目录、登记表、回收站、PHP、Composer
+/// 和密钥都不会写入真实的数据目录
+pub fn isolated_project_manager(base: &Path) -> ProjectManager {
+    let mut manager = ProjectManager::new();
+    manager.set_projects_directory(base.join("projects"));
+    manager.set_secret_store(SharedSecretStore::new(Arc::new(
+        FileSecretStore::with_path(base.join("secrets.json")),
+    )));
+    isolate_data_dirs(&mut manager, base, &Config::default());
+    manager
+}
+
+/// 创建只使用 `base` 的 PhpManager
+pub fn isolated_php_manager(base: &Path) -> PhpManager {
+    let manager = PhpManager::new();
+    lock(&manager.project_manager).set_projects_directory(base.join("projects"));
+    isolate_php_manager(&manager, base);
+    manager
+}
+
+/// 让刚创建的 PhpManager 只使用 `base` 中的数据目录，保留其配置的项目目录
+pub fn isolate_php_manager(manager: &PhpManager, base: &Path) {
+    manager.set_secret_store(Arc::new(FileSecretStore::with_path(
+        base.join("secrets.json"),
+    )));
+    let config = lock(&manager.config).clone();
+    isolate_data_dirs(&mut lock(&manager.project_manager), base, &config);
+    lock(&manager.binary_manager).set_php_directory(base.join("php-binaries"));
+    lock(&manager.composer_manager).set_composer_directory(base.join("composer"));
+}
+
+fn lock<T>(mutex: &tokio::sync::Mutex<T>) -> tokio::sync::MutexGuard<'_, T> {
+    mutex.try_lock().expect("PhpManager is in use")
+}
+
+fn isolate_data_dirs(manager: &mut ProjectManager, base: &Path, config: &Config) {
+    let mut php_binaries = PhpBinaryManager::with_config(config);
+    php_binaries.set_php_directory(base.join("php-binaries"));
+    let mut composer = ComposerManager::new();
+    composer.set_composer_directory(base.join("composer"));
+
+    manager.set_registry(ProjectRegistry::load(base.join("projects.json")));
+    manager.set_trash(ProjectTrash::load(base.join("trash")));
+    manager.set_php_binaries(php_binaries);
+    manager.set_composer(composer);
+}
+
 /// 创建模拟的 PHP 项目结构
 pub fn create_mock_php_project(base_dir: &Path, framework: &str) -> PathBuf {
     let project_dir = base_dir.join(format!("test_project_{}", framework));
@@ -269,6 +325,111 @@ pub fn create_mock_git_repo(repo_dir: &Path, framework: &str) {
     }
 }
 
+/// 使用 git2 创建真实的 Git 仓库作为克隆测试的远程仓库
+///
+/// 默认分支 main 包含框架特征文件，develop 分支额外包含 develop.txt
+pub fn create_git_fixture_repo(repo_dir: &Path, framework: &str) -> PathBuf {
+    create_mock_git_repo(repo_dir, framework);
+    fs::remove_dir_all(repo_dir.join(".git")).unwrap();
+
+    let mut init_options = git2::RepositoryInitOptions::new();
+    init_options.initial_head("main");
+    let repo = git2::Repository::init_opts(repo_dir, &init_options).unwrap();
+    let signature = git2::Signature::now("Test", "test@example.com").unwrap();
+
+    let commit_all = |message: &str, parents: &[&git2::Commit]| {
+        let mut index = repo.index().unwrap();
+        index
+            .add_all(["*"].iter(), git2::IndexAddOption::DEFAULT, None)
+            .unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            message,
+            &tree,
+            parents,
+        )
+        .unwrap()
+    };
+
+    let initial = commit_all("Initial commit", &[]);
+    let initial = repo.find_commit(initial).unwrap();
+
+    // 在 develop 分支上提交额外文件，然后切回 main
+    repo.branch("develop", &initial, false).unwrap();
+    repo.set_head("refs/heads/develop").unwrap();
+    fs::write(repo_dir.join("develop.txt"), "develop").unwrap();
+    commit_all("Develop commit", &[&initial]);
+
+    repo.set_head("refs/heads/main").unwrap();
+    repo.checkout_head(Some(git2::build::CheckoutBuilder::new().force()))
+        .unwrap();
+    fs::remove_file(repo_dir.join("develop.txt")).ok();
+
+    // 初始提交打上 v1.0.0 标签，main 再前进一个提交
+    repo.tag(
+        "v1.0.0",
+        initial.as_object(),
+        &signature,
+        "Release 1.0.0",
+        false,
+    )
+    .unwrap();
+    fs::write(repo_dir.join("CHANGELOG.md"), "# Changelog").unwrap();
+    commit_all("Add changelog", &[&initial]);
+
+    repo_dir.to_path_buf()
+}
+
+/// 在仓库当前分支上写入文件并提交，返回提交 id
+pub fn commit_file(repo_dir: &Path, file: &str, content: &str, message: &str) -> git2::Oid {
+    let repo = git2::Repository::open(repo_dir).unwrap();
+    fs::write(repo_dir.join(file), content).unwrap();
+
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new(file)).unwrap();
+    index.write().unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let signature = git2::Signature::now("Test", "test@example.com").unwrap();
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    repo.commit(
+        Some("HEAD"),
+        &signature,
+        &signature,
+        message,
+        &tree,
+        &[&head],
+    )
+    .unwrap()
+}
+
+/// 将 `submodule_repo` 作为子模块添加到 `repo_dir` 的 `path` 并提交
+pub fn add_git_submodule(repo_dir: &Path, submodule_repo: &Path, path: &str) {
+    let repo = git2::Repository::open(repo_dir).unwrap();
+    let url = url::Url::from_directory_path(submodule_repo).unwrap();
+
+    let mut submodule = repo.submodule(url.as_str(), Path::new(path), true).unwrap();
+    submodule.clone(None).unwrap();
+    submodule.add_finalize().unwrap();
+
+    let signature = git2::Signature::now("Test", "test@example.com").unwrap();
+    let mut index = repo.index().unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    repo.commit(
+        Some("HEAD"),
+        &signature,
+        &signature,
+        &format!("Add submodule {}", path),
+        &tree,
+        &[&head],
+    )
+    .unwrap();
+}
+
 /// 检查端口是否可用
 pub fn is_port_available(port: u16) -> bool {
     std::net::TcpListener::bind(("127.0.0.1", port)).is_ok()
@@ -382,6 +543,81 @@ mod tests {
         assert!(repo_dir.join("artisan").exists());
     }
 
+    #[test]
+    fn test_create_git_fixture_repo() {
+        let temp_dir = create_temp_dir();
+        let repo_dir = create_git_fixture_repo(&temp_dir.path().join("fixture"), "laravel");
+        let repo = git2::Repository::open(&repo_dir).unwrap();
+        assert_eq!(repo.head().unwrap().shorthand(), Some("main"));
+        assert!(repo.find_branch("develop", git2::BranchType::Local).is_ok());
+        assert!(repo_dir.join("artisan").exists());
+        assert!(!repo_dir.join("develop.txt").exists());
+        assert!(repo_dir.join("CHANGELOG.md").exists());
+        assert!(repo.revparse_single("refs/tags/v1.0.0").is_ok());
+    }
+
+    #[test]
+    fn test_commit_file() {
+        let temp_dir = create_temp_dir();
+        let repo_dir = create_git_fixture_repo(&temp_dir.path().join("fixture"), "plain");
+
+        let oid = commit_file(&repo_dir, "notes.txt", "notes", "Add notes");
+
+        let repo = git2::Repository::open(&repo_dir).unwrap();
+        assert_eq!(repo.head().unwrap().target(), Some(oid));
+        assert!(repo.statuses(None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_add_git_submodule() {
+        let temp_dir = create_temp_dir();
+        let theme = create_git_fixture_repo(&temp_dir.path().join("theme"), "plain");
+        let app = create_git_fixture_repo(&temp_dir.path().join("app"), "laravel");
+
+        add_git_submodule(&app, &theme, "themes/default");
+
+        let repo = git2::Repository::open(&app).unwrap();
+        let submodules = repo.submodules().unwrap();
+        assert_eq!(submodules.len(), 1);
+        assert_eq!(submodules[0].path(), Path::new("themes/default"));
+        assert!(app.join(".gitmodules").exists());
+        assert!(repo.statuses(None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_isolated_project_manager() {
+        let temp_dir = create_temp_dir();
+        create_mock_php_project(&temp_dir.path().join("projects"), "plain");
+
+        // 项目登记写入临时目录
+        let manager = isolated_project_manager(temp_dir.path());
+        assert_eq!(
+            manager.projects_directory(),
+            temp_dir.path().join("projects")
+        );
+        assert_eq!(manager.list_projects().unwrap().len(), 1);
+        assert!(temp_dir.path().join("projects.json").exists());
+    }
+
+    #[tokio::test]
+    async fn test_isolated_php_manager() {
+        let temp_dir = create_temp_dir();
+        create_mock_php_project(&temp_dir.path().join("projects"), "plain");
+
+        let manager = isolated_php_manager(temp_dir.path());
+        let project_manager = manager.project_manager.lock().await;
+        assert_eq!(project_manager.list_projects().unwrap().len(), 1);
+        assert!(temp_dir.path().join("projects.json").exists());
+        drop(project_manager);
+        assert!(manager
+            .binary_manager
+            .lock()
+            .await
+            .list_installed_versions()
+            .unwrap()
+            .is_empty());
+    }
+
     #[test]
     fn test_is_port_available() {
         // 找到一个可用端口
@@ -0,0 +1,230 @@
+use std::fs;
+use tauri_plugin_php::composer_manifest::{
+    constraint_matches, project_dependencies, ComposerJson, ComposerLock,
+};
+use tauri_plugin_php::framework_detector::FrameworkDetector;
+use tauri_plugin_php::models::{DependencyStatus, Framework};
+use tauri_plugin_php::Error;
+
+mod common;
+
+#[test]
+fn test_constraint_matches() {
+    let cases = [
+        ("^10.0", "v10.48.4", Some(true)),
+        ("^10.0", "11.0.0", Some(false)),
+        ("^0.3", "0.3.9", Some(true)),
+        ("^0.3", "0.4.0", Some(false)),
+        ("~1.2", "1.9.0", Some(true)),
+        ("~1.2.3", "1.3.0", Some(false)),
+        ("8.1.*", "8.1.27", Some(true)),
+        (">=7.2.5", "8.3.0", Some(true)),
+        (">=7.2 <8.0", "8.0.0", Some(false)),
+        ("^7.3|^8.0", "8.2.1", Some(true)),
+        ("^7.3 || ^8.0", "9.0.0", Some(false)),
+        ("1.0 - 2.0", "2.0.5", Some(true)),
+        ("*", "3.1.0", Some(true)),
+        ("^2.0@beta", "2.0.0-beta1", Some(true)),
+        // 下一个主版本的预发布版本不满足约束
+        ("^1.0", "2.0.0-RC1", Some(false)),
+        // 分支版本无法判断
+        ("^1.0", "dev-main", None),
+        ("dev-main", "1.0.0", None),
+    ];
+
+    for (constraint, version, expected) in cases {
+        assert_eq!(
+            constraint_matches(constraint, version),
+            expected,
+            "{} against {}",
+            constraint,
+            version
+        );
+    }
+}
+
+#[test]
+fn test_project_dependencies_against_lock_file() {
+    let temp_dir = common::create_temp_dir();
+    let project = temp_dir.path();
+    fs::write(
+        project.join("composer.json"),
+        r#"{
+            "name": "acme/shop",
+            "require": {
+                "php": "^8.2",
+                "ext-intl": "*",
+                "laravel/framework": "^11.0",
+                "guzzlehttp/guzzle": "^7.8",
+                "monolog/monolog": "^3.0"
+            },
+            "require-dev": {
+                "phpunit/phpunit": "^11.0"
+            }
+        }"#,
+    )
+    .unwrap();
+
+    // 没有锁文件时所有依赖都未锁定
+    let dependencies = project_dependencies(project).unwrap();
+    assert!(!dependencies.has_lock_file);
+    assert!(dependencies.lock_outdated);
+    assert_eq!(dependencies.packages.len(), 4);
+    assert!(dependencies
+        .packages
+        .iter()
+        .all(|package| package.status == DependencyStatus::NotLocked));
+
+    // Composer 将空的 platform 写为 []，packages-dev 可能为 null
+    fs::write(
+        project.join("composer.lock"),
+        r#"{
+            "content-hash": "abc",
+            "packages": [
+                {
+                    "name": "laravel/framework",
+                    "version": "v11.9.2",
+                    "require": { "php": "^8.2", "ext-mbstring": "*", "ext-openssl": "*" }
+                },
+                { "name": "guzzlehttp/guzzle", "version": "7.4.0", "require": [] },
+                { "name": "monolog/monolog", "version": "dev-main" }
+            ],
+            "packages-dev": [
+                {
+                    "name": "phpunit/phpunit",
+                    "version": "11.1.3",
+                    "require": { "ext-dom": "*", "ext-mbstring": "*" }
+                }
+            ],
+            "platform": [],
+            "platform-dev": []
+        }"#,
+    )
+    .unwrap();
+
+    let dependencies = project_dependencies(project).unwrap();
+    assert!(dependencies.has_lock_file);
+    assert!(dependencies.lock_outdated);
+
+    let status = |name: &str| {
+        let package = dependencies
+            .packages
+            .iter()
+            .find(|package| package.name == name)
+            .unwrap();
+        (package.status, package.locked_version.clone(), package.dev)
+    };
+    assert_eq!(
+        status("laravel/framework"),
+        (
+            DependencyStatus::UpToDate,
+            Some("v11.9.2".to_string()),
+            false
+        )
+    );
+    // 锁定版本低于 composer.json 的约束
+    assert_eq!(status("guzzlehttp/guzzle").0, DependencyStatus::Outdated);
+    assert_eq!(status("monolog/monolog").0, DependencyStatus::Unknown);
+    assert!(status("phpunit/phpunit").2);
+
+    // 扩展需求合并项目和已锁定包的要求
+    let extensions: Vec<(&str, bool)> = dependencies
+        .extensions
+        .iter()
+        .map(|extension| (extension.name.as_str(), extension.dev))
+        .collect();
+    assert_eq!(
+        extensions,
+        vec![
+            ("ext-intl", false),
+            ("ext-mbstring", false),
+            ("ext-openssl", false),
+            ("ext-dom", true)
+        ]
+    );
+    let mbstring = &dependencies.extensions[1];
+    assert_eq!(
+        mbstring.required_by,
+        vec!["laravel/framework", "phpunit/phpunit"]
+    );
+
+    let php = &dependencies.platform[0];
+    assert_eq!(php.name, "php");
+    assert_eq!(php.constraint, "^8.2");
+    assert_eq!(php.required_by, vec!["composer.json", "laravel/framework"]);
+}
+
+#[test]
+fn test_missing_and_invalid_manifests() {
+    let temp_dir = common::create_temp_dir();
+
+    assert!(ComposerJson::load(temp_dir.path()).unwrap().is_none());
+    assert!(ComposerLock::load(temp_dir.path()).unwrap().is_none());
+    assert!(matches!(
+        project_dependencies(temp_dir.path()),
+        Err(Error::Config(_))
+    ));
+
+    fs::write(temp_dir.path().join("composer.json"), "{ not json").unwrap();
+    assert!(matches!(
+        ComposerJson::load(temp_dir.path()),
+        Err(Error::Json(_))
+    ));
+}
+
+#[test]
+fn test_detector_reads_package_names() {
+    let temp_dir = common::create_temp_dir();
+    let detector = FrameworkDetector::new();
+
+    // 描述中提到的包名不算依赖
+    let described = temp_dir.path().join("described");
+    fs::create_dir_all(&described).unwrap();
+    fs::write(
+        described.join("composer.json"),
+        r#"{ "description": "Ported from laravel/framework", "require": {} }"#,
+    )
+    .unwrap();
+    assert_eq!(
+        detector.detect_framework(&described).unwrap(),
+        Framework::Unknown
+    );
+
+    // 只有 composer.lock 中的包也能识别框架
+    let locked = temp_dir.path().join("locked");
+    fs::create_dir_all(&locked).unwrap();
+    fs::write(
+        locked.join("composer.lock"),
+        r#"{ "packages": [{ "name": "laminas/laminas-mvc", "version": "3.7.0" }] }"#,
+    )
+    .unwrap();
+    assert_eq!(detector.detect_framework(&locked).unwrap(), Framework::Zend);
+}
+
+#[test]
+fn test_required_extensions_merge_composer_and_framework() {
+    let temp_dir = common::create_temp_dir();
+    let project = common::create_mock_php_project(temp_dir.path(), "laravel");
+    fs::write(
+        project.join("composer.json"),
+        r#"{
+            "require": { "laravel/framework": "^11.0", "ext-intl": "*", "ext-mbstring": "*" },
+            "require-dev": { "ext-xdebug": "*" }
+        }"#,
+    )
+    .unwrap();
+
+    let manager = common::isolated_project_manager(temp_dir.path());
+    let required = manager.required_extensions(&project);
+    let find = |name: &str| required.iter().find(|e| e.name == name).unwrap();
+
+    assert_eq!(find("intl").required_by, vec!["composer.json"]);
+    // composer.json 与框架都要求的扩展只出现一次
+    assert_eq!(
+        find("mbstring").required_by,
+        vec!["composer.json", "Laravel"]
+    );
+    assert_eq!(find("tokenizer").required_by, vec!["Laravel"]);
+    assert!(find("xdebug").dev);
+    assert!(!find("intl").dev);
+}
@@ -0,0 +1,486 @@
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::Path;
+use tauri_plugin_php::composer::{
+    compare_versions, composer_arguments, parse_problems, ComposerManager,
+};
+use tauri_plugin_php::models::{
+    ComposerAction, ComposerRequest, ComposerSettings, OutputStream, ProjectSettings,
+};
+use tauri_plugin_php::php_binary::PhpBinaryManager;
+use tauri_plugin_php::Error;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+mod common;
+
+fn composer_manager(base: &Path) -> ComposerManager {
+    let mut manager = ComposerManager::new();
+    manager.set_composer_directory(base.join("composer"));
+    manager
+}
+
+/// 启动一个模拟 getcomposer.org 的 HTTP 服务器
+async fn start_composer_mirror(phar: &'static [u8], checksum: String) -> String {
+    let listener = TcpListener::bind(("127.0.0.1", 0)).await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        loop {
+            let Ok((mut stream, _)) = listener.accept().await else {
+                break;
+            };
+            let checksum = checksum.clone();
+            tokio::spawn(async move {
+                let mut buffer = vec![0u8; 4096];
+                let n = stream.read(&mut buffer).await.unwrap_or(0);
+                let request = String::from_utf8_lossy(&buffer[..n]).to_string();
+                let path = request.split_whitespace().nth(1).unwrap_or("/").to_string();
+
+                let (status, body): (&str, Vec<u8>) = match path.as_str() {
+                    "/versions" => (
+                        "200 OK",
+                        br#"{"stable":[{"path":"/download/2.8.4/composer.phar","version":"2.8.4"}]}"#
+                            .to_vec(),
+                    ),
+                    "/download/2.8.4/composer.phar" => ("200 OK", phar.to_vec()),
+                    "/download/2.8.4/composer.phar.sha256sum" => (
+                        "200 OK",
+                        format!("{}  composer.phar\n", checksum).into_bytes(),
+                    ),
+                    _ => ("404 Not Found", Vec::new()),
+                };
+                let head = format!(
+                    "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    status,
+                    body.len()
+                );
+                let _ = stream.write_all(head.as_bytes()).await;
+                let _ = stream.write_all(&body).await;
+            });
+        }
+    });
+
+    format!("http://{}", addr)
+}
+
+#[test]
+fn test_compare_versions_is_numeric() {
+    use std::cmp::Ordering;
+
+    assert_eq!(compare_versions("2.10.0", "2.8.1"), Ordering::Greater);
+    assert_eq!(compare_versions("2.8.1", "2.8.1"), Ordering::Equal);
+    assert_eq!(compare_versions("1.10.27", "2.0.0"), Ordering::Less);
+}
+
+#[test]
+fn test_install_list_and_remove_composer() {
+    let temp_dir = common::create_temp_dir();
+    let manager = composer_manager(temp_dir.path());
+    let phar = temp_dir.path().join("bundled.phar");
+    fs::write(&phar, "<?php // composer").unwrap();
+
+    assert!(manager.list_installed_versions().unwrap().is_empty());
+    assert!(manager.default_composer().is_none());
+
+    manager.install_composer_from_file("2.8.1", &phar).unwrap();
+    let info = manager.install_composer_from_file("2.10.0", &phar).unwrap();
+    assert!(info.is_installed);
+    assert_eq!(info.size, Some(17));
+
+    // 最新版本排在最前并作为默认版本
+    let versions: Vec<String> = manager
+        .list_installed_versions()
+        .unwrap()
+        .into_iter()
+        .map(|info| info.version)
+        .collect();
+    assert_eq!(versions, vec!["2.10.0", "2.8.1"]);
+    assert_eq!(
+        manager.default_composer(),
+        Some(manager.get_composer_path("2.10.0"))
+    );
+
+    manager.remove_composer("2.10.0").unwrap();
+    assert_eq!(
+        manager.default_composer(),
+        Some(manager.get_composer_path("2.8.1"))
+    );
+
+    // 版本号不能跳出 Composer 目录
+    assert!(matches!(
+        manager.install_composer_from_file("../escape", &phar),
+        Err(Error::Config(_))
+    ));
+    assert!(matches!(
+        manager.remove_composer(".."),
+        Err(Error::Config(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_download_composer_verifies_checksum() {
+    const PHAR: &[u8] = b"<?php // downloaded composer";
+    let temp_dir = common::create_temp_dir();
+    let checksum: String = Sha256::digest(PHAR)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+
+    let mut manager = composer_manager(temp_dir.path());
+    manager.set_base_url(&start_composer_mirror(PHAR, checksum).await);
+
+    // 未指定版本时下载最新稳定版
+    let info = manager.download_composer(None).await.unwrap();
+    assert_eq!(info.version, "2.8.4");
+    assert_eq!(fs::read(&info.path).unwrap(), PHAR);
+    assert!(info
+        .download_url
+        .unwrap()
+        .ends_with("/download/2.8.4/composer.phar"));
+
+    // 校验和不匹配时不安装
+    let mut manager = composer_manager(&temp_dir.path().join("other"));
+    manager.set_base_url(&start_composer_mirror(PHAR, "0".repeat(64)).await);
+    let result = manager.download_composer(Some("2.8.4")).await;
+    assert!(matches!(result, Err(Error::Http(_))));
+    assert!(manager.list_installed_versions().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_composer_launcher_uses_project_php() {
+    let temp_dir = common::create_temp_dir();
+    let projects_dir = temp_dir.path().join("projects");
+    let project = common::create_mock_php_project(&projects_dir, "laravel");
+    common::create_mock_php_binary(temp_dir.path(), "8.2.0");
+    common::create_mock_php_binary(temp_dir.path(), "8.3.5");
+
+    let mut php_binaries = PhpBinaryManager::new();
+    php_binaries.set_php_directory(temp_dir.path().join("php-binaries"));
+    let composer = composer_manager(temp_dir.path());
+
+    let mut manager = common::isolated_project_manager(temp_dir.path());
+    manager.set_projects_directory(projects_dir);
+    manager.set_php_binaries(php_binaries);
+    manager.set_composer(composer.clone());
+
+    // 未安装 Composer
+    assert!(matches!(
+        manager.composer_launcher(Some(&project)),
+        Err(Error::Config(_))
+    ));
+
+    let phar = temp_dir.path().join("composer.phar");
+    fs::write(&phar, "<?php").unwrap();
+    composer.install_composer_from_file("2.8.4", &phar).unwrap();
+
+    // composer.json 要求 ^8.3，使用已安装的 8.3 补丁版本
+    let launcher = manager.composer_launcher(Some(&project)).unwrap();
+    assert_eq!(launcher.composer_phar, composer.get_composer_path("2.8.4"));
+    assert!(launcher.php_executable.to_string_lossy().contains("8.3.5"));
+
+    // 项目设置中的 PHP 版本优先
+    let id = manager.list_projects().unwrap()[0].id.clone().unwrap();
+    manager
+        .update_project_settings(
+            &id,
+            ProjectSettings {
+                php_version: Some("8.2.0".to_string()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    let launcher = manager.composer_launcher(Some(&project)).unwrap();
+    assert!(launcher.php_executable.to_string_lossy().contains("8.2.0"));
+
+    // 命令在释放锁之前解析出完整的运行参数
+    let run = manager
+        .composer_run(&ComposerRequest {
+            project_path: project.to_string_lossy().to_string(),
+            action: ComposerAction::Require,
+            packages: vec!["monolog/monolog".to_string()],
+            run_id: Some("run-1".to_string()),
+            ..Default::default()
+        })
+        .unwrap();
+    assert_eq!(run.launcher, launcher);
+    assert_eq!(run.project_path, project);
+    assert_eq!(run.args[0], "require");
+    assert!(run.args.contains(&"monolog/monolog".to_string()));
+    assert_eq!(run.run_id, "run-1");
+
+    // 没有匹配的 PHP 时报错
+    let mut empty = PhpBinaryManager::new();
+    empty.set_php_directory(temp_dir.path().join("none"));
+    manager.set_php_binaries(empty);
+    assert!(matches!(
+        manager.composer_launcher(Some(&project)),
+        Err(Error::PhpBinaryNotFound)
+    ));
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_composer_launcher_command_runs_phar_with_php() {
+    use std::os::unix::fs::PermissionsExt;
+    use tauri_plugin_php::composer::ComposerLauncher;
+
+    let temp_dir = common::create_temp_dir();
+    let php = temp_dir.path().join("bin").join("php");
+    fs::create_dir_all(php.parent().unwrap()).unwrap();
+    fs::write(&php, "#!/bin/sh\necho \"$@\"\necho \"$PATH\"\n").unwrap();
+    fs::set_permissions(&php, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let launcher = ComposerLauncher {
+        php_executable: php.clone(),
+        composer_phar: temp_dir.path().join("composer.phar"),
+    };
+    let output = launcher
+        .command(temp_dir.path())
+        .arg("install")
+        .output()
+        .await
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut lines = stdout.lines();
+
+    // PHP 执行 composer.phar，且其目录位于 PATH 最前
+    assert_eq!(
+        lines.next().unwrap(),
+        format!(
+            "{} --no-interaction install",
+            temp_dir.path().join("composer.phar").display()
+        )
+    );
+    assert!(lines
+        .next()
+        .unwrap()
+        .starts_with(&php.parent().unwrap().to_string_lossy().to_string()));
+}
+
+#[test]
+fn test_composer_arguments_merge_request_and_settings() {
+    let settings = ComposerSettings {
+        dev: Some(false),
+        extra_args: vec!["--prefer-dist".to_string()],
+    };
+
+    // 项目设置提供 --no-dev 和额外参数
+    let request = ComposerRequest {
+        action: ComposerAction::Install,
+        extra_args: vec!["--optimize-autoloader".to_string()],
+        ..Default::default()
+    };
+    assert_eq!(
+        composer_arguments(&request, &settings).unwrap(),
+        vec![
+            "install",
+            "--no-dev",
+            "--prefer-dist",
+            "--optimize-autoloader"
+        ]
+    );
+
+    // 请求中的 dev 优先于项目设置
+    let request = ComposerRequest {
+        action: ComposerAction::Update,
+        dev: Some(true),
+        ..Default::default()
+    };
+    assert_eq!(
+        composer_arguments(&request, &settings).unwrap(),
+        vec!["update", "--prefer-dist"]
+    );
+
+    // require 只在请求明确指定时加入 --dev，包名位于最后
+    let request = ComposerRequest {
+        action: ComposerAction::Require,
+        packages: vec!["phpunit/phpunit:^11".to_string()],
+        dev: Some(true),
+        ..Default::default()
+    };
+    assert_eq!(
+        composer_arguments(&request, &ComposerSettings::default()).unwrap(),
+        vec!["require", "--dev", "phpunit/phpunit:^11"]
+    );
+
+    // 缺少包名、多余的包名和非选项参数都会被拒绝
+    let invalid = [
+        ComposerRequest {
+            action: ComposerAction::Remove,
+            ..Default::default()
+        },
+        ComposerRequest {
+            action: ComposerAction::DumpAutoload,
+            packages: vec!["foo/bar".to_string()],
+            ..Default::default()
+        },
+        ComposerRequest {
+            action: ComposerAction::Require,
+            packages: vec!["--no-plugins".to_string()],
+            ..Default::default()
+        },
+        ComposerRequest {
+            extra_args: vec!["foo/bar".to_string()],
+            ..Default::default()
+        },
+    ];
+    for request in invalid {
+        assert!(matches!(
+            composer_arguments(&request, &ComposerSettings::default()),
+            Err(Error::Config(_))
+        ));
+    }
+}
+
+#[test]
+fn test_parse_problems_from_resolution_failure() {
+    let output = "Loading composer repositories with package information
+Updating dependencies
+Your requirements could not be resolved to an installable set of packages.
+
+  Problem 1
+    - Root composer.json requires PHP extension ext-intl * but it is missing from your system. Install or enable PHP's intl extension.
+  Problem 2
+    - laravel/framework[v11.0.0, ..., v11.9.2] require ext-mbstring * -> it is missing from your system. Install or enable PHP's mbstring extension.
+    - Root composer.json requires laravel/framework ^11.0 -> satisfiable by laravel/framework[v11.0.0, ..., v11.9.2].
+
+To enable extensions, verify that they are enabled in your .ini files:
+    - /etc/php/8.3/cli/php.ini
+";
+    let problems = parse_problems(output);
+
+    assert_eq!(problems.len(), 2);
+    assert_eq!(problems[0].number, Some(1));
+    assert_eq!(problems[0].missing_extensions, vec!["intl"]);
+    assert_eq!(problems[1].number, Some(2));
+    assert_eq!(problems[1].messages.len(), 2);
+    assert_eq!(problems[1].missing_extensions, vec!["mbstring"]);
+}
+
+#[test]
+fn test_parse_problems_from_error_block() {
+    let output = "./composer.json has been updated
+Running composer update foo/bar
+                                                                                
+  [InvalidArgumentException]                                                    
+  Could not find a matching version of package foo/bar. Check the package       
+  spelling, your version constraint and that the package is available.          
+                                                                                
+
+require [--dev] [--dry-run] [--] [<packages>...]
+";
+    let problems = parse_problems(output);
+
+    // 异常类名不作为消息
+    assert_eq!(problems.len(), 1);
+    assert_eq!(problems[0].number, None);
+    assert_eq!(
+        problems[0].messages,
+        vec![
+            "Could not find a matching version of package foo/bar. Check the package",
+            "spelling, your version constraint and that the package is available."
+        ]
+    );
+    assert!(problems[0].missing_extensions.is_empty());
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_run_composer_streams_output_and_reports_problems() {
+    use std::sync::{Arc, Mutex};
+
+    let temp_dir = common::create_temp_dir();
+    let projects_dir = temp_dir.path().join("projects");
+    let project = common::create_mock_php_project(&projects_dir, "laravel");
+
+    // 模拟 PHP：打印参数，并在 stderr 输出 Composer 的问题列表后失败
+    let php = common::create_mock_php_binary(temp_dir.path(), "8.3.0");
+    fs::write(
+        &php,
+        "#!/bin/sh\nshift\necho \"args: $*\"\necho '  Problem 1' >&2\necho '    - Root composer.json requires PHP extension ext-gd * but it is missing from your system.' >&2\nexit 2\n",
+    )
+    .unwrap();
+
+    let mut php_binaries = PhpBinaryManager::new();
+    php_binaries.set_php_directory(temp_dir.path().join("php-binaries"));
+    let composer = composer_manager(temp_dir.path());
+    let phar = temp_dir.path().join("composer.phar");
+    fs::write(&phar, "<?php").unwrap();
+    composer.install_composer_from_file("2.8.4", &phar).unwrap();
+
+    let mut manager = common::isolated_project_manager(temp_dir.path());
+    manager.set_projects_directory(projects_dir);
+    manager.set_php_binaries(php_binaries);
+    manager.set_composer(composer);
+
+    let id = manager.list_projects().unwrap()[0].id.clone().unwrap();
+    manager
+        .update_project_settings(
+            &id,
+            ProjectSettings {
+                php_version: Some("8.3.0".to_string()),
+                composer: ComposerSettings {
+                    dev: Some(false),
+                    extra_args: vec!["--prefer-dist".to_string()],
+                },
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let sink = received.clone();
+    let request = ComposerRequest {
+        project_path: project.to_string_lossy().to_string(),
+        action: ComposerAction::Install,
+        run_id: Some("run-1".to_string()),
+        ..Default::default()
+    };
+    let result = manager
+        .run_composer(
+            request,
+            Some(Box::new(move |output| {
+                sink.lock().unwrap().push(output.clone());
+            })),
+        )
+        .await
+        .unwrap();
+
+    // 非零退出码作为结果返回而不是错误
+    assert!(!result.success);
+    assert_eq!(result.exit_code, Some(2));
+    assert_eq!(result.run_id, "run-1");
+    assert_eq!(result.problems.len(), 1);
+    assert_eq!(result.problems[0].missing_extensions, vec!["gd"]);
+
+    // 输出逐行推送，并带有来源流
+    let received = received.lock().unwrap().clone();
+    assert_eq!(received.len(), 3);
+    assert!(received.iter().all(|output| output.run_id == "run-1"));
+    let stdout = received
+        .iter()
+        .find(|output| output.stream == OutputStream::Stdout)
+        .unwrap();
+    assert_eq!(
+        stdout.line,
+        "args: --no-interaction --no-ansi install --no-dev --prefer-dist"
+    );
+    assert_eq!(
+        received
+            .iter()
+            .filter(|output| output.stream == OutputStream::Stderr)
+            .count(),
+        2
+    );
+
+    // 没有 composer.json 的目录被拒绝
+    let request = ComposerRequest {
+        project_path: temp_dir.path().to_string_lossy().to_string(),
+        ..Default::default()
+    };
+    assert!(matches!(
+        manager.run_composer(request, None).await,
+        Err(Error::Config(_))
+    ));
+}
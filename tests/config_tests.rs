@@ -0,0 +1,171 @@
+use std::fs;
+use tauri_plugin_php::config::{load_config, save_config};
+use tauri_plugin_php::models::{Config, ServerConfig, StartServerRequest};
+use tauri_plugin_php::php_server::PhpServerManager;
+use tauri_plugin_php::{Error, PhpManager};
+
+mod common;
+
+/// 模拟 tauri.conf.json 中的插件配置
+fn tauri_conf_config() -> Config {
+    serde_json::from_str(
+        r#"{
+            "default_php_version": "8.2.0",
+            "server_config": { "default_port": 9100 }
+        }"#,
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_partial_plugin_config_uses_defaults() {
+    let config = tauri_conf_config();
+
+    // 未指定的字段使用默认值
+    assert_eq!(config.default_php_version, Some("8.2.0".to_string()));
+    assert_eq!(config.server_config.default_port, 9100);
+    assert_eq!(config.server_config.default_host, "127.0.0.1");
+    assert_eq!(config.php_versions, Config::default().php_versions);
+    assert_eq!(config.projects_dir, None);
+}
+
+#[test]
+fn test_saved_config_overrides_plugin_config() {
+    let temp_dir = common::create_temp_dir();
+    let path = temp_dir.path().join("config.json");
+
+    // 配置文件不存在时保持 tauri.conf.json 的配置
+    let config = load_config(tauri_conf_config(), &path);
+    assert_eq!(config.server_config.default_port, 9100);
+
+    // 保存的配置只覆盖其中出现的键
+    fs::write(
+        &path,
+        r#"{ "projects_dir": "/srv/projects", "server_config": { "default_host": "0.0.0.0" } }"#,
+    )
+    .unwrap();
+    let config = load_config(tauri_conf_config(), &path);
+    assert_eq!(config.projects_dir, Some("/srv/projects".to_string()));
+    assert_eq!(config.server_config.default_host, "0.0.0.0");
+    assert_eq!(config.server_config.default_port, 9100);
+    assert_eq!(config.default_php_version, Some("8.2.0".to_string()));
+
+    // 损坏的配置文件被忽略
+    fs::write(&path, "{ not json").unwrap();
+    let config = load_config(tauri_conf_config(), &path);
+    assert_eq!(config.server_config.default_host, "127.0.0.1");
+}
+
+#[test]
+fn test_save_config_round_trip_and_validation() {
+    let temp_dir = common::create_temp_dir();
+    let path = temp_dir.path().join("nested").join("config.json");
+
+    let mut config = tauri_conf_config();
+    config.projects_dir = Some("~/Sites".to_string());
+    save_config(&config, &path).unwrap();
+
+    let loaded = load_config(Config::default(), &path);
+    assert_eq!(loaded.projects_dir, Some("~/Sites".to_string()));
+    assert_eq!(loaded.server_config.default_port, 9100);
+
+    config.server_config.default_port = 0;
+    assert!(matches!(save_config(&config, &path), Err(Error::Config(_))));
+}
+
+#[tokio::test]
+async fn test_php_manager_applies_config_to_managers() {
+    let temp_dir = common::create_temp_dir();
+    let first_dir = temp_dir.path().join("first");
+    let second_dir = temp_dir.path().join("second");
+    common::create_mock_php_project(&first_dir, "laravel");
+
+    let mut config = tauri_conf_config();
+    config.projects_dir = Some(first_dir.to_string_lossy().to_string());
+    config.download_base_url = "https://mirror.example.com/php/".to_string();
+    let manager = PhpManager::with_config(config.clone());
+    common::isolate_php_manager(&manager, temp_dir.path());
+
+    {
+        let project_manager = manager.project_manager.lock().await;
+        assert_eq!(project_manager.projects_directory(), first_dir);
+        assert_eq!(project_manager.list_projects().unwrap().len(), 1);
+    }
+    {
+        let binary_manager = manager.binary_manager.lock().await;
+        assert_eq!(binary_manager.default_version(), "8.2.0");
+        let url = binary_manager.get_download_url("8.2.0").unwrap();
+        assert!(url.starts_with("https://mirror.example.com/php/v8.2.0/php-8.2.0-"));
+    }
+    assert_eq!(
+        manager
+            .server_manager
+            .lock()
+            .await
+            .server_config()
+            .default_port,
+        9100
+    );
+
+    // 运行时修改配置立即生效
+    config.projects_dir = Some(second_dir.to_string_lossy().to_string());
+    config.default_php_version = None;
+    config.server_config.default_port = 9200;
+    manager.apply_config(config).await.unwrap();
+
+    let project_manager = manager.project_manager.lock().await;
+    assert_eq!(project_manager.projects_directory(), second_dir);
+    assert!(project_manager.list_projects().unwrap().is_empty());
+    drop(project_manager);
+    assert_eq!(
+        manager.binary_manager.lock().await.default_version(),
+        "8.3.0"
+    );
+    assert_eq!(
+        manager.server_manager.lock().await.default_port_range(),
+        9200..=10199
+    );
+    assert_eq!(manager.config.lock().await.server_config.default_port, 9200);
+
+    // 无效配置不会被应用
+    let mut invalid = Config::default();
+    invalid.server_config.default_port = 0;
+    assert!(manager.apply_config(invalid).await.is_err());
+    assert_eq!(
+        manager
+            .server_manager
+            .lock()
+            .await
+            .server_config()
+            .default_port,
+        9200
+    );
+}
+
+#[tokio::test]
+async fn test_server_uses_configured_document_root() {
+    let temp_dir = common::create_temp_dir();
+    let project_path = common::create_mock_php_project(temp_dir.path(), "plain");
+    let php_executable = common::create_mock_php_binary(temp_dir.path(), "8.3.0");
+
+    let mut manager = PhpServerManager::with_config(ServerConfig {
+        document_root: Some("web".to_string()),
+        ..Default::default()
+    });
+
+    let request = StartServerRequest {
+        project_path: project_path.to_string_lossy().to_string(),
+        port: Some(common::find_available_port(8000)),
+        ..Default::default()
+    };
+
+    // 配置的文档根目录相对于项目目录解析
+    let result = manager.start_server(request, php_executable).await;
+    match result {
+        Err(Error::PhpServer(message)) => {
+            assert!(message.contains("Document root does not exist"));
+            assert!(message.contains(&project_path.join("web").to_string_lossy().to_string()));
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
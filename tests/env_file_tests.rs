@@ -0,0 +1,55 @@
+use std::fs;
+use tauri_plugin_php::env_file::{get_value, set_values};
+
+mod common;
+
+#[test]
+fn test_get_value_unquotes_and_skips_comments() {
+    let content = "# APP_KEY=commented\nAPP_NAME=\"My App\"\nAPP_KEY=\nDB_PASSWORD='p#ss'\nAPP_URL=http://localhost # inline\nexport APP_ENV=local\n";
+
+    assert_eq!(get_value(content, "APP_NAME"), Some("My App".to_string()));
+    assert_eq!(get_value(content, "APP_KEY"), Some(String::new()));
+    assert_eq!(get_value(content, "DB_PASSWORD"), Some("p#ss".to_string()));
+    assert_eq!(
+        get_value(content, "APP_URL"),
+        Some("http://localhost".to_string())
+    );
+    assert_eq!(get_value(content, "APP_ENV"), Some("local".to_string()));
+    assert_eq!(get_value(content, "MISSING"), None);
+}
+
+#[test]
+fn test_set_values_keeps_other_lines() {
+    let temp_dir = common::create_temp_dir();
+    let path = temp_dir.path().join(".env");
+    fs::write(
+        &path,
+        "# Database\nDB_CONNECTION=mysql\nDB_HOST=127.0.0.1\n",
+    )
+    .unwrap();
+
+    set_values(
+        &path,
+        &[
+            ("DB_CONNECTION", "pgsql".to_string()),
+            ("DB_PASSWORD", "se cret\"".to_string()),
+        ],
+    )
+    .unwrap();
+
+    // 已有的键原地替换，新键追加，需要时加引号
+    let content = fs::read_to_string(&path).unwrap();
+    assert_eq!(
+        content,
+        "# Database\nDB_CONNECTION=pgsql\nDB_HOST=127.0.0.1\nDB_PASSWORD=\"se cret\\\"\"\n"
+    );
+    assert_eq!(
+        get_value(&content, "DB_PASSWORD"),
+        Some("se cret\"".to_string())
+    );
+
+    // 文件不存在时创建
+    let new_path = temp_dir.path().join("new.env");
+    set_values(&new_path, &[("APP_ENV", "local".to_string())]).unwrap();
+    assert_eq!(fs::read_to_string(&new_path).unwrap(), "APP_ENV=local\n");
+}
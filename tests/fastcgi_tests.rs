@@ -0,0 +1,358 @@
+use std::fs;
+use tauri_plugin_php::fastcgi::{encode_params, execute, parse_cgi_response};
+use tauri_plugin_php::fastcgi_server::{
+    resolve_request_path, FastCgiServer, FastCgiServerConfig, RequestTarget,
+};
+use tauri_plugin_php::models::{FastCgiOptions, ServerBackend, StartServerRequest};
+use tauri_plugin_php::php_server::PhpServerManager;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+mod common;
+
+/// 读取一条 FastCGI 记录，返回 (类型, 内容)
+async fn read_record(stream: &mut TcpStream) -> (u8, Vec<u8>) {
+    let mut header = [0u8; 8];
+    stream.read_exact(&mut header).await.unwrap();
+    let length = u16::from_be_bytes([header[4], header[5]]) as usize;
+    let mut content = vec![0u8; length + header[6] as usize];
+    stream.read_exact(&mut content).await.unwrap();
+    content.truncate(length);
+    (header[1], content)
+}
+
+fn record(record_type: u8, content: &[u8]) -> Vec<u8> {
+    let mut buffer = vec![1, record_type, 0, 1];
+    buffer.extend_from_slice(&(content.len() as u16).to_be_bytes());
+    buffer.extend_from_slice(&[0, 0]);
+    buffer.extend_from_slice(content);
+    buffer
+}
+
+#[test]
+fn test_encode_params_short_and_long_values() {
+    let long_value = "x".repeat(200);
+    let encoded = encode_params(&[
+        ("A".to_string(), "bc".to_string()),
+        ("LONG".to_string(), long_value.clone()),
+    ]);
+
+    // 短长度用 1 字节编码
+    assert_eq!(&encoded[..5], &[1, 2, b'A', b'b', b'c']);
+    // 长度 >= 128 时用 4 字节编码并设置最高位
+    assert_eq!(encoded[5], 4);
+    assert_eq!(&encoded[6..10], &(200u32 | 0x8000_0000).to_be_bytes());
+    assert_eq!(&encoded[10..14], b"LONG");
+    assert_eq!(encoded.len(), 14 + long_value.len());
+}
+
+#[test]
+fn test_parse_cgi_response_with_status_header() {
+    let response =
+        parse_cgi_response(b"Status: 404 Not Found\r\nContent-Type: text/plain\r\n\r\nmissing");
+
+    assert_eq!(response.status, 404);
+    assert_eq!(
+        response.headers,
+        vec![("Content-Type".to_string(), "text/plain".to_string())]
+    );
+    assert_eq!(response.body, b"missing");
+}
+
+#[test]
+fn test_parse_cgi_response_location_implies_redirect() {
+    let response = parse_cgi_response(b"Location: /login\nSet-Cookie: a=1\n\n");
+
+    assert_eq!(response.status, 302);
+    assert_eq!(response.headers.len(), 2);
+    assert!(response.body.is_empty());
+
+    // 没有头部时整个输出都是正文
+    let response = parse_cgi_response(b"plain output");
+    assert_eq!(response.status, 200);
+    assert_eq!(response.body, b"plain output");
+}
+
+#[tokio::test]
+async fn test_execute_against_fastcgi_responder() {
+    let listener = TcpListener::bind(("127.0.0.1", 0)).await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    // 模拟一个 FastCGI 应用：读取完整请求后回显请求体
+    let responder = tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let (record_type, _) = read_record(&mut stream).await;
+        assert_eq!(record_type, 1);
+
+        let mut params = Vec::new();
+        loop {
+            let (record_type, content) = read_record(&mut stream).await;
+            assert_eq!(record_type, 4);
+            if content.is_empty() {
+                break;
+            }
+            params.extend_from_slice(&content);
+        }
+
+        let mut stdin = Vec::new();
+        loop {
+            let (record_type, content) = read_record(&mut stream).await;
+            assert_eq!(record_type, 5);
+            if content.is_empty() {
+                break;
+            }
+            stdin.extend_from_slice(&content);
+        }
+
+        let mut output = b"Content-Type: text/plain\r\n\r\n".to_vec();
+        output.extend_from_slice(&stdin);
+        let mut reply = record(6, &output);
+        reply.extend(record(7, b"notice"));
+        reply.extend(record(6, &[]));
+        reply.extend(record(3, &[0, 0, 0, 0, 0, 0, 0, 0]));
+        stream.write_all(&reply).await.unwrap();
+
+        params
+    });
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    let params = vec![("REQUEST_METHOD".to_string(), "POST".to_string())];
+    let output = execute(&mut stream, &params, b"name=php").await.unwrap();
+
+    assert_eq!(responder.await.unwrap(), encode_params(&params));
+    assert_eq!(output.stderr, b"notice");
+    assert_eq!(output.app_status, 0);
+    let response = parse_cgi_response(&output.stdout);
+    assert_eq!(response.status, 200);
+    assert_eq!(response.body, b"name=php");
+}
+
+#[test]
+fn test_resolve_request_path() {
+    let temp_dir = common::create_temp_dir();
+    let root = temp_dir.path();
+    fs::create_dir_all(root.join("css")).unwrap();
+    fs::write(root.join("index.php"), "<?php echo 'index';").unwrap();
+    fs::write(root.join("info.php"), "<?php phpinfo();").unwrap();
+    fs::write(root.join("css/app.css"), "body {}").unwrap();
+
+    // 静态文件直接返回
+    assert_eq!(
+        resolve_request_path(root, None, "/css/app.css"),
+        RequestTarget::Static(root.join("css/app.css"))
+    );
+
+    // 存在的 PHP 文件直接执行
+    assert_eq!(
+        resolve_request_path(root, None, "/info.php"),
+        RequestTarget::Script {
+            script_filename: root.join("info.php"),
+            script_name: "/info.php".to_string(),
+            path_info: None,
+        }
+    );
+
+    // 脚本后的路径作为 PATH_INFO
+    assert_eq!(
+        resolve_request_path(root, None, "/info.php/extra/path"),
+        RequestTarget::Script {
+            script_filename: root.join("info.php"),
+            script_name: "/info.php".to_string(),
+            path_info: Some("/extra/path".to_string()),
+        }
+    );
+
+    // 目录请求使用 index.php
+    assert!(matches!(
+        resolve_request_path(root, None, "/"),
+        RequestTarget::Script { ref script_name, .. } if script_name == "/index.php"
+    ));
+
+    // 不存在的路径交给前端控制器
+    assert_eq!(
+        resolve_request_path(root, None, "/users/1"),
+        RequestTarget::Script {
+            script_filename: root.join("index.php"),
+            script_name: "/index.php".to_string(),
+            path_info: Some("/users/1".to_string()),
+        }
+    );
+
+    // 拒绝目录穿越
+    assert_eq!(
+        resolve_request_path(root, None, "/../secret.php"),
+        RequestTarget::NotFound
+    );
+}
+
+#[test]
+fn test_resolve_request_path_rejects_escapes() {
+    let temp_dir = common::create_temp_dir();
+    let root = temp_dir.path().join("public");
+    fs::create_dir_all(&root).unwrap();
+    fs::write(root.join("index.php"), "<?php").unwrap();
+    fs::write(temp_dir.path().join("secret.txt"), "secret").unwrap();
+
+    // 反斜杠、盘符和绝对路径都不能离开文档根目录
+    let secret = temp_dir.path().join("secret.txt");
+    for path in [
+        "/..\\secret.txt".to_string(),
+        "/..\\..\\secret.txt".to_string(),
+        "/C:\\Windows\\win.ini".to_string(),
+        "/C:/Windows/win.ini".to_string(),
+        "/css/./../../secret.txt".to_string(),
+    ] {
+        assert_eq!(
+            resolve_request_path(&root, None, &path),
+            RequestTarget::NotFound,
+            "{}",
+            path
+        );
+    }
+
+    // 绝对路径只会被当作文档根目录下的路径
+    for path in [
+        format!("/{}", secret.display()),
+        format!("//{}", secret.display()),
+    ] {
+        match resolve_request_path(&root, None, &path) {
+            RequestTarget::Script {
+                script_filename, ..
+            } => assert_eq!(script_filename, root.join("index.php")),
+            target => panic!("{} resolved to {:?}", path, target),
+        }
+    }
+}
+
+#[test]
+fn test_resolve_request_path_with_router_script() {
+    let temp_dir = common::create_temp_dir();
+    let root = temp_dir.path();
+    fs::write(root.join("server.php"), "<?php").unwrap();
+    fs::write(root.join("robots.txt"), "User-agent: *").unwrap();
+
+    // 路由脚本处理所有非静态文件请求
+    assert!(matches!(
+        resolve_request_path(root, Some("server.php"), "/dashboard"),
+        RequestTarget::Script { ref script_name, .. } if script_name == "/server.php"
+    ));
+    assert_eq!(
+        resolve_request_path(root, Some("server.php"), "/robots.txt"),
+        RequestTarget::Static(root.join("robots.txt"))
+    );
+
+    // 没有 index.php 也没有路由脚本时返回 404
+    assert_eq!(
+        resolve_request_path(root, None, "/dashboard"),
+        RequestTarget::NotFound
+    );
+}
+
+#[tokio::test]
+async fn test_start_php_cgi_backend_without_php_cgi_binary() {
+    let temp_dir = common::create_temp_dir();
+    let project_dir = common::create_mock_php_project(temp_dir.path(), "plain");
+    let php_binary = common::create_mock_php_binary(temp_dir.path(), "8.3.0");
+
+    let mut manager = PhpServerManager::new();
+    let request = StartServerRequest {
+        project_path: project_dir.to_string_lossy().to_string(),
+        port: Some(common::find_available_port(9300)),
+        backend: ServerBackend::PhpCgi,
+        ..Default::default()
+    };
+
+    // 模拟的 PHP 目录中没有 php-cgi，应返回错误而不是启动服务器
+    let result = manager.start_server(request, php_binary).await;
+    assert!(result.is_err());
+    assert!(manager.list_running_servers().is_empty());
+}
+
+/// 通过 HTTP/1.1 发送 GET 请求并读取完整响应
+async fn http_get(addr: std::net::SocketAddr, path: &str) -> String {
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    let request = format!(
+        "GET {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n",
+        path
+    );
+    stream.write_all(request.as_bytes()).await.unwrap();
+    let mut response = Vec::new();
+    let _ = stream.read_to_end(&mut response).await;
+    String::from_utf8_lossy(&response).to_string()
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_php_cgi_front_end_times_out_and_reports_stopped_workers() {
+    use std::os::unix::fs::PermissionsExt;
+    use std::time::{Duration, Instant};
+
+    let temp_dir = common::create_temp_dir();
+    let project_dir = common::create_mock_php_project(temp_dir.path(), "plain");
+    let php_binary = common::create_mock_php_binary(temp_dir.path(), "8.3.0");
+
+    // 模拟的 php-cgi：记录监听地址，3 秒后删除自身并退出，之后无法再启动
+    let addr_file = temp_dir.path().join("worker.addr");
+    let php_cgi = php_binary.with_file_name("php-cgi");
+    fs::write(
+        &php_cgi,
+        format!(
+            "#!/bin/sh\necho \"$2\" > '{}'\nsleep 3\nrm -f \"$0\"\nexit 1\n",
+            addr_file.display()
+        ),
+    )
+    .unwrap();
+    fs::set_permissions(&php_cgi, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let server = tokio::spawn(FastCgiServer::start(FastCgiServerConfig {
+        backend: ServerBackend::PhpCgi,
+        php_executable: php_binary,
+        listen_addr: "127.0.0.1:0".parse().unwrap(),
+        project_path: project_dir.clone(),
+        document_root: project_dir,
+        router_script: None,
+        options: FastCgiOptions {
+            workers: 1,
+            request_timeout_secs: 1,
+            ..Default::default()
+        },
+        env: Default::default(),
+    }));
+
+    // 测试代替 php-cgi 在其地址上监听：接受连接但从不响应
+    let worker_addr: std::net::SocketAddr = loop {
+        if let Some(addr) = fs::read_to_string(&addr_file)
+            .ok()
+            .and_then(|addr| addr.trim().parse().ok())
+        {
+            break addr;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    };
+    let listener = TcpListener::bind(worker_addr).await.unwrap();
+    let worker = tokio::spawn(async move {
+        let mut connections = Vec::new();
+        while let Ok((stream, _)) = listener.accept().await {
+            connections.push(stream);
+        }
+    });
+    let mut server = server.await.unwrap().unwrap();
+
+    // 进程不响应时在超时后返回 504
+    let response = http_get(server.local_addr(), "/index.php").await;
+    assert!(response.starts_with("HTTP/1.1 504"), "{}", response);
+
+    // 所有进程都无法再启动后立即返回 503，而不是一直等待
+    let started = Instant::now();
+    while server.is_running() && started.elapsed() < Duration::from_secs(10) {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    assert!(!server.is_running());
+    let started = Instant::now();
+    let response = http_get(server.local_addr(), "/index.php").await;
+    assert!(response.starts_with("HTTP/1.1 503"), "{}", response);
+    assert!(started.elapsed() < Duration::from_secs(1));
+
+    worker.abort();
+    server.shutdown().await;
+}
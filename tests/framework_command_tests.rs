@@ -0,0 +1,381 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use tauri_plugin_php::framework_command::CommandRegistry;
+use tauri_plugin_php::models::{Framework, FrameworkCommandRequest, ProjectSettings};
+use tauri_plugin_php::project_manager::ProjectManager;
+use tauri_plugin_php::Error;
+
+mod common;
+
+/// 创建只包含一个项目的 ProjectManager，项目使用模拟的 PHP 8.3.0
+fn manager_with_project(
+    base: &Path,
+    framework: &str,
+    php_script: &str,
+) -> (ProjectManager, PathBuf) {
+    let projects_dir = base.join("projects");
+    let project = common::create_mock_php_project(&projects_dir, framework);
+    let php = common::create_mock_php_binary(base, "8.3.0");
+    fs::write(&php, php_script).unwrap();
+
+    let manager = common::isolated_project_manager(base);
+
+    let id = manager.list_projects().unwrap()[0].id.clone().unwrap();
+    manager
+        .update_project_settings(
+            &id,
+            ProjectSettings {
+                php_version: Some("8.3.0".to_string()),
+                env: BTreeMap::from([("APP_ENV".to_string(), "testing".to_string())]),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    (manager, project)
+}
+
+#[test]
+fn test_framework_command_uses_console_script() {
+    let temp_dir = common::create_temp_dir();
+    let (manager, project) = manager_with_project(temp_dir.path(), "symfony", "#!/bin/sh\n");
+
+    let command = manager
+        .framework_command(&FrameworkCommandRequest {
+            project_path: project.to_string_lossy().to_string(),
+            args: vec!["cache:clear".to_string()],
+            env: BTreeMap::from([("APP_DEBUG".to_string(), "0".to_string())]),
+            ..Default::default()
+        })
+        .unwrap();
+    assert_eq!(command.framework, Framework::Symfony);
+    assert_eq!(command.script, "bin/console");
+    assert_eq!(command.args, vec!["cache:clear"]);
+    // 请求的环境变量叠加在项目设置之上
+    assert_eq!(
+        command.env.get("APP_ENV").map(String::as_str),
+        Some("testing")
+    );
+    assert_eq!(command.env.get("APP_DEBUG").map(String::as_str), Some("0"));
+
+    // 缺少控制台脚本时报错
+    fs::remove_file(project.join("bin/console")).unwrap();
+    let result = manager.framework_command(&FrameworkCommandRequest {
+        project_path: project.to_string_lossy().to_string(),
+        ..Default::default()
+    });
+    assert!(matches!(result, Err(Error::Config(_))));
+}
+
+#[test]
+fn test_framework_command_rejects_plain_projects() {
+    let temp_dir = common::create_temp_dir();
+    let (manager, project) = manager_with_project(temp_dir.path(), "plain", "#!/bin/sh\n");
+
+    let result = manager.framework_command(&FrameworkCommandRequest {
+        project_path: project.to_string_lossy().to_string(),
+        args: vec!["list".to_string()],
+        ..Default::default()
+    });
+    assert!(matches!(result, Err(Error::Config(_))));
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_run_framework_command_streams_output_and_exit_code() {
+    use std::sync::Mutex;
+    use tauri_plugin_php::models::OutputStream;
+
+    let temp_dir = common::create_temp_dir();
+    // 模拟 PHP：打印脚本、参数和环境变量，然后以 3 退出
+    let (manager, project) = manager_with_project(
+        temp_dir.path(),
+        "laravel",
+        "#!/bin/sh\necho \"$*\"\necho \"env $APP_ENV\"\necho oops >&2\nexit 3\n",
+    );
+    let command = manager
+        .framework_command(&FrameworkCommandRequest {
+            project_path: project.to_string_lossy().to_string(),
+            args: vec!["route:list".to_string(), "--json".to_string()],
+            ..Default::default()
+        })
+        .unwrap();
+
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let sink = received.clone();
+    let result = command
+        .run(
+            "run-1",
+            Arc::new(AtomicBool::new(false)),
+            Some(Box::new(move |output| {
+                sink.lock().unwrap().push(output.clone());
+            })),
+        )
+        .await
+        .unwrap();
+
+    // 非零退出码作为结果返回而不是错误
+    assert!(!result.success);
+    assert_eq!(result.exit_code, Some(3));
+    assert_eq!(result.script, "artisan");
+    assert!(result.output.contains("artisan route:list --json\n"));
+    assert!(result.output.contains("env testing\n"));
+
+    let received = received.lock().unwrap().clone();
+    assert_eq!(received.len(), 3);
+    assert!(received.iter().all(|output| output.run_id == "run-1"));
+    assert!(received
+        .iter()
+        .any(|output| output.stream == OutputStream::Stderr && output.line == "oops"));
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_run_framework_command_can_be_cancelled() {
+    let temp_dir = common::create_temp_dir();
+    let (manager, project) = manager_with_project(
+        temp_dir.path(),
+        "laravel",
+        &format!(
+            "#!/bin/sh\nsleep 30 &\necho $! > '{}'\necho started\nexec sleep 30\n",
+            temp_dir.path().join("worker.pid").display()
+        ),
+    );
+    let command = manager
+        .framework_command(&FrameworkCommandRequest {
+            project_path: project.to_string_lossy().to_string(),
+            args: vec!["queue:work".to_string()],
+            ..Default::default()
+        })
+        .unwrap();
+
+    let registry = Arc::new(CommandRegistry::new());
+    let cancel = registry.register("worker").unwrap();
+    // 同一 run id 不能重复注册
+    assert!(registry.register("worker").is_err());
+    assert!(!registry.cancel("missing"));
+
+    let canceller = registry.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+        canceller.cancel("worker");
+    });
+
+    let started = std::time::Instant::now();
+    let result = command.run("worker", cancel, None).await;
+    assert!(matches!(result, Err(Error::Cancelled(_))));
+    assert!(started.elapsed() < std::time::Duration::from_secs(10));
+
+    // 命令启动的子进程一并结束（僵尸进程视为已结束）
+    let pid = fs::read_to_string(temp_dir.path().join("worker.pid")).unwrap();
+    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+    let state = std::process::Command::new("ps")
+        .args(["-o", "stat=", "-p", pid.trim()])
+        .output()
+        .unwrap();
+    let state = String::from_utf8_lossy(&state.stdout);
+    assert!(
+        state.trim().is_empty() || state.trim().starts_with('Z'),
+        "{}",
+        state
+    );
+
+    registry.unregister("worker");
+    assert!(!registry.cancel("worker"));
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_cancel_framework_command_after_it_closes_its_output() {
+    let temp_dir = common::create_temp_dir();
+    // 命令关闭输出后继续运行，例如转入后台的服务
+    let (manager, project) = manager_with_project(
+        temp_dir.path(),
+        "laravel",
+        "#!/bin/sh\necho started\nexec sleep 30 > /dev/null 2>&1\n",
+    );
+    let command = manager
+        .framework_command(&FrameworkCommandRequest {
+            project_path: project.to_string_lossy().to_string(),
+            args: vec!["serve".to_string()],
+            ..Default::default()
+        })
+        .unwrap();
+
+    let cancel = Arc::new(AtomicBool::new(false));
+    let flag = cancel.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+        flag.store(true, std::sync::atomic::Ordering::SeqCst);
+    });
+
+    let started = std::time::Instant::now();
+    let result = command.run("serve", cancel, None).await;
+    assert!(matches!(result, Err(Error::Cancelled(_))));
+    assert!(started.elapsed() < std::time::Duration::from_secs(10));
+}
+
+#[test]
+fn test_parse_json_commands_reads_definitions() {
+    use tauri_plugin_php::framework_command::parse_json_commands;
+
+    // PHP 的弃用提示出现在 JSON 之前；空定义被编码为 []
+    let output = r#"Deprecated: something in vendor/foo.php on line 3
+{
+    "application": {"name": "Laravel Framework", "version": "11.0.0"},
+    "commands": [
+        {
+            "name": "make:controller",
+            "description": "Create a new controller class",
+            "hidden": false,
+            "definition": {
+                "arguments": {
+                    "name": {"name": "name", "is_required": true, "is_array": false, "description": "The name of the controller"}
+                },
+                "options": {
+                    "force": {"name": "--force", "shortcut": "", "accept_value": false, "description": "Overwrite"},
+                    "model": {"name": "--model", "shortcut": "-m", "accept_value": true, "description": "Generate a resource controller"}
+                }
+            }
+        },
+        {"name": "inspire", "description": "Display an inspiring quote", "definition": {"arguments": [], "options": []}},
+        {"name": "_complete", "description": "Internal", "hidden": true, "definition": {"arguments": [], "options": []}}
+    ]
+}"#;
+    let commands = parse_json_commands(output).unwrap();
+    assert_eq!(commands.len(), 2);
+
+    let make = &commands[0];
+    assert_eq!(make.name, "make:controller");
+    assert_eq!(make.arguments.len(), 1);
+    assert!(make.arguments[0].required);
+    assert_eq!(make.options[0].name, "--force");
+    assert_eq!(make.options[0].shortcut, None);
+    assert_eq!(make.options[1].shortcut.as_deref(), Some("-m"));
+    assert!(make.options[1].accepts_value);
+    assert!(commands[1].arguments.is_empty());
+
+    assert!(parse_json_commands("PHP Fatal error").is_err());
+}
+
+#[test]
+fn test_parse_text_commands_for_each_console() {
+    use tauri_plugin_php::framework_command::parse_text_commands;
+
+    let names = |output: &str| -> Vec<String> {
+        parse_text_commands(output)
+            .into_iter()
+            .map(|command| command.name)
+            .collect()
+    };
+
+    // ThinkPHP：选项之后才是命令列表，分组标题缩进一格
+    let think = "Usage:\n  command [options] [arguments]\n\nOptions:\n  -h, --help            Display this help message\n\nAvailable commands:\n  build              Build App Dirs\n  list               Lists commands\n \u{1b}[33mmake\u{1b}[39m\n  \u{1b}[32mmake:command\u{1b}[39m       Create a new command class\n";
+    assert_eq!(names(think), vec!["build", "list", "make:command"]);
+    let commands = parse_text_commands(think);
+    assert_eq!(commands[2].description, "Create a new command class");
+
+    // CodeIgniter：没有标题，分组不缩进
+    let spark = "CodeIgniter v4.5.1 Command Line Tool - Server Time: 2024-05-01 10:00:00 UTC+00:00\n\nCache\n  cache:clear        Clears the current system caches.\n  cache:info         Shows file cache information in the current system.\n\nDatabase\n  migrate            Locates and runs all new migrations against the database.\n";
+    assert_eq!(names(spark), vec!["cache:clear", "cache:info", "migrate"]);
+
+    // Yii：控制器和动作，换行的描述与结尾提示不是命令
+    let yii = "\nThis is Yii version 2.0.49.\n\nThe following commands are available:\n\n- asset                         Allows you to combine and compress your JavaScript and CSS files.\n    asset/compress (default)    Combines and compresses the asset files according to the given configuration.\n                                files.\n- migrate                       Manages application migrations.\n\nTo see the help of each command, enter:\n\n  yii help <command-name>\n";
+    assert_eq!(names(yii), vec!["asset", "asset/compress", "migrate"]);
+
+    // CakePHP：命令名包含空格
+    let cake = "Current Paths:\n\n* app:  src/\n* root: /var/www\n\nAvailable Commands:\n\nApp:\n - hello\n\nCakePHP:\n - cache clear         Clear all data in a single cache engine\n - routes\n\nTo run a command, type `cake command_name [args|options]`\n";
+    assert_eq!(names(cake), vec!["hello", "cache clear", "routes"]);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_list_framework_commands_caches_by_lock_hash() {
+    let temp_dir = common::create_temp_dir();
+    let calls = temp_dir.path().join("calls.log");
+    let listing = temp_dir.path().join("list.json");
+    fs::write(
+        &listing,
+        r#"{"commands": [{"name": "migrate", "description": "Run the database migrations", "definition": {"arguments": [], "options": []}}]}"#,
+    )
+    .unwrap();
+    // 模拟 PHP：记录调用并输出命令列表
+    let (manager, project) = manager_with_project(
+        temp_dir.path(),
+        "laravel",
+        &format!(
+            "#!/bin/sh\necho \"$*\" >> '{}'\ncat '{}'\n",
+            calls.display(),
+            listing.display()
+        ),
+    );
+    let call_count = || fs::read_to_string(&calls).unwrap().lines().count();
+
+    // 没有 composer.lock 时不缓存
+    let list = manager
+        .list_framework_commands(&project, false)
+        .await
+        .unwrap();
+    assert_eq!(list.script, "artisan");
+    assert_eq!(list.commands[0].name, "migrate");
+    assert_eq!(list.lock_hash, None);
+    manager
+        .list_framework_commands(&project, false)
+        .await
+        .unwrap();
+    assert_eq!(call_count(), 2);
+    assert_eq!(
+        fs::read_to_string(&calls).unwrap().lines().next(),
+        Some("artisan list --format=json")
+    );
+
+    // 锁文件不变时使用缓存
+    fs::write(project.join("composer.lock"), r#"{"packages": []}"#).unwrap();
+    let first = manager
+        .list_framework_commands(&project, false)
+        .await
+        .unwrap();
+    assert!(!first.cached);
+    let second = manager
+        .list_framework_commands(&project, false)
+        .await
+        .unwrap();
+    assert!(second.cached);
+    assert_eq!(second.lock_hash, first.lock_hash);
+    assert_eq!(call_count(), 3);
+
+    // 命令在快照上查询，缓存与原管理器共享
+    let snapshot = manager.clone();
+    assert!(
+        snapshot
+            .list_framework_commands(&project, false)
+            .await
+            .unwrap()
+            .cached
+    );
+    assert_eq!(call_count(), 3);
+
+    // 锁文件变化或强制刷新时重新查询
+    fs::write(
+        project.join("composer.lock"),
+        r#"{"packages": [{"name": "laravel/framework", "version": "v11.0.0"}]}"#,
+    )
+    .unwrap();
+    assert!(
+        !manager
+            .list_framework_commands(&project, false)
+            .await
+            .unwrap()
+            .cached
+    );
+    assert!(
+        !manager
+            .list_framework_commands(&project, true)
+            .await
+            .unwrap()
+            .cached
+    );
+    assert_eq!(call_count(), 5);
+}
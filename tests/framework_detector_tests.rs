@@ -1,5 +1,6 @@
-use tauri_plugin_php::framework_detector::FrameworkDetector;
-use tauri_plugin_php::models::Framework;
+use tauri_plugin_php::framework_detector::{console_script, FrameworkDetector};
+use tauri_plugin_php::models::{Framework, FrameworkVersion, LaravelSetupOptions, VersionSource};
+use tauri_plugin_php::setup_pipeline::default_pipeline;
 
 mod common;
 
@@ -162,9 +163,32 @@ async fn test_thinkphp_version_detection() {
     let tp3_project = temp_dir.path().join("thinkphp3");
     std::fs::create_dir_all(tp3_project.join("ThinkPHP")).unwrap();
     std::fs::create_dir_all(tp3_project.join("Application")).unwrap();
+    std::fs::write(
+        tp3_project.join("ThinkPHP/ThinkPHP.php"),
+        "<?php
+const THINK_VERSION = '3.2.3';",
+    )
+    .unwrap();
 
     let result = detector.detect_framework(&tp3_project).unwrap();
     assert_eq!(result, Framework::ThinkPHP);
+
+    // 仅有 Application/ 或 application/ 目录不足以识别为 ThinkPHP
+    for folder in ["Application", "application"] {
+        let project = temp_dir.path().join(format!("plain-{}", folder));
+        std::fs::create_dir_all(project.join(folder)).unwrap();
+        std::fs::create_dir_all(project.join("public")).unwrap();
+        std::fs::write(
+            project.join("index.php"),
+            "<?php
+",
+        )
+        .unwrap();
+        assert_eq!(
+            detector.detect_framework(&project).unwrap(),
+            Framework::Plain
+        );
+    }
 }
 
 #[tokio::test]
@@ -223,3 +247,258 @@ async fn test_partial_framework_files() {
     // 应该仍然检测为 Laravel，因为 artisan 是强特征
     assert_eq!(result, Framework::Laravel);
 }
+
+#[test]
+fn test_rank_frameworks_reports_evidence() {
+    use tauri_plugin_php::models::EvidenceKind;
+
+    let temp_dir = common::create_temp_dir();
+    let project_path = common::create_mock_php_project(temp_dir.path(), "laravel");
+
+    let detector = FrameworkDetector::new();
+    let candidates = detector.rank_frameworks(&project_path).unwrap();
+    let best = &candidates[0];
+    assert_eq!(best.framework, Framework::Laravel);
+    assert_eq!(best.confidence, 1.0);
+    assert!(best.evidence.iter().any(|evidence| {
+        evidence.kind == EvidenceKind::RequiredPackage && evidence.source == "laravel/framework"
+    }));
+    assert!(best
+        .evidence
+        .iter()
+        .any(|evidence| evidence.kind == EvidenceKind::File && evidence.source == "artisan"));
+
+    // 共有的 config/app.php 只给 CakePHP 很低的分数
+    let cake = candidates
+        .iter()
+        .find(|candidate| candidate.framework == Framework::CakePHP)
+        .unwrap();
+    assert!(cake.score < 10);
+    assert!(candidates
+        .windows(2)
+        .all(|pair| pair[0].score >= pair[1].score));
+}
+
+#[test]
+fn test_locked_package_outweighs_shared_layout() {
+    let temp_dir = common::create_temp_dir();
+    let detector = FrameworkDetector::new();
+
+    // CakePHP 项目同时具有 ThinkPHP 5 常见的目录
+    let cake = temp_dir.path().join("cake");
+    std::fs::create_dir_all(cake.join("config")).unwrap();
+    std::fs::create_dir_all(cake.join("application")).unwrap();
+    std::fs::create_dir_all(cake.join("public")).unwrap();
+    std::fs::write(cake.join("config/app.php"), "<?php\nreturn [];").unwrap();
+    std::fs::write(cake.join("public/index.php"), "<?php\n").unwrap();
+    std::fs::write(
+        cake.join("composer.lock"),
+        r#"{ "packages": [{ "name": "cakephp/cakephp", "version": "5.0.0" }] }"#,
+    )
+    .unwrap();
+    assert_eq!(
+        detector.detect_framework(&cake).unwrap(),
+        Framework::CakePHP
+    );
+
+    // 仅有 application/ 和 public/ 不再被识别为 ThinkPHP
+    let generic = temp_dir.path().join("generic");
+    std::fs::create_dir_all(generic.join("application")).unwrap();
+    std::fs::create_dir_all(generic.join("public")).unwrap();
+    std::fs::write(generic.join("index.php"), "<?php\n").unwrap();
+    assert_eq!(
+        detector.detect_framework(&generic).unwrap(),
+        Framework::Plain
+    );
+
+    // Lumen 项目也有 artisan，但锁定的 lumen-framework 优先
+    let lumen = temp_dir.path().join("lumen");
+    std::fs::create_dir_all(lumen.join("bootstrap")).unwrap();
+    std::fs::write(lumen.join("artisan"), "#!/usr/bin/env php").unwrap();
+    std::fs::write(
+        lumen.join("bootstrap/app.php"),
+        "<?php\n$app = new Laravel\\Lumen\\Application(dirname(__DIR__));",
+    )
+    .unwrap();
+    std::fs::write(
+        lumen.join("composer.lock"),
+        r#"{ "packages": [{ "name": "laravel/lumen-framework", "version": "v10.0.0" }] }"#,
+    )
+    .unwrap();
+    let candidates = detector.rank_frameworks(&lumen).unwrap();
+    assert_eq!(candidates[0].framework, Framework::Lumen);
+    assert_eq!(candidates[1].framework, Framework::Laravel);
+}
+
+#[tokio::test]
+async fn test_detect_version_from_lock_and_constants() {
+    let temp_dir = common::create_temp_dir();
+    let detector = FrameworkDetector::new();
+
+    // composer.lock 中的版本优先
+    let locked = temp_dir.path().join("locked");
+    std::fs::create_dir_all(&locked).unwrap();
+    std::fs::write(
+        locked.join("composer.lock"),
+        r#"{ "packages": [{ "name": "laravel/framework", "version": "v10.48.2" }] }"#,
+    )
+    .unwrap();
+    assert_eq!(
+        detector.detect_version(&locked, &Framework::Laravel),
+        Some(FrameworkVersion {
+            major: 10,
+            minor: Some(48),
+            source: VersionSource::LockFile,
+        })
+    );
+
+    // 分支版本无法解析，回退到 vendor 中的 VERSION 常量
+    std::fs::write(
+        locked.join("composer.lock"),
+        r#"{ "packages": [{ "name": "laravel/framework", "version": "dev-master" }] }"#,
+    )
+    .unwrap();
+    let source = locked.join("vendor/laravel/framework/src/Illuminate/Foundation");
+    std::fs::create_dir_all(&source).unwrap();
+    std::fs::write(
+        source.join("Application.php"),
+        "<?php\nclass Application\n{\n    const VERSION = '8.83.27';\n}",
+    )
+    .unwrap();
+    let version = detector
+        .detect_version(&locked, &Framework::Laravel)
+        .unwrap();
+    assert_eq!((version.major, version.minor), (8, Some(83)));
+    assert_eq!(version.source, VersionSource::VersionConstant);
+
+    // 框架自带的源码：ThinkPHP 5.0 的 THINK_VERSION
+    let tp5 = temp_dir.path().join("tp5");
+    std::fs::create_dir_all(tp5.join("thinkphp")).unwrap();
+    std::fs::write(
+        tp5.join("thinkphp/base.php"),
+        "<?php\ndefine('THINK_VERSION', '5.0.24');",
+    )
+    .unwrap();
+    let version = detector.detect_version(&tp5, &Framework::ThinkPHP).unwrap();
+    assert_eq!((version.major, version.minor), (5, Some(0)));
+
+    // 没有任何线索时版本未知
+    let empty = temp_dir.path().join("empty");
+    std::fs::create_dir_all(&empty).unwrap();
+    assert_eq!(detector.detect_version(&empty, &Framework::Symfony), None);
+}
+
+#[tokio::test]
+async fn test_detect_version_from_layout() {
+    let temp_dir = common::create_temp_dir();
+    let detector = FrameworkDetector::new();
+    let major = |path: &std::path::Path, framework: Framework| {
+        detector
+            .detect_version(path, &framework)
+            .map(|version| (version.major, version.minor, version.source))
+    };
+
+    let tp3 = temp_dir.path().join("tp3");
+    std::fs::create_dir_all(tp3.join("ThinkPHP")).unwrap();
+    std::fs::create_dir_all(tp3.join("Application")).unwrap();
+    std::fs::write(
+        tp3.join("ThinkPHP/ThinkPHP.php"),
+        "<?php
+",
+    )
+    .unwrap();
+    assert_eq!(
+        major(&tp3, Framework::ThinkPHP),
+        Some((3, None, VersionSource::Layout))
+    );
+
+    // ThinkPHP 5 的 thinkphp/ 与 application/ 不会被当作 3.x
+    let tp5 = temp_dir.path().join("tp5-layout");
+    std::fs::create_dir_all(tp5.join("thinkphp")).unwrap();
+    std::fs::create_dir_all(tp5.join("application")).unwrap();
+    assert_eq!(
+        major(&tp5, Framework::ThinkPHP),
+        Some((5, None, VersionSource::Layout))
+    );
+
+    let tp6 = temp_dir.path().join("tp6");
+    std::fs::create_dir_all(tp6.join("app")).unwrap();
+    std::fs::write(tp6.join("think"), "#!/usr/bin/env php").unwrap();
+    assert_eq!(
+        major(&tp6, Framework::ThinkPHP),
+        Some((6, None, VersionSource::Layout))
+    );
+
+    let ci3 = temp_dir.path().join("ci3");
+    std::fs::create_dir_all(ci3.join("application/config")).unwrap();
+    std::fs::write(ci3.join("application/config/config.php"), "<?php\n").unwrap();
+    assert_eq!(
+        major(&ci3, Framework::CodeIgniter),
+        Some((3, None, VersionSource::Layout))
+    );
+
+    let ci4 = temp_dir.path().join("ci4");
+    std::fs::create_dir_all(&ci4).unwrap();
+    std::fs::write(ci4.join("spark"), "#!/usr/bin/env php").unwrap();
+    assert_eq!(
+        major(&ci4, Framework::CodeIgniter),
+        Some((4, None, VersionSource::Layout))
+    );
+
+    let laravel11 = temp_dir.path().join("laravel11");
+    std::fs::create_dir_all(laravel11.join("bootstrap")).unwrap();
+    std::fs::write(
+        laravel11.join("bootstrap/app.php"),
+        "<?php\nreturn Application::configure(basePath: dirname(__DIR__))->create();",
+    )
+    .unwrap();
+    assert_eq!(
+        major(&laravel11, Framework::Laravel),
+        Some((11, None, VersionSource::Layout))
+    );
+}
+
+#[tokio::test]
+async fn test_framework_info_follows_version() {
+    let detector = FrameworkDetector::new();
+    let version = |major: u32, minor: u32| FrameworkVersion {
+        major,
+        minor: Some(minor),
+        source: VersionSource::LockFile,
+    };
+
+    let ci4 = detector.framework_info(&Framework::CodeIgniter, Some(&version(4, 4)));
+    assert_eq!(ci4.default_entry_point, "public/index.php");
+    assert_eq!(ci4.console_script, Some("spark"));
+    let ci3 = detector.framework_info(&Framework::CodeIgniter, Some(&version(3, 1)));
+    assert_eq!(ci3.default_entry_point, "index.php");
+    assert_eq!(ci3.console_script, None);
+
+    let tp3 = detector.framework_info(&Framework::ThinkPHP, Some(&version(3, 2)));
+    assert_eq!(tp3.default_entry_point, "index.php");
+    assert!(!tp3.requires_composer);
+    assert_eq!(
+        tp3.setup_commands,
+        vec![
+            "composer install --no-dev (if composer.json exists)",
+            "mkdir -p Application/Runtime",
+        ]
+    );
+
+    assert_eq!(
+        console_script(&Framework::Symfony, Some(&version(2, 8))),
+        Some("app/console")
+    );
+
+    // Laravel 5.2 还没有 storage:link 和 optimize:clear
+    let names: Vec<String> = default_pipeline(
+        &Framework::Laravel,
+        Some(&version(5, 2)),
+        &LaravelSetupOptions::default(),
+    )
+    .steps
+    .iter()
+    .map(|step| step.display_name())
+    .collect();
+    assert_eq!(names, vec!["composer install", ".env", "key:generate"]);
+}
@@ -0,0 +1,139 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tauri_plugin_php::gateway::{
+    gateway_routes, path_below_prefix, resolve_gateway_target, Gateway,
+};
+use tauri_plugin_php::models::{ServerBackend, ServerStatus};
+use tauri_plugin_php::php_server::PhpServerManager;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::Mutex;
+
+fn running_status(project_path: &str, port: u16) -> ServerStatus {
+    ServerStatus {
+        is_running: true,
+        pid: Some(1),
+        port: Some(port),
+        host: Some("127.0.0.1".to_string()),
+        document_root: Some(project_path.to_string()),
+        started_at: None,
+        https: false,
+        project_path: Some(project_path.to_string()),
+        backend: ServerBackend::BuiltIn,
+    }
+}
+
+async fn get(addr: SocketAddr, host: &str, path: &str) -> String {
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    let request = format!(
+        "GET {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n\r\n",
+        path, host
+    );
+    stream.write_all(request.as_bytes()).await.unwrap();
+
+    let mut response = Vec::new();
+    let _ = stream.read_to_end(&mut response).await;
+    String::from_utf8_lossy(&response).to_string()
+}
+
+#[test]
+fn test_resolve_gateway_target_from_host() {
+    let target = resolve_gateway_target(Some("project-a.localhost:8080"), "/admin").unwrap();
+    assert_eq!(target.project, "project-a");
+    assert!(target.prefix.is_none());
+
+    // 多级子域名取最靠近 localhost 的一级
+    let target = resolve_gateway_target(Some("www.Project-B.localhost"), "/").unwrap();
+    assert_eq!(target.project, "project-b");
+}
+
+#[test]
+fn test_resolve_gateway_target_from_path_prefix() {
+    let target = resolve_gateway_target(Some("localhost:8080"), "/project-a/index.php").unwrap();
+    assert_eq!(target.project, "project-a");
+    assert_eq!(target.prefix, Some("/project-a".to_string()));
+
+    let target = resolve_gateway_target(Some("127.0.0.1:8080"), "/project-a").unwrap();
+    assert_eq!(target.project, "project-a");
+
+    // 根路径没有项目，由网关返回项目列表
+    assert!(resolve_gateway_target(Some("localhost:8080"), "/").is_none());
+    assert!(resolve_gateway_target(None, "/").is_none());
+}
+
+#[test]
+fn test_path_below_prefix_with_doubled_slash() {
+    // 多余的前导斜杠与解析项目时一样被忽略
+    let target = resolve_gateway_target(Some("localhost:8080"), "//project-a/x").unwrap();
+    let prefix = target.prefix.unwrap();
+    assert_eq!(
+        path_below_prefix("//project-a/x", &prefix),
+        Some("/x".to_string())
+    );
+    assert_eq!(
+        path_below_prefix("/project-a/x", &prefix),
+        Some("/x".to_string())
+    );
+    assert_eq!(
+        path_below_prefix("/project-a", &prefix),
+        Some(String::new())
+    );
+
+    // 多字节字符不会导致切片越界；不匹配的路径返回 None
+    let target = resolve_gateway_target(Some("localhost"), "///项目/页面").unwrap();
+    let prefix = target.prefix.unwrap();
+    assert_eq!(
+        path_below_prefix("///项目/页面", &prefix),
+        Some("/页面".to_string())
+    );
+    assert_eq!(path_below_prefix("/project-a/x", &prefix), None);
+    assert_eq!(path_below_prefix("/project-ab/x", "/project-a"), None);
+}
+
+#[test]
+fn test_gateway_routes_from_running_servers() {
+    let servers = vec![
+        (
+            "server-2".to_string(),
+            running_status("/projects/ProjectB", 8001),
+        ),
+        (
+            "server-1".to_string(),
+            running_status("/projects/project-a", 8000),
+        ),
+    ];
+
+    let routes = gateway_routes(&servers, 8080);
+
+    assert_eq!(routes.len(), 2);
+    assert_eq!(routes[0].project, "ProjectB");
+    assert_eq!(routes[0].host_url, "http://projectb.localhost:8080/");
+    assert_eq!(routes[0].path_url, "http://localhost:8080/ProjectB/");
+    assert_eq!(routes[1].project, "project-a");
+    assert_eq!(routes[1].server_id, "server-1");
+    assert_eq!(routes[1].upstream_port, Some(8000));
+}
+
+#[tokio::test]
+async fn test_gateway_index_and_missing_project() {
+    let server_manager = Arc::new(Mutex::new(PhpServerManager::new()));
+    let gateway = Gateway::start(SocketAddr::from(([127, 0, 0, 1], 0)), server_manager)
+        .await
+        .unwrap();
+
+    let response = get(gateway.local_addr(), "localhost", "/").await;
+    assert!(response.starts_with("HTTP/1.1 200 OK"));
+    assert!(response.contains("No PHP servers are running"));
+
+    let response = get(gateway.local_addr(), "project-a.localhost", "/").await;
+    assert!(response.starts_with("HTTP/1.1 503"));
+    assert!(response.contains("project-a"));
+
+    let response = get(gateway.local_addr(), "localhost", "/project-b/").await;
+    assert!(response.starts_with("HTTP/1.1 503"));
+
+    // 多余的前导斜杠同样解析到项目
+    let response = get(gateway.local_addr(), "localhost", "//project-b/x").await;
+    assert!(response.starts_with("HTTP/1.1 503"));
+    assert!(response.contains("project-b"));
+}
@@ -0,0 +1,157 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tauri_plugin_php::proxy::HttpsProxy;
+use tauri_plugin_php::tls::{self, LocalCertificateAuthority};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_rustls::rustls::crypto::ring as ring_provider;
+use tokio_rustls::rustls::pki_types::pem::PemObject;
+use tokio_rustls::rustls::pki_types::{CertificateDer, ServerName};
+use tokio_rustls::rustls::{ClientConfig, RootCertStore};
+use tokio_rustls::TlsConnector;
+
+mod common;
+
+/// 启动一个简单的 HTTP 上游服务器，将收到的请求头原样写回响应体
+async fn start_echo_upstream() -> SocketAddr {
+    let listener = TcpListener::bind(("127.0.0.1", 0)).await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let mut buffer = vec![0u8; 8192];
+                let mut received = Vec::new();
+                loop {
+                    let n = stream.read(&mut buffer).await.unwrap_or(0);
+                    if n == 0 {
+                        return;
+                    }
+                    received.extend_from_slice(&buffer[..n]);
+                    if received.windows(4).any(|w| w == b"\r\n\r\n") {
+                        break;
+                    }
+                }
+
+                let body = String::from_utf8_lossy(&received).to_lowercase();
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            });
+        }
+    });
+
+    addr
+}
+
+fn client_config(authority: &LocalCertificateAuthority) -> Arc<ClientConfig> {
+    let mut roots = RootCertStore::empty();
+    roots
+        .add(CertificateDer::from_pem_slice(authority.ca_cert_pem().as_bytes()).unwrap())
+        .unwrap();
+
+    Arc::new(
+        ClientConfig::builder_with_provider(Arc::new(ring_provider::default_provider()))
+            .with_safe_default_protocol_versions()
+            .unwrap()
+            .with_root_certificates(roots)
+            .with_no_client_auth(),
+    )
+}
+
+#[test]
+fn test_certificate_authority_is_created_and_reused() {
+    let temp_dir = common::create_temp_dir();
+    let certs_dir = temp_dir.path().join("certs");
+
+    let authority = LocalCertificateAuthority::load_or_create(&certs_dir).unwrap();
+    assert!(authority.ca_cert_path().exists());
+    assert!(certs_dir.join("ca-key.pem").exists());
+    assert!(authority.ca_cert_pem().contains("BEGIN CERTIFICATE"));
+
+    // 第二次加载应复用同一个 CA，而不是重新生成
+    let reloaded = LocalCertificateAuthority::load_or_create(&certs_dir).unwrap();
+    assert_eq!(authority.ca_cert_pem(), reloaded.ca_cert_pem());
+}
+
+#[test]
+fn test_issue_server_certificate_includes_ca_in_chain() {
+    let temp_dir = common::create_temp_dir();
+    let authority = LocalCertificateAuthority::load_or_create(temp_dir.path()).unwrap();
+
+    let certified_key = authority
+        .issue_server_certificate("project-a.localhost")
+        .unwrap();
+
+    assert_eq!(certified_key.cert.len(), 2);
+}
+
+#[tokio::test]
+async fn test_https_proxy_forwards_to_upstream() {
+    let temp_dir = common::create_temp_dir();
+    let authority = Arc::new(LocalCertificateAuthority::load_or_create(temp_dir.path()).unwrap());
+
+    let upstream = start_echo_upstream().await;
+    let tls_config = tls::server_config(authority.clone(), "localhost").unwrap();
+    let proxy = HttpsProxy::start(SocketAddr::from(([127, 0, 0, 1], 0)), upstream, tls_config)
+        .await
+        .unwrap();
+
+    // 证书由本地 CA 签发，客户端只信任该 CA 即可完成握手
+    let connector = TlsConnector::from(client_config(&authority));
+    let stream = TcpStream::connect(proxy.local_addr()).await.unwrap();
+    let mut tls_stream = connector
+        .connect(ServerName::try_from("localhost").unwrap(), stream)
+        .await
+        .unwrap();
+
+    tls_stream
+        .write_all(b"GET /hello?name=php HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+        .await
+        .unwrap();
+
+    let mut response = Vec::new();
+    let _ = tls_stream.read_to_end(&mut response).await;
+    let response = String::from_utf8_lossy(&response);
+
+    assert!(response.starts_with("HTTP/1.1 200 OK"));
+    assert!(response.contains("get /hello?name=php http/1.1"));
+    assert!(response.contains("x-forwarded-proto: https"));
+    assert!(response.contains("x-forwarded-host: localhost"));
+}
+
+#[tokio::test]
+async fn test_https_proxy_returns_bad_gateway_without_upstream() {
+    let temp_dir = common::create_temp_dir();
+    let authority = Arc::new(LocalCertificateAuthority::load_or_create(temp_dir.path()).unwrap());
+
+    // 绑定后立即释放，得到一个大概率无人监听的端口
+    let unused = std::net::TcpListener::bind(("127.0.0.1", 0))
+        .unwrap()
+        .local_addr()
+        .unwrap();
+
+    let tls_config = tls::server_config(authority.clone(), "localhost").unwrap();
+    let proxy = HttpsProxy::start(SocketAddr::from(([127, 0, 0, 1], 0)), unused, tls_config)
+        .await
+        .unwrap();
+
+    let connector = TlsConnector::from(client_config(&authority));
+    let stream = TcpStream::connect(proxy.local_addr()).await.unwrap();
+    let mut tls_stream = connector
+        .connect(ServerName::try_from("localhost").unwrap(), stream)
+        .await
+        .unwrap();
+
+    tls_stream
+        .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+        .await
+        .unwrap();
+
+    let mut response = Vec::new();
+    let _ = tls_stream.read_to_end(&mut response).await;
+    assert!(String::from_utf8_lossy(&response).starts_with("HTTP/1.1 502"));
+}
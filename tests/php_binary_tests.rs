@@ -1,5 +1,5 @@
 use std::fs;
-use tauri_plugin_php::php_binary::PhpBinaryManager;
+use tauri_plugin_php::php_binary::{parse_module_list, PhpBinaryManager};
 
 mod common;
 
@@ -244,3 +244,57 @@ async fn test_binary_info_structure() {
     assert!(binary_info.download_url.is_some());
     assert!(binary_info.size.is_some());
 }
+
+#[test]
+fn test_parse_module_list() {
+    let output =
+        "[PHP Modules]\nCore\nctype\nmbstring\nPDO\nZend OPcache\n\n[Zend Modules]\nZend OPcache\n";
+
+    // 名称与 Composer 的 ext-* 写法一致，并去重排序
+    assert_eq!(
+        parse_module_list(output),
+        vec!["core", "ctype", "mbstring", "pdo", "zend-opcache"]
+    );
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_check_extensions_with_php_binary() {
+    use tauri_plugin_php::models::RequiredExtension;
+    use tauri_plugin_php::php_binary::check_extensions;
+
+    let temp_dir = common::create_temp_dir();
+    let php = common::create_mock_php_binary(temp_dir.path(), "8.3.1");
+    fs::write(
+        &php,
+        "#!/bin/sh\n[ \"$1\" = \"-m\" ] || exit 1\nprintf '[PHP Modules]\\nCore\\nmbstring\\nopenssl\\n'\n",
+    )
+    .unwrap();
+
+    let mut manager = PhpBinaryManager::new();
+    manager.set_php_directory(temp_dir.path().join("php-binaries"));
+    assert_eq!(
+        manager.get_loaded_extensions("8.3").await.unwrap(),
+        vec!["core", "mbstring", "openssl"]
+    );
+
+    let required = vec![
+        RequiredExtension {
+            name: "mbstring".to_string(),
+            required_by: vec!["Laravel".to_string()],
+            dev: false,
+        },
+        RequiredExtension {
+            name: "intl".to_string(),
+            required_by: vec!["composer.json".to_string()],
+            dev: false,
+        },
+    ];
+    let report = check_extensions(&php, required).await.unwrap();
+    assert_eq!(report.required.len(), 2);
+    assert_eq!(report.missing.len(), 1);
+    assert_eq!(report.missing[0].name, "intl");
+
+    // 未安装的版本
+    assert!(manager.get_loaded_extensions("7.4").await.is_err());
+}
@@ -1,4 +1,4 @@
-use tauri_plugin_php::models::{ServerStatus, StartServerRequest};
+use tauri_plugin_php::models::{ServerBackend, ServerStatus, StartServerRequest};
 use tauri_plugin_php::php_server::PhpServerManager;
 
 mod common;
@@ -15,7 +15,9 @@ async fn test_find_available_port() {
     let manager = PhpServerManager::new();
 
     let start_port = 8000;
-    let available_port = manager.find_available_port(start_port);
+    let available_port = manager
+        .find_available_port(start_port..=65535, &[])
+        .unwrap();
 
     // 验证返回的端口在合理范围内
     assert!(available_port >= start_port);
@@ -33,6 +35,63 @@ async fn test_find_available_port() {
     drop(listener_result);
 }
 
+#[tokio::test]
+async fn test_find_available_port_respects_range_and_exclusions() {
+    let manager = PhpServerManager::new();
+
+    let start_port = common::find_available_port(20000);
+    let port = manager
+        .find_available_port(start_port..=start_port + 50, &[start_port])
+        .unwrap();
+
+    assert!(port > start_port);
+    assert!(port <= start_port + 50);
+}
+
+#[tokio::test]
+async fn test_find_available_port_exhausted_range() {
+    let manager = PhpServerManager::new();
+
+    // 占用唯一的端口，使范围耗尽
+    let port = common::find_available_port(21000);
+    let _listener = std::net::TcpListener::bind(("127.0.0.1", port)).unwrap();
+
+    let result = manager.find_available_port(port..=port, &[]);
+    assert!(matches!(
+        result,
+        Err(tauri_plugin_php::Error::NoAvailablePort(start, end)) if start == port && end == port
+    ));
+
+    // 全部被排除的范围同样应该返回错误而不是 panic
+    let result = manager.find_available_port(22000..=22001, &[22000, 22001]);
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn test_reserve_port_is_not_handed_out_twice() {
+    let mut manager = PhpServerManager::new();
+
+    let start_port = common::find_available_port(23000);
+    let first = manager
+        .reserve_port(start_port..=start_port + 50, &[])
+        .unwrap();
+    let second = manager
+        .reserve_port(start_port..=start_port + 50, &[])
+        .unwrap();
+
+    assert_ne!(first, second);
+    assert!(manager.is_port_reserved(first));
+    assert!(manager.is_port_reserved(second));
+
+    manager.release_port(first);
+    assert!(!manager.is_port_reserved(first));
+
+    // 单端口范围在被预留后应视为耗尽
+    let single = common::find_available_port(24000);
+    manager.reserve_port(single..=single, &[]).unwrap();
+    assert!(manager.reserve_port(single..=single, &[]).is_err());
+}
+
 #[tokio::test]
 async fn test_server_status_for_nonexistent_server() {
     let manager = PhpServerManager::new();
@@ -45,6 +104,7 @@ async fn test_server_status_for_nonexistent_server() {
     assert!(status.host.is_none());
     assert!(status.document_root.is_none());
     assert!(status.started_at.is_none());
+    assert!(status.project_path.is_none());
 }
 
 #[tokio::test]
@@ -91,6 +151,7 @@ async fn test_start_server_request_validation() {
         host: Some("127.0.0.1".to_string()),
         php_version: Some("8.3.0".to_string()),
         document_root: Some(project_path.to_string_lossy().to_string()),
+        ..Default::default()
     };
 
     // 注意：这个测试可能会失败，因为我们使用的是模拟的 PHP 二进制文件
@@ -135,6 +196,9 @@ async fn test_server_status_structure() {
         host: Some("127.0.0.1".to_string()),
         document_root: Some("/path/to/project".to_string()),
         started_at: Some("2024-01-01T00:00:00Z".to_string()),
+        https: false,
+        project_path: Some("/path/to/project".to_string()),
+        backend: ServerBackend::BuiltIn,
     };
 
     assert!(status.is_running);
@@ -153,6 +217,7 @@ async fn test_start_server_request_structure() {
         host: Some("127.0.0.1".to_string()),
         php_version: Some("8.3.0".to_string()),
         document_root: Some("/path/to/project/public".to_string()),
+        ..Default::default()
     };
 
     assert_eq!(request.project_path, "/path/to/project");
@@ -216,7 +281,9 @@ async fn test_concurrent_server_operations() {
             task::spawn(async move {
                 let manager = PhpServerManager::new();
                 let start_port = 8000 + i * 100;
-                let port = manager.find_available_port(start_port);
+                let port = manager
+                    .find_available_port(start_port..=65535, &[])
+                    .unwrap();
 
                 // 验证端口查找 - 端口应该大于等于起始端口
                 assert!(
@@ -1,6 +1,17 @@
+use std::collections::BTreeMap;
 use std::fs;
-use tauri_plugin_php::models::{CloneProjectRequest, Framework, ProjectInfo};
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Mutex};
+use tauri_plugin_php::git::{CloneRegistry, ProgressCallback};
+use tauri_plugin_php::models::{
+    CloneProgress, CloneProjectRequest, CloneStage, CreateProjectRequest, Framework, GitChange,
+    GitCredentials, ProjectInfo, ProjectSettings, RemoveProjectOptions, StartServerRequest,
+};
 use tauri_plugin_php::project_manager::ProjectManager;
+use tauri_plugin_php::project_registry::ProjectRegistry;
+use tauri_plugin_php::project_templates;
+use tauri_plugin_php::secret_store::{MemorySecretStore, SecretStore, SharedSecretStore};
+use tauri_plugin_php::Error;
 
 mod common;
 
@@ -45,6 +56,7 @@ async fn test_clone_project_request_structure() {
         destination: Some("/path/to/destination".to_string()),
         branch: Some("main".to_string()),
         auto_setup: true,
+        ..Default::default()
     };
 
     assert_eq!(request.git_url, "https://github.com/user/repo.git");
@@ -62,9 +74,13 @@ async fn test_project_info_structure() {
         name: "test-project".to_string(),
         path: "/path/to/project".to_string(),
         framework: Some(Framework::Laravel),
+        framework_version: None,
         git_url: Some("https://github.com/user/repo.git".to_string()),
         php_version: Some("8.3.0".to_string()),
         entry_point: Some("public/index.php".to_string()),
+        id: None,
+        settings: Default::default(),
+        setup: None,
     };
 
     assert_eq!(project_info.name, "test-project");
@@ -383,3 +399,1319 @@ async fn test_concurrent_project_operations() {
         assert!(project_path.exists());
     }
 }
+
+#[tokio::test]
+async fn test_clone_project_from_local_path() {
+    let temp_dir = common::create_temp_dir();
+    let remote = common::create_git_fixture_repo(&temp_dir.path().join("remote-app"), "laravel");
+    let projects_dir = temp_dir.path().join("projects");
+
+    let mut manager = common::isolated_project_manager(temp_dir.path());
+    manager.set_projects_directory(projects_dir.clone());
+
+    let request = CloneProjectRequest {
+        git_url: remote.to_string_lossy().to_string(),
+        destination: None,
+        branch: None,
+        auto_setup: false,
+        ..Default::default()
+    };
+    let project = manager.clone_project(request).await.unwrap();
+
+    // 克隆结果是真实的仓库，并检出默认分支
+    assert_eq!(project.name, "remote-app");
+    assert_eq!(project.framework, Some(Framework::Laravel));
+    let cloned = projects_dir.join("remote-app");
+    assert!(cloned.join(".git").is_dir());
+    assert!(cloned.join("artisan").exists());
+    assert!(!cloned.join("develop.txt").exists());
+}
+
+#[tokio::test]
+async fn test_clone_project_honours_branch_over_file_url() {
+    let temp_dir = common::create_temp_dir();
+    let remote = common::create_git_fixture_repo(&temp_dir.path().join("remote"), "plain");
+    let destination = temp_dir.path().join("checkout");
+
+    let manager = common::isolated_project_manager(temp_dir.path());
+    let request = CloneProjectRequest {
+        git_url: url::Url::from_directory_path(&remote).unwrap().to_string(),
+        destination: Some(destination.to_string_lossy().to_string()),
+        branch: Some("develop".to_string()),
+        auto_setup: false,
+        ..Default::default()
+    };
+    manager.clone_project(request).await.unwrap();
+
+    assert!(destination.join("develop.txt").exists());
+    let repo = git2::Repository::open(&destination).unwrap();
+    assert_eq!(repo.head().unwrap().shorthand(), Some("develop"));
+
+    // 项目目录之外的克隆作为导入项目列出
+    let projects = manager.list_projects().unwrap();
+    assert_eq!(projects.len(), 1);
+    assert_eq!(projects[0].name, "remote");
+    assert_eq!(
+        fs::canonicalize(&projects[0].path).unwrap(),
+        fs::canonicalize(&destination).unwrap()
+    );
+}
+
+#[tokio::test]
+async fn test_clone_project_failure_cleans_destination() {
+    let temp_dir = common::create_temp_dir();
+    let remote = common::create_git_fixture_repo(&temp_dir.path().join("remote"), "plain");
+    let destination = temp_dir.path().join("checkout");
+
+    let manager = common::isolated_project_manager(temp_dir.path());
+    let request = CloneProjectRequest {
+        git_url: remote.to_string_lossy().to_string(),
+        destination: Some(destination.to_string_lossy().to_string()),
+        branch: Some("missing-branch".to_string()),
+        auto_setup: false,
+        ..Default::default()
+    };
+
+    // 分支不存在时克隆失败，且不留下半成品目录
+    assert!(manager.clone_project(request).await.is_err());
+    assert!(!destination.exists());
+}
+
+#[tokio::test]
+async fn test_clone_project_refuses_existing_destination() {
+    let temp_dir = common::create_temp_dir();
+    let remote = common::create_git_fixture_repo(&temp_dir.path().join("remote"), "plain");
+    let destination = temp_dir.path().join("existing");
+    fs::create_dir_all(&destination).unwrap();
+    fs::write(destination.join("keep.txt"), "keep").unwrap();
+
+    let manager = common::isolated_project_manager(temp_dir.path());
+    let request = CloneProjectRequest {
+        git_url: remote.to_string_lossy().to_string(),
+        destination: Some(destination.to_string_lossy().to_string()),
+        branch: None,
+        auto_setup: false,
+        ..Default::default()
+    };
+
+    assert!(manager.clone_project(request).await.is_err());
+    assert!(destination.join("keep.txt").exists());
+}
+
+#[tokio::test]
+async fn test_clone_project_with_credentials() {
+    let temp_dir = common::create_temp_dir();
+    let remote = common::create_git_fixture_repo(&temp_dir.path().join("remote"), "plain");
+
+    let store = Arc::new(MemorySecretStore::new());
+    store.set("token", "ghp_secret").unwrap();
+    let mut manager = common::isolated_project_manager(temp_dir.path());
+    manager.set_secret_store(SharedSecretStore::new(store));
+
+    // 本地仓库不需要认证，提供的凭据不会影响克隆
+    let destination = temp_dir.path().join("with-token");
+    let request = CloneProjectRequest {
+        git_url: remote.to_string_lossy().to_string(),
+        destination: Some(destination.to_string_lossy().to_string()),
+        credentials: Some(GitCredentials::Token {
+            username: None,
+            token_secret_id: "token".to_string(),
+        }),
+        ..Default::default()
+    };
+    manager.clone_project(request).await.unwrap();
+    assert!(destination.join(".git").exists());
+
+    // 引用不存在的密钥时在克隆前失败
+    let destination = temp_dir.path().join("missing-secret");
+    let request = CloneProjectRequest {
+        git_url: remote.to_string_lossy().to_string(),
+        destination: Some(destination.to_string_lossy().to_string()),
+        credentials: Some(GitCredentials::Token {
+            username: None,
+            token_secret_id: "unknown".to_string(),
+        }),
+        ..Default::default()
+    };
+    let result = manager.clone_project(request).await;
+    assert!(matches!(result, Err(Error::Config(_))));
+    assert!(!destination.exists());
+}
+
+fn clone_request(remote: &std::path::Path, destination: &std::path::Path) -> CloneProjectRequest {
+    CloneProjectRequest {
+        git_url: url::Url::from_directory_path(remote).unwrap().to_string(),
+        destination: Some(destination.to_string_lossy().to_string()),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_clone_project_checks_out_tag_and_commit() {
+    let temp_dir = common::create_temp_dir();
+    let remote = common::create_git_fixture_repo(&temp_dir.path().join("remote"), "laravel");
+    let manager = common::isolated_project_manager(temp_dir.path());
+
+    // 标签指向初始提交，没有 CHANGELOG.md
+    let destination = temp_dir.path().join("tagged");
+    let request = CloneProjectRequest {
+        tag: Some("v1.0.0".to_string()),
+        ..clone_request(&remote, &destination)
+    };
+    manager.clone_project(request).await.unwrap();
+    let repo = git2::Repository::open(&destination).unwrap();
+    assert!(repo.head_detached().unwrap());
+    assert!(destination.join("artisan").exists());
+    assert!(!destination.join("CHANGELOG.md").exists());
+
+    // develop 分支上的提交
+    let develop_commit = git2::Repository::open(&remote)
+        .unwrap()
+        .revparse_single("develop")
+        .unwrap()
+        .id()
+        .to_string();
+    let destination = temp_dir.path().join("commit");
+    let request = CloneProjectRequest {
+        commit: Some(develop_commit.clone()),
+        ..clone_request(&remote, &destination)
+    };
+    manager.clone_project(request).await.unwrap();
+    let repo = git2::Repository::open(&destination).unwrap();
+    assert_eq!(
+        repo.head().unwrap().target().unwrap().to_string(),
+        develop_commit
+    );
+    assert!(destination.join("develop.txt").exists());
+
+    // 不存在的标签会清理目标目录
+    let destination = temp_dir.path().join("missing-tag");
+    let request = CloneProjectRequest {
+        tag: Some("v9.9.9".to_string()),
+        ..clone_request(&remote, &destination)
+    };
+    assert!(manager.clone_project(request).await.is_err());
+    assert!(!destination.exists());
+
+    // 标签不能与分支同时指定
+    let request = CloneProjectRequest {
+        tag: Some("v1.0.0".to_string()),
+        branch: Some("develop".to_string()),
+        ..clone_request(&remote, &temp_dir.path().join("conflict"))
+    };
+    assert!(matches!(
+        manager.clone_project(request).await,
+        Err(Error::Config(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_clone_project_single_branch() {
+    let temp_dir = common::create_temp_dir();
+    let remote = common::create_git_fixture_repo(&temp_dir.path().join("remote"), "plain");
+    let manager = common::isolated_project_manager(temp_dir.path());
+
+    let remote_branches = |path: &std::path::Path| {
+        let repo = git2::Repository::open(path).unwrap();
+        let mut names: Vec<String> = repo
+            .branches(Some(git2::BranchType::Remote))
+            .unwrap()
+            .map(|branch| branch.unwrap().0.name().unwrap().unwrap().to_string())
+            // origin/HEAD 只是指向默认分支的符号引用
+            .filter(|name| name != "origin/HEAD")
+            .collect();
+        names.sort();
+        names
+    };
+
+    // 未指定分支时只获取远程默认分支
+    let destination = temp_dir.path().join("default-only");
+    let request = CloneProjectRequest {
+        single_branch: true,
+        // 本地仓库不支持浅克隆，深度被忽略
+        depth: Some(1),
+        ..clone_request(&remote, &destination)
+    };
+    manager.clone_project(request).await.unwrap();
+    assert_eq!(remote_branches(&destination), vec!["origin/main"]);
+
+    let destination = temp_dir.path().join("develop-only");
+    let request = CloneProjectRequest {
+        branch: Some("develop".to_string()),
+        single_branch: true,
+        ..clone_request(&remote, &destination)
+    };
+    manager.clone_project(request).await.unwrap();
+    assert_eq!(remote_branches(&destination), vec!["origin/develop"]);
+    assert!(destination.join("develop.txt").exists());
+
+    // 单分支克隆标签时只获取该标签
+    let destination = temp_dir.path().join("tag-only");
+    let request = CloneProjectRequest {
+        tag: Some("v1.0.0".to_string()),
+        single_branch: true,
+        ..clone_request(&remote, &destination)
+    };
+    manager.clone_project(request).await.unwrap();
+    assert!(remote_branches(&destination).is_empty());
+    assert!(destination.join("index.php").exists());
+    assert!(!destination.join("CHANGELOG.md").exists());
+}
+
+#[tokio::test]
+async fn test_clone_project_recurse_submodules() {
+    let temp_dir = common::create_temp_dir();
+    let theme = common::create_git_fixture_repo(&temp_dir.path().join("theme"), "plain");
+    let remote = common::create_git_fixture_repo(&temp_dir.path().join("remote"), "laravel");
+    common::add_git_submodule(&remote, &theme, "themes/default");
+    let manager = common::isolated_project_manager(temp_dir.path());
+
+    // 默认不初始化子模块
+    let destination = temp_dir.path().join("without");
+    manager
+        .clone_project(clone_request(&remote, &destination))
+        .await
+        .unwrap();
+    assert!(!destination.join("themes/default/index.php").exists());
+
+    let events = Arc::new(Mutex::new(Vec::<CloneProgress>::new()));
+    let recorded = events.clone();
+    let on_progress: ProgressCallback = Box::new(move |progress| {
+        recorded.lock().unwrap().push(progress.clone());
+    });
+
+    let destination = temp_dir.path().join("with");
+    let request = CloneProjectRequest {
+        recurse_submodules: true,
+        ..clone_request(&remote, &destination)
+    };
+    manager
+        .clone_project_with_progress(request, Arc::new(AtomicBool::new(false)), Some(on_progress))
+        .await
+        .unwrap();
+    assert!(destination.join("themes/default/index.php").exists());
+    assert!(destination.join("themes/default/CHANGELOG.md").exists());
+
+    let events = events.lock().unwrap();
+    let last = events.last().unwrap();
+    assert_eq!(last.stage, CloneStage::Done);
+    assert_eq!(last.submodules_total, 1);
+    assert_eq!(last.submodules_completed, 1);
+}
+
+async fn clone_for_git_tests(
+    manager: &ProjectManager,
+    remote: &std::path::Path,
+    destination: &std::path::Path,
+) {
+    manager
+        .clone_project(clone_request(remote, destination))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_git_status_reports_branch_and_changes() {
+    let temp_dir = common::create_temp_dir();
+    let remote = common::create_git_fixture_repo(&temp_dir.path().join("remote"), "laravel");
+    let destination = temp_dir.path().join("app");
+    let manager = common::isolated_project_manager(temp_dir.path());
+    clone_for_git_tests(&manager, &remote, &destination).await;
+
+    let status = manager.git_status(&destination).unwrap();
+    assert_eq!(status.branch, Some("main".to_string()));
+    assert_eq!(status.upstream, Some("origin/main".to_string()));
+    assert!(status.remote_url.as_deref().unwrap().starts_with("file://"));
+    assert!(!status.detached);
+    assert_eq!((status.ahead, status.behind), (0, 0));
+    assert!(!status.is_dirty);
+
+    // 修改已跟踪文件并新增未跟踪文件
+    fs::write(destination.join("artisan"), "changed").unwrap();
+    fs::write(destination.join("notes.txt"), "notes").unwrap();
+    let status = manager.git_status(&destination).unwrap();
+    assert!(status.is_dirty);
+    let change = |path: &str| {
+        status
+            .files
+            .iter()
+            .find(|file| file.path == path)
+            .and_then(|file| file.worktree)
+    };
+    assert_eq!(change("artisan"), Some(GitChange::Modified));
+    assert_eq!(change("notes.txt"), Some(GitChange::Untracked));
+
+    // 本地提交领先上游
+    common::commit_file(&destination, "notes.txt", "notes", "Add notes");
+    let status = manager.git_status(&destination).unwrap();
+    assert_eq!((status.ahead, status.behind), (1, 0));
+
+    // list_projects 从仓库配置读取远程地址
+    let mut manager = common::isolated_project_manager(temp_dir.path());
+    manager.set_projects_directory(temp_dir.path().to_path_buf());
+    let projects = manager.list_projects().unwrap();
+    let app = projects
+        .iter()
+        .find(|p| std::path::Path::new(&p.path).ends_with("app"))
+        .unwrap();
+    assert_eq!(app.git_url, status.remote_url);
+}
+
+#[tokio::test]
+async fn test_fetch_and_fast_forward_pull() {
+    let temp_dir = common::create_temp_dir();
+    let remote = common::create_git_fixture_repo(&temp_dir.path().join("remote"), "plain");
+    let destination = temp_dir.path().join("app");
+    let manager = common::isolated_project_manager(temp_dir.path());
+    clone_for_git_tests(&manager, &remote, &destination).await;
+
+    let upstream_commit = common::commit_file(&remote, "CHANGELOG.md", "# 1.1", "Release 1.1");
+
+    let status = manager.fetch_project(&destination, None).await.unwrap();
+    assert_eq!((status.ahead, status.behind), (0, 1));
+
+    let result = manager.pull_project(&destination, None).await.unwrap();
+    assert!(result.updated);
+    assert_eq!(result.current_commit, Some(upstream_commit.to_string()));
+    assert_eq!(result.status.behind, 0);
+    assert_eq!(
+        fs::read_to_string(destination.join("CHANGELOG.md")).unwrap(),
+        "# 1.1"
+    );
+
+    // 再次拉取时已是最新
+    let result = manager.pull_project(&destination, None).await.unwrap();
+    assert!(!result.updated);
+
+    // 本地与远程分叉时拒绝拉取
+    common::commit_file(&remote, "remote.txt", "remote", "Remote change");
+    let local_commit = common::commit_file(&destination, "local.txt", "local", "Local change");
+    let result = manager.pull_project(&destination, None).await;
+    assert!(matches!(result, Err(Error::Git(_))));
+    let status = manager.git_status(&destination).unwrap();
+    assert_eq!(status.head_commit, Some(local_commit.to_string()));
+    assert_eq!((status.ahead, status.behind), (1, 1));
+}
+
+#[tokio::test]
+async fn test_switch_branch() {
+    let temp_dir = common::create_temp_dir();
+    let remote = common::create_git_fixture_repo(&temp_dir.path().join("remote"), "plain");
+    let destination = temp_dir.path().join("app");
+    let manager = common::isolated_project_manager(temp_dir.path());
+    clone_for_git_tests(&manager, &remote, &destination).await;
+
+    // 仅存在于远程的分支会创建本地跟踪分支
+    let status = manager.switch_branch(&destination, "develop").unwrap();
+    assert_eq!(status.branch, Some("develop".to_string()));
+    assert_eq!(status.upstream, Some("origin/develop".to_string()));
+    assert!(destination.join("develop.txt").exists());
+
+    let status = manager.switch_branch(&destination, "main").unwrap();
+    assert_eq!(status.branch, Some("main".to_string()));
+    assert!(!destination.join("develop.txt").exists());
+
+    // 未跟踪文件会被覆盖时拒绝切换
+    fs::write(destination.join("develop.txt"), "local").unwrap();
+    assert!(manager.switch_branch(&destination, "develop").is_err());
+    assert_eq!(
+        fs::read_to_string(destination.join("develop.txt")).unwrap(),
+        "local"
+    );
+
+    assert!(manager.switch_branch(&destination, "missing").is_err());
+}
+
+#[tokio::test]
+async fn test_project_manager_snapshot_shares_registry() {
+    let temp_dir = common::create_temp_dir();
+    let manager = common::isolated_project_manager(temp_dir.path());
+    let external = common::create_mock_php_project(&temp_dir.path().join("work"), "plain");
+
+    // 命令在快照上执行长时间操作，登记结果对原管理器可见
+    let snapshot = manager.clone();
+    let info = snapshot.import_project(&external, None).unwrap();
+    let projects = manager.list_projects().unwrap();
+    assert_eq!(projects.len(), 1);
+    assert_eq!(projects[0].id, info.id);
+}
+
+#[tokio::test]
+async fn test_import_project_is_listed_and_persisted() {
+    let temp_dir = common::create_temp_dir();
+    let projects_dir = temp_dir.path().join("projects");
+    let registry_path = temp_dir.path().join("data").join("projects.json");
+    let scanned = common::create_mock_php_project(&projects_dir, "laravel");
+    let external = common::create_mock_php_project(&temp_dir.path().join("work"), "thinkphp");
+
+    let mut manager = common::isolated_project_manager(temp_dir.path());
+    manager.set_projects_directory(projects_dir.clone());
+    manager.set_registry(ProjectRegistry::load(registry_path.clone()));
+
+    let info = manager.import_project(&external, None).unwrap();
+    assert_eq!(info.name, "test_project_thinkphp");
+    assert_eq!(info.framework, Some(Framework::ThinkPHP));
+    // 导入不会移动目录
+    assert!(external.join("think").exists());
+
+    // 重复导入只更新名称
+    let info = manager.import_project(&external, Some("Legacy")).unwrap();
+    assert_eq!(info.name, "Legacy");
+
+    // 重新加载注册表后仍能列出
+    let mut manager = common::isolated_project_manager(temp_dir.path());
+    manager.set_projects_directory(projects_dir.clone());
+    manager.set_registry(ProjectRegistry::load(registry_path.clone()));
+    let projects = manager.list_projects().unwrap();
+    let names: Vec<&str> = projects.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, vec!["test_project_laravel", "Legacy"]);
+    // 项目目录中的项目在列出时也会登记，以获得稳定的 id
+    assert_eq!(manager_registry_len(&registry_path), 2);
+    assert!(projects.iter().all(|p| p.id.is_some()));
+
+    // 已在项目目录中的项目不会重复出现
+    manager.import_project(&scanned, None).unwrap();
+    assert_eq!(manager.list_projects().unwrap().len(), 2);
+
+    // 目录被删除后不再列出
+    fs::remove_dir_all(&external).unwrap();
+    let names: Vec<String> = manager
+        .list_projects()
+        .unwrap()
+        .into_iter()
+        .map(|p| p.name)
+        .collect();
+    assert_eq!(names, vec!["test_project_laravel".to_string()]);
+
+    // 不存在的目录无法导入
+    assert!(matches!(
+        manager.import_project(&temp_dir.path().join("missing"), None),
+        Err(Error::Config(_))
+    ));
+}
+
+fn manager_registry_len(registry_path: &std::path::Path) -> usize {
+    ProjectRegistry::load(registry_path.to_path_buf())
+        .projects()
+        .len()
+}
+
+#[tokio::test]
+async fn test_project_settings_persist_and_apply_to_server_requests() {
+    let temp_dir = common::create_temp_dir();
+    let projects_dir = temp_dir.path().join("projects");
+    let registry_path = temp_dir.path().join("projects.json");
+    let project = common::create_mock_php_project(&projects_dir, "laravel");
+
+    let mut manager = common::isolated_project_manager(temp_dir.path());
+    manager.set_projects_directory(projects_dir.clone());
+    manager.set_registry(ProjectRegistry::load(registry_path.clone()));
+
+    let id = manager.list_projects().unwrap()[0].id.clone().unwrap();
+    // 再次列出时 id 保持不变
+    assert_eq!(manager.list_projects().unwrap()[0].id, Some(id.clone()));
+
+    let mut env = BTreeMap::new();
+    env.insert("APP_ENV".to_string(), "local".to_string());
+    env.insert("APP_DEBUG".to_string(), "true".to_string());
+    let settings = ProjectSettings {
+        display_name: Some("  Shop  ".to_string()),
+        php_version: Some("8.2.0".to_string()),
+        port: Some(8123),
+        document_root: Some("public".to_string()),
+        env,
+        ..Default::default()
+    };
+    let info = manager.update_project_settings(&id, settings).unwrap();
+    assert_eq!(info.name, "Shop");
+    assert_eq!(info.settings.display_name, Some("Shop".to_string()));
+
+    // 设置在重新加载后仍然存在
+    let mut manager = common::isolated_project_manager(temp_dir.path());
+    manager.set_projects_directory(projects_dir.clone());
+    manager.set_registry(ProjectRegistry::load(registry_path.clone()));
+    let settings = manager.get_project_settings(&id).unwrap();
+    assert_eq!(settings.port, Some(8123));
+    assert_eq!(manager.get_project(&id).unwrap().name, "Shop");
+    assert_eq!(manager.list_projects().unwrap()[0].name, "Shop");
+
+    // 通过 id 启动时填充未设置的字段
+    let mut request = StartServerRequest {
+        project_id: Some(id.clone()),
+        port: Some(9000),
+        ..Default::default()
+    };
+    request
+        .env
+        .insert("APP_ENV".to_string(), "testing".to_string());
+    manager.apply_project_settings(&mut request).unwrap();
+    let canonical = fs::canonicalize(&project).unwrap();
+    assert_eq!(request.project_path, canonical.to_string_lossy());
+    assert_eq!(request.port, Some(9000));
+    assert_eq!(request.php_version, Some("8.2.0".to_string()));
+    assert_eq!(
+        request.document_root,
+        Some(canonical.join("public").to_string_lossy().to_string())
+    );
+    assert_eq!(request.env["APP_ENV"], "testing");
+    assert_eq!(request.env["APP_DEBUG"], "true");
+
+    // 通过项目路径同样能找到设置
+    let mut request = StartServerRequest {
+        project_path: project.to_string_lossy().to_string(),
+        ..Default::default()
+    };
+    manager.apply_project_settings(&mut request).unwrap();
+    assert_eq!(request.port, Some(8123));
+
+    // 未登记的路径保持原样
+    let mut request = StartServerRequest {
+        project_path: temp_dir.path().to_string_lossy().to_string(),
+        ..Default::default()
+    };
+    manager.apply_project_settings(&mut request).unwrap();
+    assert_eq!(request.port, None);
+
+    let invalid = ProjectSettings {
+        env: BTreeMap::from([("A=B".to_string(), "x".to_string())]),
+        ..Default::default()
+    };
+    assert!(manager.update_project_settings(&id, invalid).is_err());
+    assert!(manager.get_project_settings("unknown").is_err());
+    let mut request = StartServerRequest {
+        project_id: Some("unknown".to_string()),
+        ..Default::default()
+    };
+    assert!(manager.apply_project_settings(&mut request).is_err());
+}
+
+#[test]
+fn test_project_registry_moves_corrupt_file_aside() {
+    let temp_dir = common::create_temp_dir();
+    let registry_path = temp_dir.path().join("projects.json");
+    fs::write(&registry_path, "{ not json").unwrap();
+
+    let registry = ProjectRegistry::load(registry_path.clone());
+    assert!(registry.projects().is_empty());
+    assert!(temp_dir.path().join("projects.json.corrupt").exists());
+}
+
+#[test]
+fn test_repository_name_from_remotes() {
+    let cases = [
+        ("https://github.com/user/repo.git", "repo"),
+        ("https://github.com/user/repo/", "repo"),
+        ("git@github.com:user/repo.git", "repo"),
+        ("ssh://git@example.com:2222/group/project.git", "project"),
+        ("file:///srv/git/app.git", "app"),
+        ("/srv/git/local-app", "local-app"),
+        ("../relative/app.git", "app"),
+    ];
+
+    for (remote, expected) in cases {
+        assert_eq!(
+            tauri_plugin_php::git::repository_name(remote).unwrap(),
+            expected
+        );
+    }
+    assert!(tauri_plugin_php::git::repository_name("https://github.com/").is_err());
+}
+
+#[tokio::test]
+async fn test_clone_project_reports_progress() {
+    let temp_dir = common::create_temp_dir();
+    let remote = common::create_git_fixture_repo(&temp_dir.path().join("remote"), "laravel");
+    let destination = temp_dir.path().join("checkout");
+
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let sink = events.clone();
+    let on_progress: ProgressCallback = Box::new(move |progress: &CloneProgress| {
+        sink.lock().unwrap().push(progress.clone());
+    });
+
+    let manager = common::isolated_project_manager(temp_dir.path());
+    let request = CloneProjectRequest {
+        git_url: url::Url::from_directory_path(&remote).unwrap().to_string(),
+        destination: Some(destination.to_string_lossy().to_string()),
+        clone_id: Some("clone-1".to_string()),
+        ..Default::default()
+    };
+    manager
+        .clone_project_with_progress(request, Arc::new(AtomicBool::new(false)), Some(on_progress))
+        .await
+        .unwrap();
+
+    // 最后一条进度事件表示克隆完成，且所有事件都带有 clone id
+    let events = events.lock().unwrap();
+    assert!(!events.is_empty());
+    assert!(events.iter().all(|e| e.clone_id == "clone-1"));
+    let last = events.last().unwrap();
+    assert_eq!(last.stage, CloneStage::Done);
+    assert!(last.total_objects > 0);
+    assert_eq!(last.received_objects, last.total_objects);
+}
+
+#[tokio::test]
+async fn test_cancelled_clone_removes_destination() {
+    let temp_dir = common::create_temp_dir();
+    let remote = common::create_git_fixture_repo(&temp_dir.path().join("remote"), "plain");
+    let destination = temp_dir.path().join("checkout");
+
+    let registry = CloneRegistry::new();
+    let cancel = registry.register("clone-2").unwrap();
+    // 同一个 clone id 不能重复注册
+    assert!(registry.register("clone-2").is_err());
+    assert!(registry.cancel("clone-2"));
+    assert!(!registry.cancel("unknown"));
+
+    let manager = common::isolated_project_manager(temp_dir.path());
+    let request = CloneProjectRequest {
+        git_url: url::Url::from_directory_path(&remote).unwrap().to_string(),
+        destination: Some(destination.to_string_lossy().to_string()),
+        clone_id: Some("clone-2".to_string()),
+        ..Default::default()
+    };
+    let result = manager
+        .clone_project_with_progress(request, cancel, None)
+        .await;
+
+    assert!(matches!(result, Err(Error::Cancelled(_))));
+    assert!(!destination.exists());
+    registry.unregister("clone-2");
+    assert!(!registry.cancel("clone-2"));
+}
+
+#[tokio::test]
+async fn test_remove_project_rejects_paths_outside_projects_dir() {
+    let temp_dir = common::create_temp_dir();
+    let manager = common::isolated_project_manager(temp_dir.path());
+    let projects_dir = temp_dir.path().join("projects");
+    fs::create_dir_all(&projects_dir).unwrap();
+    let outside = common::create_mock_php_project(temp_dir.path(), "plain");
+
+    for name in ["", ".", "..", "../test_project_plain", "a/b", ".hidden"] {
+        assert!(
+            matches!(manager.remove_project(name, false), Err(Error::Config(_))),
+            "{:?} should be rejected",
+            name
+        );
+    }
+    assert!(matches!(
+        manager.remove_project("missing", false),
+        Err(Error::Config(_))
+    ));
+
+    // 指向项目目录之外的符号链接不会被跟随删除
+    #[cfg(unix)]
+    {
+        std::os::unix::fs::symlink(&outside, projects_dir.join("link")).unwrap();
+        assert!(matches!(
+            manager.remove_project("link", false),
+            Err(Error::PermissionDenied(_))
+        ));
+    }
+    assert!(outside.join("index.php").exists());
+
+    // 正常删除
+    let project = common::create_mock_php_project(&projects_dir, "laravel");
+    let name = project.file_name().unwrap().to_str().unwrap().to_string();
+    assert!(manager.remove_project(&name, false).unwrap().is_none());
+    assert!(!project.exists());
+}
+
+#[tokio::test]
+async fn test_remove_project_to_trash_restore_and_purge() {
+    let temp_dir = common::create_temp_dir();
+    let manager = common::isolated_project_manager(temp_dir.path());
+    let projects_dir = temp_dir.path().join("projects");
+    let project = common::create_mock_php_project(&projects_dir, "laravel");
+    let name = project.file_name().unwrap().to_str().unwrap().to_string();
+
+    // 保存设置，恢复后应保留 id 与设置
+    let id = manager.list_projects().unwrap()[0].id.clone().unwrap();
+    let settings = ProjectSettings {
+        port: Some(8111),
+        ..Default::default()
+    };
+    manager.update_project_settings(&id, settings).unwrap();
+
+    let trashed = manager.remove_project(&name, true).unwrap().unwrap();
+    assert!(!project.exists());
+    assert!(temp_dir
+        .path()
+        .join("trash")
+        .join(&trashed.trash_id)
+        .is_dir());
+    assert!(manager.list_projects().unwrap().is_empty());
+
+    // 回收站在重新加载后仍然存在
+    let manager = common::isolated_project_manager(temp_dir.path());
+    assert_eq!(manager.list_trash(), vec![trashed.clone()]);
+
+    // 以新名称恢复
+    let restored = manager
+        .restore_project(&trashed.trash_id, Some("restored"))
+        .unwrap();
+    assert_eq!(restored.id, Some(id.clone()));
+    assert_eq!(restored.settings.port, Some(8111));
+    assert!(projects_dir.join("restored").join("artisan").exists());
+    assert!(manager.list_trash().is_empty());
+    assert!(manager.restore_project(&trashed.trash_id, None).is_err());
+
+    // 目标已存在时拒绝恢复
+    let trashed = manager.remove_project("restored", true).unwrap().unwrap();
+    fs::create_dir_all(projects_dir.join("restored")).unwrap();
+    assert!(manager.restore_project(&trashed.trash_id, None).is_err());
+    assert_eq!(manager.list_trash().len(), 1);
+
+    // 清空回收站
+    assert_eq!(manager.purge_trash(None).unwrap(), 1);
+    assert!(manager.list_trash().is_empty());
+    assert!(!temp_dir
+        .path()
+        .join("trash")
+        .join(&trashed.trash_id)
+        .exists());
+    assert!(manager.purge_trash(Some(&trashed.trash_id)).is_err());
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_remove_project_with_running_server() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = common::create_temp_dir();
+    let projects_dir = temp_dir.path().join("projects");
+    let project = common::create_mock_php_project(&projects_dir, "plain");
+    let name = project.file_name().unwrap().to_str().unwrap().to_string();
+
+    // 一个只会保持运行的假 PHP 可执行文件
+    let php = temp_dir.path().join("php");
+    fs::write(&php, "#!/bin/sh\nexec sleep 30\n").unwrap();
+    fs::set_permissions(&php, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let manager = common::isolated_php_manager(temp_dir.path());
+    let server_id = manager
+        .server_manager
+        .lock()
+        .await
+        .start_server(
+            StartServerRequest {
+                project_path: project.to_string_lossy().to_string(),
+                port: Some(common::find_available_port(8600)),
+                ..Default::default()
+            },
+            php,
+        )
+        .await
+        .unwrap();
+
+    // 服务器仍在使用项目时拒绝删除
+    let result = manager
+        .remove_project(&name, RemoveProjectOptions::default())
+        .await;
+    assert!(matches!(result, Err(Error::PhpServer(_))));
+    assert!(project.exists());
+
+    // 强制删除会先停止服务器
+    let result = manager
+        .remove_project(
+            &name,
+            RemoveProjectOptions {
+                force: true,
+                trash: false,
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(result.stopped_servers, vec![server_id]);
+    assert!(!project.exists());
+    assert!(manager
+        .server_manager
+        .lock()
+        .await
+        .list_running_servers()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_create_project_from_bundled_skeleton() {
+    let temp_dir = common::create_temp_dir();
+    let manager = common::isolated_project_manager(temp_dir.path());
+
+    let info = manager
+        .create_project(CreateProjectRequest {
+            name: "hello".to_string(),
+            framework: Framework::Plain,
+            auto_setup: true,
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+    let project = temp_dir.path().join("projects").join("hello");
+    assert!(project.join("index.php").exists());
+    assert_eq!(info.framework, Some(Framework::Plain));
+    assert_eq!(info.entry_point, Some("index.php".to_string()));
+    assert!(info.id.is_some());
+    assert_eq!(manager.list_projects().unwrap()[0].id, info.id);
+
+    // 目标目录非空时拒绝创建
+    let result = manager
+        .create_project(CreateProjectRequest {
+            name: "hello".to_string(),
+            framework: Framework::Plain,
+            ..Default::default()
+        })
+        .await;
+    assert!(matches!(result, Err(Error::Config(_))));
+
+    // 无效名称与没有模板的框架
+    for (name, framework) in [("../hello", Framework::Plain), ("app", Framework::Phalcon)] {
+        let result = manager
+            .create_project(CreateProjectRequest {
+                name: name.to_string(),
+                framework,
+                ..Default::default()
+            })
+            .await;
+        assert!(matches!(result, Err(Error::Config(_))));
+    }
+}
+
+#[tokio::test]
+async fn test_create_project_from_git_template() {
+    let temp_dir = common::create_temp_dir();
+    let template = common::create_git_fixture_repo(&temp_dir.path().join("template"), "laravel");
+    let manager = common::isolated_project_manager(temp_dir.path());
+    let template_url = url::Url::from_directory_path(&template)
+        .unwrap()
+        .to_string();
+
+    let info = manager
+        .create_project(CreateProjectRequest {
+            name: "shop".to_string(),
+            framework: Framework::Laravel,
+            version: Some("v1.0.0".to_string()),
+            template_url: Some(template_url),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+    // 新项目不保留模板的 Git 历史，并检出指定的标签
+    let project = temp_dir.path().join("projects").join("shop");
+    assert!(project.join("artisan").exists());
+    assert!(!project.join(".git").exists());
+    assert!(!project.join("CHANGELOG.md").exists());
+    assert_eq!(info.framework, Some(Framework::Laravel));
+    assert_eq!(info.git_url, None);
+
+    // 模板获取失败时清理目标目录
+    let result = manager
+        .create_project(CreateProjectRequest {
+            name: "broken".to_string(),
+            framework: Framework::Laravel,
+            template_url: Some(
+                temp_dir
+                    .path()
+                    .join("missing")
+                    .to_string_lossy()
+                    .to_string(),
+            ),
+            ..Default::default()
+        })
+        .await;
+    assert!(result.is_err());
+    assert!(!temp_dir.path().join("projects").join("broken").exists());
+}
+
+#[tokio::test]
+async fn test_create_project_into_existing_empty_folder() {
+    let temp_dir = common::create_temp_dir();
+    let template = common::create_git_fixture_repo(&temp_dir.path().join("template"), "laravel");
+    let manager = common::isolated_project_manager(temp_dir.path());
+    let template_url = url::Url::from_directory_path(&template)
+        .unwrap()
+        .to_string();
+
+    // 已存在的空目录可以作为目标；Composer 版本约束不作为标签使用
+    let destination = temp_dir.path().join("work").join("shop");
+    fs::create_dir_all(&destination).unwrap();
+    let info = manager
+        .create_project(CreateProjectRequest {
+            name: "shop".to_string(),
+            framework: Framework::Laravel,
+            version: Some("^1.0".to_string()),
+            template_url: Some(template_url),
+            destination: Some(destination.to_string_lossy().to_string()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert!(destination.join("artisan").exists());
+    assert!(destination.join("CHANGELOG.md").exists());
+    assert!(!destination.join(".git").exists());
+    // 暂存目录不会残留
+    let mut entries: Vec<String> = fs::read_dir(temp_dir.path().join("work"))
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .collect();
+    entries.sort();
+    assert_eq!(entries, vec!["shop"]);
+    assert_eq!(info.framework, Some(Framework::Laravel));
+
+    // 模板获取失败时保留用户原有的目录
+    let existing = temp_dir.path().join("work").join("broken");
+    fs::create_dir_all(&existing).unwrap();
+    let result = manager
+        .create_project(CreateProjectRequest {
+            name: "broken".to_string(),
+            framework: Framework::Laravel,
+            template_url: Some(
+                temp_dir
+                    .path()
+                    .join("missing")
+                    .to_string_lossy()
+                    .to_string(),
+            ),
+            destination: Some(existing.to_string_lossy().to_string()),
+            ..Default::default()
+        })
+        .await;
+    assert!(result.is_err());
+    assert!(existing.is_dir());
+    assert_eq!(fs::read_dir(&existing).unwrap().count(), 0);
+
+    // 目标旁边同名的 .template 目录属于用户，失败后保持不变
+    let neighbour = temp_dir.path().join("work").join("broken.template");
+    fs::create_dir_all(&neighbour).unwrap();
+    fs::write(neighbour.join("notes.txt"), "keep").unwrap();
+    let result = manager
+        .create_project(CreateProjectRequest {
+            name: "broken".to_string(),
+            framework: Framework::Laravel,
+            template_url: Some(
+                temp_dir
+                    .path()
+                    .join("missing")
+                    .to_string_lossy()
+                    .to_string(),
+            ),
+            destination: Some(existing.to_string_lossy().to_string()),
+            ..Default::default()
+        })
+        .await;
+    assert!(result.is_err());
+    assert_eq!(
+        fs::read_to_string(neighbour.join("notes.txt")).unwrap(),
+        "keep"
+    );
+    let mut entries: Vec<String> = fs::read_dir(temp_dir.path().join("work"))
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .collect();
+    entries.sort();
+    assert_eq!(entries, vec!["broken", "broken.template", "shop"]);
+}
+
+#[test]
+fn test_project_templates_cover_frameworks() {
+    let templates = project_templates::templates();
+    let laravel = templates
+        .iter()
+        .find(|template| template.framework == Framework::Laravel)
+        .unwrap();
+    assert_eq!(laravel.composer_package.as_deref(), Some("laravel/laravel"));
+    assert!(laravel.git_url.is_some());
+
+    let plain = project_templates::template_for(&Framework::Plain).unwrap();
+    assert!(plain.bundled);
+    assert!(project_templates::template_for(&Framework::Unknown).is_none());
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_setup_laravel_project_reports_steps() {
+    use tauri_plugin_php::composer::ComposerManager;
+    use tauri_plugin_php::models::{
+        DatabaseConfig, LaravelSetupOptions, SetupProjectRequest, SetupStepStatus,
+    };
+    use tauri_plugin_php::php_binary::PhpBinaryManager;
+
+    let temp_dir = common::create_temp_dir();
+    let mut manager = common::isolated_project_manager(temp_dir.path());
+    let project = common::create_mock_php_project(&temp_dir.path().join("projects"), "laravel");
+
+    // 模拟 PHP：记录 artisan 调用，migrate 失败
+    let php = common::create_mock_php_binary(temp_dir.path(), "8.3.0");
+    let calls = temp_dir.path().join("calls.log");
+    fs::write(
+        &php,
+        format!(
+            "#!/bin/sh\necho \"$*\" >> '{}'\n[ \"$2\" = migrate ] && {{ echo 'SQLSTATE[HY000]' >&2; exit 1; }}\necho done\n",
+            calls.display()
+        ),
+    )
+    .unwrap();
+    let mut php_binaries = PhpBinaryManager::new();
+    php_binaries.set_php_directory(temp_dir.path().join("php-binaries"));
+    manager.set_php_binaries(php_binaries);
+    let mut composer = ComposerManager::new();
+    composer.set_composer_directory(temp_dir.path().join("composer"));
+    manager.set_composer(composer);
+
+    let status = |report: &tauri_plugin_php::models::SetupReport, name: &str| {
+        report
+            .steps
+            .iter()
+            .find(|step| step.name == name)
+            .map(|step| (step.status, step.message.clone().unwrap_or_default()))
+            .unwrap()
+    };
+
+    // 依赖未安装时跳过 artisan 步骤并说明原因
+    let request = SetupProjectRequest {
+        project_path: project.to_string_lossy().to_string(),
+        ..Default::default()
+    };
+    let report = manager.setup_project(request.clone()).await.unwrap();
+    assert_eq!(report.framework, Framework::Laravel);
+    assert_eq!(
+        status(&report, "composer install").0,
+        SetupStepStatus::Skipped
+    );
+    assert_eq!(status(&report, ".env").0, SetupStepStatus::Succeeded);
+    let (key_status, reason) = status(&report, "key:generate");
+    assert_eq!(key_status, SetupStepStatus::Skipped);
+    assert!(reason.contains("vendor/autoload.php is missing"));
+    assert!(!calls.exists());
+
+    // 安装依赖后运行 artisan，并配置 SQLite 数据库
+    fs::write(project.join("artisan"), "<?php").unwrap();
+    fs::create_dir_all(project.join("vendor")).unwrap();
+    fs::write(project.join("vendor/autoload.php"), "<?php").unwrap();
+    let report = manager
+        .setup_project(SetupProjectRequest {
+            laravel: LaravelSetupOptions {
+                migrate: true,
+                seed: true,
+                database: Some(DatabaseConfig {
+                    connection: "sqlite".to_string(),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            ..request.clone()
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(status(&report, ".env").0, SetupStepStatus::Skipped);
+    assert_eq!(status(&report, "database").0, SetupStepStatus::Succeeded);
+    assert_eq!(
+        status(&report, "key:generate").0,
+        SetupStepStatus::Succeeded
+    );
+    assert_eq!(
+        status(&report, "storage:link").0,
+        SetupStepStatus::Succeeded
+    );
+    assert_eq!(status(&report, "migrate").0, SetupStepStatus::Failed);
+    assert_eq!(
+        status(&report, "optimize:clear").0,
+        SetupStepStatus::Succeeded
+    );
+    assert!(!report.succeeded());
+    let migrate = report.steps.iter().find(|s| s.name == "migrate").unwrap();
+    assert!(migrate.output.contains("SQLSTATE"));
+
+    assert_eq!(
+        fs::read_to_string(&calls).unwrap(),
+        "artisan key:generate --force\nartisan storage:link\nartisan migrate --force --seed\nartisan optimize:clear\n"
+    );
+    let sqlite = project.join("database/database.sqlite");
+    assert!(sqlite.is_file());
+    let env = fs::read_to_string(project.join(".env")).unwrap();
+    assert!(env.contains("DB_CONNECTION=sqlite\n"));
+    assert!(env.contains(&format!("DB_DATABASE={}\n", sqlite.display())));
+
+    // 已设置 APP_KEY 且已链接 storage 时跳过
+    fs::write(
+        project.join(".env"),
+        env.replace("APP_KEY=", "APP_KEY=base64:abc"),
+    )
+    .unwrap();
+    std::os::unix::fs::symlink(project.join("storage"), project.join("public/storage")).unwrap();
+    let report = manager.setup_project(request).await.unwrap();
+    assert_eq!(
+        status(&report, "key:generate"),
+        (
+            SetupStepStatus::Skipped,
+            "APP_KEY is already set".to_string()
+        )
+    );
+    assert_eq!(status(&report, "storage:link").0, SetupStepStatus::Skipped);
+    // 未请求的步骤不在流水线中
+    assert!(report.steps.iter().all(|step| step.name != "migrate"));
+    assert!(report.succeeded());
+}
+
+#[tokio::test]
+async fn test_setup_project_dry_run_changes_nothing() {
+    use tauri_plugin_php::models::{SetupProjectRequest, SetupStepStatus};
+
+    let temp_dir = common::create_temp_dir();
+    let manager = common::isolated_project_manager(temp_dir.path());
+    let project = common::create_mock_php_project(&temp_dir.path().join("projects"), "laravel");
+
+    let report = manager
+        .setup_project(SetupProjectRequest {
+            project_path: project.to_string_lossy().to_string(),
+            dry_run: true,
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert!(report.dry_run);
+
+    // 条件满足的步骤为计划状态，其余说明跳过原因
+    let env = report
+        .steps
+        .iter()
+        .find(|step| step.name == ".env")
+        .unwrap();
+    assert_eq!(env.status, SetupStepStatus::Planned);
+    assert_eq!(
+        env.message.as_deref(),
+        Some("cp .env.example .env (if .env.example exists)")
+    );
+    let key = report
+        .steps
+        .iter()
+        .find(|step| step.name == "key:generate")
+        .unwrap();
+    // 后续步骤按前面计划的步骤已执行来判断：.env 由 .env.example 复制，依赖已安装
+    assert_eq!(key.status, SetupStepStatus::Planned);
+    assert!(!project.join(".env").exists());
+    assert!(!project.join("vendor").exists());
+}
+
+#[tokio::test]
+async fn test_setup_project_runs_overridden_pipeline() {
+    use tauri_plugin_php::models::{SetupProjectRequest, SetupStepStatus};
+
+    let temp_dir = common::create_temp_dir();
+    let manager = common::isolated_project_manager(temp_dir.path());
+    let project = common::create_mock_php_project(&temp_dir.path().join("projects"), "thinkphp");
+    fs::write(project.join(".example.env"), "APP_DEBUG=true\n").unwrap();
+    fs::write(
+        project.join("php-setup.json"),
+        r#"{
+    "skip": ["composer install"],
+    "append": [
+        {"type": "writable_dir", "path": "public/uploads"},
+        {"name": "debug", "type": "set_env", "values": {"APP_DEBUG": "false", "ROOT": "{project}"}}
+    ]
+}"#,
+    )
+    .unwrap();
+
+    let report = manager
+        .setup_project(SetupProjectRequest {
+            project_path: project.to_string_lossy().to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(report.framework, Framework::ThinkPHP);
+    // 版本已知时不再保留 3.x 的 Application/Runtime 步骤
+    assert!(report.framework_version.is_some());
+    let statuses: Vec<(&str, SetupStepStatus)> = report
+        .steps
+        .iter()
+        .map(|step| (step.name.as_str(), step.status))
+        .collect();
+    assert_eq!(
+        statuses,
+        vec![
+            (".env", SetupStepStatus::Succeeded),
+            ("runtime", SetupStepStatus::Succeeded),
+            ("public/uploads", SetupStepStatus::Succeeded),
+            ("debug", SetupStepStatus::Succeeded),
+        ]
+    );
+    assert!(project.join("public/uploads").is_dir());
+    let env = fs::read_to_string(project.join(".env")).unwrap();
+    assert!(env.contains("APP_DEBUG=false\n"));
+    assert!(env.contains(&format!("ROOT={}\n", project.display())));
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_setup_steps_do_not_follow_symlinks_out_of_the_project() {
+    use tauri_plugin_php::models::{SetupProjectRequest, SetupStepStatus};
+
+    let temp_dir = common::create_temp_dir();
+    let manager = common::isolated_project_manager(temp_dir.path());
+    let project = common::create_mock_php_project(&temp_dir.path().join("projects"), "plain");
+    let outside = temp_dir.path().join("outside");
+    fs::create_dir_all(&outside).unwrap();
+    fs::write(outside.join("secrets.env"), "KEEP=1\n").unwrap();
+
+    // 项目内指向项目外的符号链接，以及项目内部的符号链接
+    std::os::unix::fs::symlink(outside.join("secrets.env"), project.join(".env")).unwrap();
+    std::os::unix::fs::symlink(&outside, project.join("shared")).unwrap();
+    std::os::unix::fs::symlink(outside.join("missing"), project.join("dangling")).unwrap();
+    fs::create_dir_all(project.join("storage")).unwrap();
+    std::os::unix::fs::symlink(project.join("storage"), project.join("public-storage")).unwrap();
+    fs::write(
+        project.join("php-setup.json"),
+        r#"{
+    "steps": [
+        {"name": "env", "type": "set_env", "values": {"KEEP": "0"}},
+        {"name": "shared", "type": "writable_dir", "path": "shared/cache"},
+        {"name": "dangling", "type": "create_file", "path": "dangling"},
+        {"name": "inside", "type": "writable_dir", "path": "public-storage/cache"}
+    ]
+}"#,
+    )
+    .unwrap();
+
+    for dry_run in [true, false] {
+        let report = manager
+            .setup_project(SetupProjectRequest {
+                project_path: project.to_string_lossy().to_string(),
+                dry_run,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let statuses: Vec<(&str, SetupStepStatus)> = report
+            .steps
+            .iter()
+            .map(|step| (step.name.as_str(), step.status))
+            .collect();
+        let inside = if dry_run {
+            SetupStepStatus::Planned
+        } else {
+            SetupStepStatus::Succeeded
+        };
+        assert_eq!(
+            statuses,
+            vec![
+                ("env", SetupStepStatus::Failed),
+                ("shared", SetupStepStatus::Failed),
+                ("dangling", SetupStepStatus::Failed),
+                ("inside", inside),
+            ]
+        );
+    }
+    assert_eq!(
+        fs::read_to_string(outside.join("secrets.env")).unwrap(),
+        "KEEP=1\n"
+    );
+    assert!(!outside.join("cache").exists());
+    assert!(!outside.join("missing").exists());
+    assert!(project.join("storage/cache").is_dir());
+}
@@ -0,0 +1,153 @@
+use std::sync::Arc;
+use tauri::http::{Request, StatusCode, Uri};
+use tauri_plugin_php::php_server::PhpServerManager;
+use tauri_plugin_php::protocol::{handle_request, parse_protocol_uri, ProtocolState};
+use tokio::sync::Mutex;
+
+mod common;
+
+#[test]
+fn test_parse_protocol_uri_with_project_host() {
+    let uri: Uri = "php://project-a/admin/users?page=2".parse().unwrap();
+    let target = parse_protocol_uri(&uri).unwrap();
+
+    assert_eq!(target.project, "project-a");
+    assert_eq!(target.path_and_query, "/admin/users?page=2");
+    assert_eq!(target.public_base, "php://project-a");
+    assert!(target.prefix.is_none());
+}
+
+#[test]
+fn test_parse_protocol_uri_with_project_prefix() {
+    // Windows 与 Android 上自定义协议会被改写为 http://php.localhost/...
+    let uri: Uri = "http://php.localhost/project-a/index.php?x=1"
+        .parse()
+        .unwrap();
+    let target = parse_protocol_uri(&uri).unwrap();
+
+    assert_eq!(target.project, "project-a");
+    assert_eq!(target.path_and_query, "/index.php?x=1");
+    assert_eq!(target.public_base, "http://php.localhost/project-a");
+    assert_eq!(target.prefix, Some("/project-a".to_string()));
+
+    let uri: Uri = "php://localhost/project-b".parse().unwrap();
+    let target = parse_protocol_uri(&uri).unwrap();
+    assert_eq!(target.project, "project-b");
+    assert_eq!(target.path_and_query, "/");
+}
+
+#[test]
+fn test_parse_protocol_uri_without_project() {
+    let uri: Uri = "php://localhost/".parse().unwrap();
+    assert!(parse_protocol_uri(&uri).is_none());
+}
+
+#[tokio::test]
+async fn test_handle_request_without_running_server() {
+    let server_manager = Arc::new(Mutex::new(PhpServerManager::new()));
+    let request = Request::builder()
+        .uri("php://missing-project/")
+        .body(Vec::new())
+        .unwrap();
+
+    let response = handle_request(&ProtocolState::new(server_manager), request).await;
+
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert!(String::from_utf8_lossy(response.body()).contains("missing-project"));
+}
+
+#[tokio::test]
+async fn test_handle_request_without_project_name() {
+    let server_manager = Arc::new(Mutex::new(PhpServerManager::new()));
+    let request = Request::builder()
+        .uri("php://localhost/")
+        .body(Vec::new())
+        .unwrap();
+
+    let response = handle_request(&ProtocolState::new(server_manager), request).await;
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_handle_request_refuses_oversized_responses() {
+    use std::os::unix::fs::PermissionsExt;
+    use tauri_plugin_php::models::StartServerRequest;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let temp_dir = common::create_temp_dir();
+    let project_dir = common::create_mock_php_project(temp_dir.path(), "plain");
+    // 模拟的 php 只保持运行，由测试在其端口上应答
+    let php = temp_dir.path().join("php");
+    std::fs::write(&php, "#!/bin/sh\nexec sleep 30\n").unwrap();
+    std::fs::set_permissions(&php, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    let port = common::find_available_port(9400);
+    let server_manager = Arc::new(Mutex::new(PhpServerManager::new()));
+    server_manager
+        .lock()
+        .await
+        .start_server(
+            StartServerRequest {
+                project_path: project_dir.to_string_lossy().to_string(),
+                port: Some(port),
+                host: Some("127.0.0.1".to_string()),
+                ..Default::default()
+            },
+            php,
+        )
+        .await
+        .unwrap();
+
+    // 按路径返回带长度、不带长度（读到连接关闭）或较小的响应
+    let listener = tokio::net::TcpListener::bind(("127.0.0.1", port))
+        .await
+        .unwrap();
+    let backend = tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let mut request = vec![0u8; 4096];
+            let read = stream.read(&mut request).await.unwrap_or(0);
+            let request = String::from_utf8_lossy(&request[..read]).to_string();
+            let response = if request.starts_with("GET /sized") {
+                format!(
+                    "HTTP/1.1 200 OK\r\nContent-Length: 2048\r\n\r\n{}",
+                    "a".repeat(2048)
+                )
+            } else if request.starts_with("GET /stream") {
+                format!(
+                    "HTTP/1.1 200 OK\r\nConnection: close\r\n\r\n{}",
+                    "b".repeat(2048)
+                )
+            } else {
+                "HTTP/1.1 200 OK\r\nContent-Length: 5\r\nConnection: close\r\n\r\nsmall".to_string()
+            };
+            let _ = stream.write_all(response.as_bytes()).await;
+        }
+    });
+
+    let project = project_dir
+        .file_name()
+        .unwrap()
+        .to_string_lossy()
+        .to_string();
+    let mut state = ProtocolState::new(server_manager.clone());
+    state.set_max_response_bytes(1024);
+    let get = |path: &str| {
+        Request::builder()
+            .uri(format!("php://{}{}", project, path))
+            .body(Vec::new())
+            .unwrap()
+    };
+
+    let response = handle_request(&state, get("/sized")).await;
+    assert_eq!(response.status(), StatusCode::BAD_GATEWAY);
+    let response = handle_request(&state, get("/stream")).await;
+    assert_eq!(response.status(), StatusCode::BAD_GATEWAY);
+    let response = handle_request(&state, get("/")).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.body(), b"small");
+
+    backend.abort();
+    server_manager.lock().await.stop_all_servers().unwrap();
+}
@@ -0,0 +1,152 @@
+use git2::CredentialType;
+use std::sync::Arc;
+use tauri_plugin_php::git::{CredentialProvider, ResolvedCredentials};
+use tauri_plugin_php::models::GitCredentials;
+use tauri_plugin_php::secret_store::{
+    FileSecretStore, MemorySecretStore, SecretStore, SharedSecretStore,
+};
+use tauri_plugin_php::Error;
+
+mod common;
+
+#[test]
+fn test_memory_secret_store_roundtrip() {
+    let store = MemorySecretStore::new();
+
+    assert_eq!(store.get("token").unwrap(), None);
+    store.set("token", "ghp_secret").unwrap();
+    assert_eq!(store.get("token").unwrap(), Some("ghp_secret".to_string()));
+
+    assert!(store.delete("token").unwrap());
+    assert!(!store.delete("token").unwrap());
+
+    // 空 id 会被拒绝
+    assert!(matches!(store.set("  ", "value"), Err(Error::Config(_))));
+}
+
+#[test]
+fn test_file_secret_store_persists_privately() {
+    let temp_dir = common::create_temp_dir();
+    let path = temp_dir.path().join("plugin").join("secrets.json");
+
+    let store = FileSecretStore::with_path(path.clone());
+    store.set("deploy-key", "passphrase").unwrap();
+    store.set("github-token", "ghp_secret").unwrap();
+
+    // 新实例从同一文件读取
+    let reopened = FileSecretStore::with_path(path.clone());
+    assert_eq!(
+        reopened.get("deploy-key").unwrap(),
+        Some("passphrase".to_string())
+    );
+    assert!(reopened.delete("deploy-key").unwrap());
+    assert_eq!(store.get("deploy-key").unwrap(), None);
+    assert_eq!(
+        store.get("github-token").unwrap(),
+        Some("ghp_secret".to_string())
+    );
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+}
+
+#[test]
+fn test_shared_secret_store_can_be_replaced() {
+    let shared = SharedSecretStore::new(Arc::new(MemorySecretStore::new()));
+    let holder = shared.clone();
+    shared.set("token", "first").unwrap();
+
+    let replacement = Arc::new(MemorySecretStore::new());
+    replacement.set("token", "second").unwrap();
+    shared.replace(replacement);
+
+    // 所有持有者都看到新的存储
+    assert_eq!(holder.get("token").unwrap(), Some("second".to_string()));
+}
+
+#[test]
+fn test_resolve_credentials_reads_secrets() {
+    let store = MemorySecretStore::new();
+    store.set("github-token", "ghp_secret").unwrap();
+
+    let resolved = ResolvedCredentials::resolve(
+        &GitCredentials::Token {
+            username: None,
+            token_secret_id: "github-token".to_string(),
+        },
+        &store,
+    )
+    .unwrap();
+    assert!(matches!(
+        resolved,
+        ResolvedCredentials::Token { ref token, .. } if token == "ghp_secret"
+    ));
+    // Debug 输出不会泄露令牌
+    assert!(!format!("{:?}", resolved).contains("ghp_secret"));
+
+    let missing = ResolvedCredentials::resolve(
+        &GitCredentials::Token {
+            username: None,
+            token_secret_id: "unknown".to_string(),
+        },
+        &store,
+    );
+    assert!(matches!(missing, Err(Error::Config(_))));
+
+    let missing_key = ResolvedCredentials::resolve(
+        &GitCredentials::SshKey {
+            username: None,
+            private_key_path: "/nonexistent/id_ed25519".to_string(),
+            public_key_path: None,
+            passphrase_secret_id: None,
+        },
+        &store,
+    );
+    assert!(matches!(missing_key, Err(Error::Config(_))));
+}
+
+#[test]
+fn test_credential_provider_tries_each_method_once() {
+    let mut provider = CredentialProvider::new(Some(ResolvedCredentials::Token {
+        username: None,
+        token: "ghp_secret".to_string(),
+    }));
+    let url = "https://example.com/acme/app.git";
+
+    let cred = provider
+        .credentials(url, None, CredentialType::USER_PASS_PLAINTEXT)
+        .unwrap();
+    assert!(cred.has_username());
+
+    // 令牌被拒绝后不再重复提供，避免无限重试
+    let err = match provider.credentials(url, None, CredentialType::USER_PASS_PLAINTEXT) {
+        Ok(_) => panic!("token offered twice"),
+        Err(e) => e,
+    };
+    assert_eq!(err.code(), git2::ErrorCode::Auth);
+
+    // SSH 地址只请求用户名时使用 URL 中的用户名
+    let mut provider =
+        CredentialProvider::new(Some(ResolvedCredentials::SshAgent { username: None }));
+    let cred = provider
+        .credentials(
+            "ssh://deploy@example.com/app.git",
+            Some("deploy"),
+            CredentialType::USERNAME,
+        )
+        .unwrap();
+    assert_eq!(cred.credtype(), git2::CredentialType::USERNAME.bits());
+
+    // 令牌不适用于只接受 SSH 密钥的远程
+    let mut provider = CredentialProvider::new(Some(ResolvedCredentials::Token {
+        username: None,
+        token: "ghp_secret".to_string(),
+    }));
+    assert!(provider
+        .credentials(url, None, CredentialType::SSH_KEY)
+        .is_err());
+}
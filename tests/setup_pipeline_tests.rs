@@ -0,0 +1,204 @@
+use std::fs;
+use tauri_plugin_php::framework_detector::FrameworkDetector;
+use tauri_plugin_php::models::{DatabaseConfig, Framework, LaravelSetupOptions};
+use tauri_plugin_php::setup_pipeline::{
+    default_pipeline, load_overrides, SetupAction, SetupPipeline, SETUP_FILE,
+};
+
+mod common;
+
+#[test]
+fn test_default_laravel_pipeline_follows_options() {
+    let pipeline = default_pipeline(&Framework::Laravel, None, &LaravelSetupOptions::default());
+    assert_eq!(
+        pipeline.describe(Some("artisan")),
+        vec![
+            "composer install --no-dev (if composer.json exists)",
+            "cp .env.example .env (if .env.example exists)",
+            "php artisan key:generate --force (unless APP_KEY is set)",
+            "php artisan storage:link (unless public/storage exists)",
+            "php artisan optimize:clear",
+        ]
+    );
+
+    // 请求迁移和 SQLite 时加入数据库步骤
+    let options = LaravelSetupOptions {
+        migrate: true,
+        seed: true,
+        database: Some(DatabaseConfig {
+            connection: "sqlite".to_string(),
+            ..Default::default()
+        }),
+        clear_caches: false,
+        ..Default::default()
+    };
+    let names: Vec<String> = default_pipeline(&Framework::Laravel, None, &options)
+        .steps
+        .iter()
+        .map(|step| step.display_name())
+        .collect();
+    assert_eq!(
+        names,
+        vec![
+            "composer install",
+            ".env",
+            "database file",
+            "database",
+            "key:generate",
+            "storage:link",
+            "migrate",
+        ]
+    );
+}
+
+#[test]
+fn test_framework_info_describes_default_pipeline() {
+    let detector = FrameworkDetector::new();
+    let info = detector.get_framework_info(&Framework::CakePHP);
+    assert_eq!(info.console_script, Some("bin/cake.php"));
+    assert_eq!(
+        info.setup_commands,
+        default_pipeline(&Framework::CakePHP, None, &LaravelSetupOptions::default())
+            .describe(info.console_script)
+    );
+    assert!(info.setup_commands.contains(&"mkdir -p tmp".to_string()));
+
+    let plain = detector.get_framework_info(&Framework::Plain);
+    assert_eq!(plain.console_script, None);
+}
+
+#[test]
+fn test_overrides_skip_append_and_replace_steps() {
+    let temp_dir = common::create_temp_dir();
+    assert!(load_overrides(temp_dir.path()).unwrap().is_none());
+
+    fs::write(
+        temp_dir.path().join(SETUP_FILE),
+        r#"{
+    "skip": ["optimize:clear", "storage:link"],
+    "append": [
+        {"type": "console", "args": ["db:seed", "--class=DemoSeeder"]},
+        {"name": "uploads", "type": "writable_dir", "path": "storage/app/uploads"}
+    ]
+}"#,
+    )
+    .unwrap();
+    let mut pipeline = default_pipeline(&Framework::Laravel, None, &LaravelSetupOptions::default());
+    pipeline
+        .apply_overrides(load_overrides(temp_dir.path()).unwrap().unwrap())
+        .unwrap();
+    let names: Vec<String> = pipeline
+        .steps
+        .iter()
+        .map(|step| step.display_name())
+        .collect();
+    assert_eq!(
+        names,
+        vec![
+            "composer install",
+            ".env",
+            "key:generate",
+            "db:seed",
+            "uploads"
+        ]
+    );
+
+    // steps 替换框架默认步骤
+    fs::write(
+        temp_dir.path().join(SETUP_FILE),
+        r#"{"steps": [{"type": "composer", "action": "Install", "args": ["--prefer-dist"]}]}"#,
+    )
+    .unwrap();
+    let mut pipeline = default_pipeline(&Framework::Laravel, None, &LaravelSetupOptions::default());
+    pipeline
+        .apply_overrides(load_overrides(temp_dir.path()).unwrap().unwrap())
+        .unwrap();
+    assert_eq!(
+        pipeline.describe(None),
+        vec!["composer install --prefer-dist"]
+    );
+}
+
+#[test]
+fn test_sqlite_database_path() {
+    let sqlite = |database: &str| LaravelSetupOptions {
+        database: Some(DatabaseConfig {
+            connection: "sqlite".to_string(),
+            database: Some(database.to_string()),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let database_value = |pipeline: &SetupPipeline| {
+        pipeline
+            .steps
+            .iter()
+            .find_map(|step| match &step.action {
+                SetupAction::SetEnv { values, .. } => values.get("DB_DATABASE").cloned(),
+                _ => None,
+            })
+            .unwrap()
+    };
+
+    // 相对路径在项目内创建
+    let pipeline = default_pipeline(&Framework::Laravel, None, &sqlite("storage/app.sqlite"));
+    assert!(pipeline.validate().is_ok());
+    assert_eq!(database_value(&pipeline), "{project}/storage/app.sqlite");
+    assert!(pipeline
+        .steps
+        .iter()
+        .any(|step| step.display_name() == "database file"));
+
+    // 绝对路径原样使用，不创建文件
+    let temp_dir = common::create_temp_dir();
+    let external = temp_dir.path().join("app.sqlite");
+    let external = external.to_string_lossy().to_string();
+    let pipeline = default_pipeline(&Framework::Laravel, None, &sqlite(&external));
+    assert!(pipeline.validate().is_ok());
+    assert_eq!(database_value(&pipeline), external);
+    assert!(pipeline
+        .steps
+        .iter()
+        .all(|step| step.display_name() != "database file"));
+
+    // 离开项目的相对路径被拒绝
+    let pipeline = default_pipeline(&Framework::Laravel, None, &sqlite("../app.sqlite"));
+    assert!(pipeline.validate().is_err());
+    let manager = common::isolated_project_manager(temp_dir.path());
+    assert!(manager
+        .setup_pipeline(
+            temp_dir.path(),
+            &Framework::Laravel,
+            None,
+            &sqlite("../app.sqlite")
+        )
+        .is_err());
+}
+
+#[test]
+fn test_overrides_reject_paths_outside_project() {
+    let temp_dir = common::create_temp_dir();
+    let mut pipeline = default_pipeline(&Framework::Plain, None, &LaravelSetupOptions::default());
+
+    for step in [
+        r#"{"type": "copy_file", "from": "../secrets.env", "to": ".env"}"#,
+        r#"{"type": "create_file", "path": "/etc/passwd"}"#,
+        r#"{"type": "set_env", "file": "../.env", "values": {"A": "1"}}"#,
+    ] {
+        fs::write(
+            temp_dir.path().join(SETUP_FILE),
+            format!(r#"{{"append": [{}]}}"#, step),
+        )
+        .unwrap();
+        let overrides = load_overrides(temp_dir.path()).unwrap().unwrap();
+        assert!(pipeline.apply_overrides(overrides).is_err(), "{}", step);
+    }
+
+    // 格式错误的文件报告为配置错误
+    fs::write(
+        temp_dir.path().join(SETUP_FILE),
+        r#"{"append": [{"type": "rm"}]}"#,
+    )
+    .unwrap();
+    assert!(load_overrides(temp_dir.path()).is_err());
+}